@@ -0,0 +1,52 @@
+-- Schema from database/tp3_database.mwb. Tables are only created when missing so
+-- databases built from the Workbench model are picked up as they are.
+
+CREATE TABLE IF NOT EXISTS Ingredients (
+    idIngredient INT NOT NULL AUTO_INCREMENT,
+    Name VARCHAR(45) NOT NULL,
+    Proteins DOUBLE NOT NULL,
+    Carbs DOUBLE NOT NULL,
+    Fats DOUBLE NOT NULL,
+    PRIMARY KEY (idIngredient)
+);
+
+CREATE TABLE IF NOT EXISTS Recipes (
+    idRecipe INT NOT NULL AUTO_INCREMENT,
+    Name VARCHAR(45) NOT NULL,
+    Category VARCHAR(45) NOT NULL,
+    Instructions VARCHAR(1000) NOT NULL,
+    PRIMARY KEY (idRecipe)
+);
+
+CREATE TABLE IF NOT EXISTS MealPlans (
+    idMealPlan INT NOT NULL AUTO_INCREMENT,
+    Name VARCHAR(45) NOT NULL,
+    Category VARCHAR(45) NOT NULL,
+    PRIMARY KEY (idMealPlan)
+);
+
+CREATE TABLE IF NOT EXISTS RecipeIngredients (
+    idRecipeIngredient INT NOT NULL AUTO_INCREMENT,
+    Amount DOUBLE NOT NULL,
+    Unit VARCHAR(45) NOT NULL,
+    Ingredients_idIngredient INT NOT NULL,
+    Recipes_idRecipe INT NOT NULL,
+    PRIMARY KEY (idRecipeIngredient),
+    INDEX fk_RecipeIngredients_Ingredients1_idx (Ingredients_idIngredient),
+    INDEX fk_RecipeIngredients_Recipes1_idx (Recipes_idRecipe),
+    CONSTRAINT fk_RecipeIngredients_Ingredients1 FOREIGN KEY (Ingredients_idIngredient) REFERENCES Ingredients (idIngredient),
+    CONSTRAINT fk_RecipeIngredients_Recipes1 FOREIGN KEY (Recipes_idRecipe) REFERENCES Recipes (idRecipe)
+);
+
+CREATE TABLE IF NOT EXISTS RecipeMeals (
+    idRecipeMeals INT NOT NULL AUTO_INCREMENT,
+    Day VARCHAR(45) NOT NULL,
+    MealType VARCHAR(45) NOT NULL,
+    Recipes_idRecipe INT NOT NULL,
+    MealPlans_idMealPlan INT NOT NULL,
+    PRIMARY KEY (idRecipeMeals),
+    INDEX fk_RecipeMeals_MealPlans1_idx (MealPlans_idMealPlan),
+    INDEX fk_RecipeMeals_Recipes1_idx (Recipes_idRecipe),
+    CONSTRAINT fk_RecipeMeals_MealPlans1 FOREIGN KEY (MealPlans_idMealPlan) REFERENCES MealPlans (idMealPlan),
+    CONSTRAINT fk_RecipeMeals_Recipes1 FOREIGN KEY (Recipes_idRecipe) REFERENCES Recipes (idRecipe)
+);
@@ -0,0 +1,4 @@
+-- Dataset the ingredient was imported from, e.g. "fdc:171287" or "ciqual:25601".
+ALTER TABLE Ingredients
+    ADD COLUMN SourceId VARCHAR(45) NULL,
+    ADD UNIQUE INDEX SourceId_UNIQUE (SourceId);
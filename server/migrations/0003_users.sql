@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS Users (
+    idUser INT NOT NULL AUTO_INCREMENT,
+    Username VARCHAR(45) NOT NULL,
+    PasswordHash VARCHAR(255) NOT NULL,
+    CreatedAt DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (idUser),
+    UNIQUE INDEX Username_UNIQUE (Username)
+);
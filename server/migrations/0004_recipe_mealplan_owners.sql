@@ -0,0 +1,12 @@
+-- Recipes and meal plans created before user accounts have no owner and stay public.
+ALTER TABLE Recipes
+    ADD COLUMN Users_idUser INT NULL,
+    ADD COLUMN Visibility VARCHAR(16) NOT NULL DEFAULT 'public',
+    ADD INDEX fk_Recipes_Users1_idx (Users_idUser),
+    ADD CONSTRAINT fk_Recipes_Users1 FOREIGN KEY (Users_idUser) REFERENCES Users (idUser);
+
+ALTER TABLE MealPlans
+    ADD COLUMN Users_idUser INT NULL,
+    ADD COLUMN Visibility VARCHAR(16) NOT NULL DEFAULT 'public',
+    ADD INDEX fk_MealPlans_Users1_idx (Users_idUser),
+    ADD CONSTRAINT fk_MealPlans_Users1 FOREIGN KEY (Users_idUser) REFERENCES Users (idUser);
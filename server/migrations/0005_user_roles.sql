@@ -0,0 +1,5 @@
+ALTER TABLE Users ADD COLUMN Role VARCHAR(16) NOT NULL DEFAULT 'member';
+
+-- The oldest account administers the instance.
+UPDATE Users SET Role = 'admin'
+WHERE idUser = (SELECT idUser FROM (SELECT MIN(idUser) AS idUser FROM Users) AS FirstUser);
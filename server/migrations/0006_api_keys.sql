@@ -0,0 +1,15 @@
+CREATE TABLE IF NOT EXISTS ApiKeys (
+    idApiKey INT NOT NULL AUTO_INCREMENT,
+    Name VARCHAR(45) NOT NULL,
+    Prefix VARCHAR(16) NOT NULL,
+    KeyHash CHAR(64) NOT NULL,
+    Scope VARCHAR(16) NOT NULL,
+    Users_idUser INT NOT NULL,
+    CreatedAt DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    LastUsedAt DATETIME NULL,
+    RevokedAt DATETIME NULL,
+    PRIMARY KEY (idApiKey),
+    UNIQUE INDEX KeyHash_UNIQUE (KeyHash),
+    INDEX fk_ApiKeys_Users1_idx (Users_idUser),
+    CONSTRAINT fk_ApiKeys_Users1 FOREIGN KEY (Users_idUser) REFERENCES Users (idUser)
+);
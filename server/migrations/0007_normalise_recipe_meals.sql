@@ -0,0 +1,21 @@
+-- Days and meal types are stored with their English lowercase name. Days that
+-- cannot be recognised are left untouched and skipped when reading.
+UPDATE RecipeMeals SET Day = CASE
+    WHEN LOWER(TRIM(TRAILING '.' FROM TRIM(Day))) IN ('monday', 'mon', 'lunes', 'lun') THEN 'monday'
+    WHEN LOWER(TRIM(TRAILING '.' FROM TRIM(Day))) IN ('tuesday', 'tue', 'martes', 'mar') THEN 'tuesday'
+    WHEN LOWER(TRIM(TRAILING '.' FROM TRIM(Day))) IN ('wednesday', 'wed', 'miércoles', 'miercoles', 'mié', 'mie') THEN 'wednesday'
+    WHEN LOWER(TRIM(TRAILING '.' FROM TRIM(Day))) IN ('thursday', 'thu', 'jueves', 'jue') THEN 'thursday'
+    WHEN LOWER(TRIM(TRAILING '.' FROM TRIM(Day))) IN ('friday', 'fri', 'viernes', 'vie') THEN 'friday'
+    WHEN LOWER(TRIM(TRAILING '.' FROM TRIM(Day))) IN ('saturday', 'sat', 'sábado', 'sabado', 'sáb', 'sab') THEN 'saturday'
+    WHEN LOWER(TRIM(TRAILING '.' FROM TRIM(Day))) IN ('sunday', 'sun', 'domingo', 'dom') THEN 'sunday'
+    ELSE Day
+END;
+
+-- Meal types other than the built-in ones are kept as custom meal types.
+UPDATE RecipeMeals SET MealType = CASE
+    WHEN LOWER(TRIM(MealType)) IN ('breakfast', 'desayuno') THEN 'breakfast'
+    WHEN LOWER(TRIM(MealType)) IN ('lunch', 'almuerzo', 'comida') THEN 'lunch'
+    WHEN LOWER(TRIM(MealType)) IN ('snack', 'merienda') THEN 'snack'
+    WHEN LOWER(TRIM(MealType)) IN ('dinner', 'cena') THEN 'dinner'
+    ELSE LOWER(TRIM(MealType))
+END;
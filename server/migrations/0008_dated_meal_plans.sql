@@ -0,0 +1,9 @@
+-- Meal plans with a start date cover LengthDays consecutive days and their slots
+-- fall on calendar dates. Plans without one stay a generic, repeating week.
+ALTER TABLE MealPlans
+    ADD COLUMN StartDate DATE NULL,
+    ADD COLUMN LengthDays INT NOT NULL DEFAULT 7;
+
+ALTER TABLE RecipeMeals
+    ADD COLUMN Date DATE NULL,
+    ADD INDEX RecipeMeals_Date_idx (Date);
@@ -0,0 +1,7 @@
+-- Ingredient amounts of a recipe are for Servings people. A meal plan slot may
+-- cook a different number of servings; NULL keeps the recipe's own yield.
+ALTER TABLE Recipes
+    ADD COLUMN Servings INT NOT NULL DEFAULT 1;
+
+ALTER TABLE RecipeMeals
+    ADD COLUMN Servings INT NULL;
@@ -0,0 +1,24 @@
+-- A recipe can carry any number of tags. Tag names are stored trimmed and
+-- lowercased, so "Desayuno" and "desayuno " are the same tag.
+CREATE TABLE IF NOT EXISTS Tags (
+    idTag INT NOT NULL AUTO_INCREMENT,
+    Name VARCHAR(45) NOT NULL,
+    PRIMARY KEY (idTag),
+    UNIQUE INDEX Name_UNIQUE (Name)
+);
+
+CREATE TABLE IF NOT EXISTS RecipeTags (
+    Recipes_idRecipe INT NOT NULL,
+    Tags_idTag INT NOT NULL,
+    PRIMARY KEY (Recipes_idRecipe, Tags_idTag),
+    INDEX fk_RecipeTags_Tags1_idx (Tags_idTag),
+    CONSTRAINT fk_RecipeTags_Recipes1 FOREIGN KEY (Recipes_idRecipe) REFERENCES Recipes (idRecipe),
+    CONSTRAINT fk_RecipeTags_Tags1 FOREIGN KEY (Tags_idTag) REFERENCES Tags (idTag)
+);
+
+-- Every existing category becomes the first tag of its recipes.
+INSERT IGNORE INTO Tags (Name)
+SELECT DISTINCT LOWER(TRIM(Category)) FROM Recipes WHERE TRIM(Category) <> '';
+
+INSERT IGNORE INTO RecipeTags (Recipes_idRecipe, Tags_idTag)
+SELECT r.idRecipe, t.idTag FROM Recipes r JOIN Tags t ON t.Name = LOWER(TRIM(r.Category));
@@ -0,0 +1,21 @@
+-- Ordered instruction steps of a recipe, each with an optional duration and
+-- the recipe ingredients it uses. Instructions stays for free-text recipes.
+CREATE TABLE IF NOT EXISTS RecipeSteps (
+    idRecipeStep INT NOT NULL AUTO_INCREMENT,
+    Position INT NOT NULL,
+    Text VARCHAR(2000) NOT NULL,
+    DurationMinutes INT NULL,
+    Recipes_idRecipe INT NOT NULL,
+    PRIMARY KEY (idRecipeStep),
+    INDEX fk_RecipeSteps_Recipes1_idx (Recipes_idRecipe),
+    CONSTRAINT fk_RecipeSteps_Recipes1 FOREIGN KEY (Recipes_idRecipe) REFERENCES Recipes (idRecipe)
+);
+
+CREATE TABLE IF NOT EXISTS RecipeStepIngredients (
+    RecipeSteps_idRecipeStep INT NOT NULL,
+    Ingredients_idIngredient INT NOT NULL,
+    PRIMARY KEY (RecipeSteps_idRecipeStep, Ingredients_idIngredient),
+    INDEX fk_RecipeStepIngredients_Ingredients1_idx (Ingredients_idIngredient),
+    CONSTRAINT fk_RecipeStepIngredients_RecipeSteps1 FOREIGN KEY (RecipeSteps_idRecipeStep) REFERENCES RecipeSteps (idRecipeStep),
+    CONSTRAINT fk_RecipeStepIngredients_Ingredients1 FOREIGN KEY (Ingredients_idIngredient) REFERENCES Ingredients (idIngredient)
+);
@@ -0,0 +1,13 @@
+-- Recipes used inside other recipes, such as a sauce or a dough. Servings is
+-- how many servings of the component recipe go into the recipe.
+CREATE TABLE IF NOT EXISTS RecipeComponents (
+    idRecipeComponent INT NOT NULL AUTO_INCREMENT,
+    Servings DOUBLE NOT NULL,
+    Recipes_idRecipe INT NOT NULL,
+    Components_idRecipe INT NOT NULL,
+    PRIMARY KEY (idRecipeComponent),
+    INDEX fk_RecipeComponents_Recipes1_idx (Recipes_idRecipe),
+    INDEX fk_RecipeComponents_Recipes2_idx (Components_idRecipe),
+    CONSTRAINT fk_RecipeComponents_Recipes1 FOREIGN KEY (Recipes_idRecipe) REFERENCES Recipes (idRecipe),
+    CONSTRAINT fk_RecipeComponents_Recipes2 FOREIGN KEY (Components_idRecipe) REFERENCES Recipes (idRecipe)
+);
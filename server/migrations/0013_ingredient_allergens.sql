@@ -0,0 +1,4 @@
+-- Allergens an ingredient contains, as a comma-separated list of the EU major
+-- allergens (e.g. "gluten,milk"). Recipe allergens are derived from these.
+ALTER TABLE Ingredients
+    ADD COLUMN Allergens VARCHAR(255) NOT NULL DEFAULT '';
@@ -0,0 +1,8 @@
+-- What an ingredient is or contains that some diets leave out, as a
+-- comma-separated list (e.g. "dairy,eggs"), and the diets a meal plan follows
+-- (e.g. "vegetarian,gluten-free"). Recipe diets are derived from ingredients.
+ALTER TABLE Ingredients
+    ADD COLUMN DietAttributes VARCHAR(255) NOT NULL DEFAULT '';
+
+ALTER TABLE MealPlans
+    ADD COLUMN Diets VARCHAR(255) NOT NULL DEFAULT '';
@@ -0,0 +1,9 @@
+-- Allergens and diet attributes are NULL until they are given, so that an
+-- ingredient nobody has labelled is not taken for one that contains none.
+-- Empty lists stored so far may mean either, so they count as not given.
+ALTER TABLE Ingredients
+    MODIFY COLUMN Allergens VARCHAR(255) NULL DEFAULT NULL,
+    MODIFY COLUMN DietAttributes VARCHAR(255) NULL DEFAULT NULL;
+
+UPDATE Ingredients SET Allergens = NULL WHERE Allergens = '';
+UPDATE Ingredients SET DietAttributes = NULL WHERE DietAttributes = '';
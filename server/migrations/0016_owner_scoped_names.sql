@@ -0,0 +1,7 @@
+-- Recipe and meal plan names only have to be unique per owner, so that a name
+-- taken by someone else's private recipe or plan can still be used.
+ALTER TABLE Recipes
+    ADD UNIQUE INDEX Recipes_owner_name_UNIQUE (Users_idUser, Name);
+
+ALTER TABLE MealPlans
+    ADD UNIQUE INDEX MealPlans_owner_name_UNIQUE (Users_idUser, Name);
@@ -0,0 +1,52 @@
+use rocket::serde::json::Json;
+use rocket::http::Status;
+use rocket::{get, post, delete};
+use rocket::State;
+use validator::Validate;
+
+use crate::api::guards::{Authorized, UserAdmin};
+use crate::business::dtos::api_key_dto::{ApiKeyDTO, ApiKeyRequestDTO, NewApiKeyDTO};
+use crate::business::services::api_key_service::{ApiKeyError, ApiKeyServiceTrait};
+
+#[post("/admin/api-keys", data = "<api_key_data>")]
+pub async fn create_api_key(
+    api_key_data: Json<ApiKeyRequestDTO>,
+    user: Authorized<UserAdmin>,
+    api_key_service: &State<Box<dyn ApiKeyServiceTrait + Send + Sync>>,
+) -> Result<(Status, Json<NewApiKeyDTO>), Status> {
+    let request = api_key_data.into_inner();
+
+    if request.validate().is_err() {
+        return Err(Status::UnprocessableEntity);
+    }
+
+    match api_key_service.create_api_key(request, &user.caller()).await {
+        Ok(api_key) => Ok((Status::Created, Json(api_key))),
+        Err(ApiKeyError::ValidationError) => Err(Status::UnprocessableEntity),
+        Err(_) => Err(Status::InternalServerError)
+    }
+}
+
+#[get("/admin/api-keys")]
+pub async fn get_all_api_keys(
+    _user: Authorized<UserAdmin>,
+    api_key_service: &State<Box<dyn ApiKeyServiceTrait + Send + Sync>>,
+) -> Result<Json<Vec<ApiKeyDTO>>, Status> {
+    match api_key_service.get_all_api_keys().await {
+        Ok(api_keys) => Ok(Json(api_keys)),
+        Err(_) => Err(Status::InternalServerError)
+    }
+}
+
+#[delete("/admin/api-keys/<id>")]
+pub async fn revoke_api_key(
+    id: i64,
+    _user: Authorized<UserAdmin>,
+    api_key_service: &State<Box<dyn ApiKeyServiceTrait + Send + Sync>>,
+) -> Result<String, Status> {
+    match api_key_service.revoke_api_key(id).await {
+        Ok(()) => Ok("API key revoked successfully".to_string()),
+        Err(ApiKeyError::NotFound) => Err(Status::NotFound),
+        Err(_) => Err(Status::InternalServerError)
+    }
+}
@@ -0,0 +1,27 @@
+use std::sync::Arc;
+
+use rocket::serde::json::Json;
+use rocket::http::Status;
+use rocket::get;
+use rocket::State;
+
+use crate::api::fairings::shutdown::ShutdownState;
+use crate::business::dtos::health_dto::{LivenessDTO, ReadinessDTO};
+use crate::business::services::health_service::HealthServiceTrait;
+
+#[get("/live")]
+pub async fn live() -> Json<LivenessDTO> {
+    Json(LivenessDTO { status: "alive".to_string() })
+}
+
+#[get("/ready")]
+pub async fn ready(
+    shutdown: &State<Arc<ShutdownState>>,
+    health_service: &State<Box<dyn HealthServiceTrait + Send + Sync>>,
+) -> (Status, Json<ReadinessDTO>) {
+    let readiness = health_service.check_readiness(shutdown.is_draining()).await;
+
+    let status = if readiness.status == "ready" { Status::Ok } else { Status::ServiceUnavailable };
+
+    (status, Json(readiness))
+}
@@ -1,15 +1,18 @@
 use rocket::serde::json::Json;
-use rocket::http::Status;
+use rocket::http::{ContentType, Status};
+use rocket::data::{Data, Limits, ToByteUnit};
 use rocket::{get, post, put, delete};
 use rocket::State;
 use validator::Validate;
 
-use crate::business::dtos::ingredient_dto::IngredientDTO;
+use crate::api::guards::{Authorized, CatalogueWrite};
+use crate::business::dtos::ingredient_dto::{IngredientDTO, IngredientImportReport, IngredientLinesDTO, ParsedIngredientDTO};
 use crate::business::services::ingredient_service::{IngredientServiceTrait, IngredientError};
 
 #[post("/add/ingredient", data = "<ingredient_data>")]
 pub async fn add_ingredient(
     ingredient_data: Json<IngredientDTO>,
+    _user: Authorized<CatalogueWrite>,
     ingredient_service: &State<Box<dyn IngredientServiceTrait + Send + Sync>>,
 ) -> Result<String, Status> {
     let ingredient = ingredient_data.into_inner();
@@ -23,7 +26,8 @@ pub async fn add_ingredient(
         Err(IngredientError::ValidationError) => Err(Status::UnprocessableEntity),
         Err(IngredientError::DatabaseError(_)) => Err(Status::InternalServerError),
         Err(IngredientError::DuplicateNameError) => Err(Status::Conflict),
-        Err(IngredientError::NotFound) => Err(Status::NotFound)
+        Err(IngredientError::NotFound) => Err(Status::NotFound),
+        Err(_) => Err(Status::InternalServerError)
     }
 }
 
@@ -41,6 +45,7 @@ pub async fn get_all_ingredients(
 #[put("/update/ingredient", data = "<ingredient_data>")]
 pub async fn update_ingredient(
     ingredient_data: Json<IngredientDTO>,
+    _user: Authorized<CatalogueWrite>,
     ingredient_service: &State<Box<dyn IngredientServiceTrait + Send + Sync>>,
 ) -> Result<String, Status> {
     let ingredient = ingredient_data.into_inner();
@@ -54,13 +59,15 @@ pub async fn update_ingredient(
         Err(IngredientError::ValidationError) => Err(Status::UnprocessableEntity),
         Err(IngredientError::DatabaseError(_)) => Err(Status::InternalServerError),
         Err(IngredientError::DuplicateNameError) => Err(Status::Conflict),
-        Err(IngredientError::NotFound) => Err(Status::NotFound)
+        Err(IngredientError::NotFound) => Err(Status::NotFound),
+        Err(_) => Err(Status::InternalServerError)
     }
 }
 
 #[delete("/delete/ingredient/<id>")]
 pub async fn delete_ingredient(
     id: i64,
+    _user: Authorized<CatalogueWrite>,
     ingredient_service: &State<Box<dyn IngredientServiceTrait + Send + Sync>>,
 ) -> Result<String, Status> {
     match ingredient_service.delete_ingredient(id).await {
@@ -69,4 +76,55 @@ pub async fn delete_ingredient(
         Err(IngredientError::NotFound) => Err(Status::NotFound),
         _ => Err(Status::InternalServerError),
     }
-}
\ No newline at end of file
+}
+
+#[get("/ingredients.csv")]
+pub async fn export_ingredients_csv(
+    ingredient_service: &State<Box<dyn IngredientServiceTrait + Send + Sync>>,
+) -> Result<(ContentType, String), Status> {
+    match ingredient_service.export_ingredients_csv().await {
+        Ok(csv) => Ok((ContentType::CSV, csv)),
+        Err(_) => Err(Status::InternalServerError)
+    }
+}
+
+#[post("/ingredients/import", data = "<csv_data>")]
+pub async fn import_ingredients_csv(
+    csv_data: Data<'_>,
+    limits: &Limits,
+    _user: Authorized<CatalogueWrite>,
+    ingredient_service: &State<Box<dyn IngredientServiceTrait + Send + Sync>>,
+) -> Result<(Status, Json<IngredientImportReport>), Status> {
+    let limit = limits.get("file/csv").unwrap_or(1.mebibytes());
+
+    let csv = match csv_data.open(limit).into_string().await {
+        Ok(csv) if csv.is_complete() => csv.into_inner(),
+        Ok(_) => return Err(Status::PayloadTooLarge),
+        Err(_) => return Err(Status::BadRequest)
+    };
+
+    match ingredient_service.import_ingredients_csv(&csv).await {
+        Ok(report) => Ok((Status::Ok, Json(report))),
+        Err(IngredientError::ImportError(report)) => Ok((Status::UnprocessableEntity, Json(report))),
+        Err(IngredientError::DatabaseError(_)) => Err(Status::InternalServerError),
+        _ => Err(Status::InternalServerError)
+    }
+}
+
+#[post("/ingredients/parse", data = "<lines_data>")]
+pub async fn parse_ingredient_lines(
+    lines_data: Json<IngredientLinesDTO>,
+    ingredient_service: &State<Box<dyn IngredientServiceTrait + Send + Sync>>,
+) -> Result<Json<Vec<ParsedIngredientDTO>>, Status> {
+    let lines = lines_data.into_inner();
+
+    if lines.validate().is_err() {
+        return Err(Status::UnprocessableEntity);
+    }
+
+    match ingredient_service.parse_ingredient_lines(lines.lines).await {
+        Ok(parsed_lines) => Ok(Json(parsed_lines)),
+        Err(IngredientError::DatabaseError(_)) => Err(Status::InternalServerError),
+        _ => Err(Status::InternalServerError)
+    }
+}
@@ -1,15 +1,21 @@
+use chrono::{NaiveDate, NaiveTime};
 use rocket::serde::json::Json;
-use rocket::http::Status;
-use rocket::{get, post, put, delete};
+use rocket::http::{ContentType, Status};
+use rocket::{get, post, put, delete, FromForm};
 use rocket::State;
 use validator::Validate;
 
-use crate::business::dtos::mealplan_dto::MealPlanDTO;
+use crate::api::guards::{AuthenticatedUser, Authorized, MealPlanWrite};
+use crate::api::params::{IcsId, IsoDate};
+use crate::business::names;
+use crate::business::dtos::mealplan_dto::{MealPlanDietCheckDTO, MealPlanDTO, MealPlanFilter, MealPlanNutritionDTO, PlannedDayDTO, ShoppingListDTO};
+use crate::business::formats::ical::CalendarOptions;
 use crate::business::services::mealplan_service::{MealPlanServiceTrait, MealPlanError};
 
 #[post("/add/mealplan", data = "<mealplan_data>")]
 pub async fn add_mealplan(
     mealplan_data: Json<MealPlanDTO>,
+    user: Authorized<MealPlanWrite>,
     mealplan_service: &State<Box<dyn MealPlanServiceTrait + Send + Sync>>,
 ) -> Result<String, Status> {
     let mealplan = mealplan_data.into_inner();
@@ -18,21 +24,45 @@ pub async fn add_mealplan(
         return Err(Status::UnprocessableEntity);
     }
 
-    match mealplan_service.add_mealplan(mealplan).await {
+    match mealplan_service.add_mealplan(mealplan, &user.caller()).await {
         Ok(()) => Ok("mealplan added successfully".to_string()),
         Err(MealPlanError::ValidationError) => Err(Status::UnprocessableEntity),
         Err(MealPlanError::DatabaseError(_)) => Err(Status::InternalServerError),
         Err(MealPlanError::DuplicateNameError) => Err(Status::Conflict),
         Err(MealPlanError::NotFound) => Err(Status::NotFound),
-        Err(MealPlanError::NotImplemented) => Err(Status::NotImplemented)
+        Err(MealPlanError::Forbidden) => Err(Status::Forbidden)
     }
 }
 
-#[get("/get/mealplan")]
+#[derive(Debug, FromForm)]
+pub struct MealPlanQuery {
+    exclude_allergens: Option<String>
+}
+
+impl MealPlanQuery {
+    /// `exclude_allergens` is a comma-separated list.
+    fn into_filter(self) -> Option<MealPlanFilter> {
+        let mut filter = MealPlanFilter::default();
+
+        if let Some(exclude_allergens) = self.exclude_allergens {
+            filter.exclude_allergens = names::parse_list(&exclude_allergens).ok()?;
+        }
+
+        Some(filter)
+    }
+}
+
+#[get("/get/mealplan?<query..>")]
 pub async fn get_all_mealplans(
+    query: MealPlanQuery,
+    user: Option<AuthenticatedUser>,
     mealplan_service: &State<Box<dyn MealPlanServiceTrait + Send + Sync>>,
 ) -> Result<Json<Vec<MealPlanDTO>>, Status> {
-    match mealplan_service.get_all_mealplan().await {
+    let Some(filter) = query.into_filter() else {
+        return Err(Status::UnprocessableEntity);
+    };
+
+    match mealplan_service.get_all_mealplan(user.map(|user| user.caller()).as_ref(), &filter).await {
         Ok(mealplans) => Ok(Json(mealplans)),
         Err(MealPlanError::DatabaseError(_)) => Err(Status::InternalServerError),
         _ => Err(Status::InternalServerError)
@@ -42,12 +72,15 @@ pub async fn get_all_mealplans(
 #[delete("/delete/mealplan/<id>")]
 pub async fn delete_mealplan(
     id: i64,
+    user: Authorized<MealPlanWrite>,
     mealplan_service: &State<Box<dyn MealPlanServiceTrait + Send + Sync>>,
 ) -> Result<String, Status> {
-    match mealplan_service.delete_mealplan(id).await {
+    match mealplan_service.delete_mealplan(id, &user.caller()).await {
         Ok(()) => Ok("Meal plan deleted successfully".to_string()),
         Err(MealPlanError::DatabaseError(_)) => Err(Status::InternalServerError),
+        Err(MealPlanError::DuplicateNameError) => Err(Status::Conflict),
         Err(MealPlanError::NotFound) => Err(Status::NotFound),
+        Err(MealPlanError::Forbidden) => Err(Status::Forbidden),
         _ => Err(Status::InternalServerError),
     }
 }
@@ -55,6 +88,7 @@ pub async fn delete_mealplan(
 #[put("/update/mealplan", data = "<mealplan_data>")]
 pub async fn update_mealplan(
     mealplan_data: Json<MealPlanDTO>,
+    user: Authorized<MealPlanWrite>,
     mealplan_service: &State<Box<dyn MealPlanServiceTrait + Send + Sync>>,
 ) -> Result<String, Status> {
     let mealplan = mealplan_data.into_inner();
@@ -63,12 +97,122 @@ pub async fn update_mealplan(
         return Err(Status::UnprocessableEntity);
     }
 
-    match mealplan_service.update_mealplan(mealplan).await {
+    match mealplan_service.update_mealplan(mealplan, &user.caller()).await {
         Ok(()) => Ok("Meal plan updated successfully".to_string()),
         Err(MealPlanError::ValidationError) => Err(Status::UnprocessableEntity),
         Err(MealPlanError::DatabaseError(_)) => Err(Status::InternalServerError),
         Err(MealPlanError::DuplicateNameError) => Err(Status::Conflict),
         Err(MealPlanError::NotFound) => Err(Status::NotFound),
-        Err(MealPlanError::NotImplemented) => Err(Status::NotImplemented)
+        Err(MealPlanError::Forbidden) => Err(Status::Forbidden)
     }
-}
\ No newline at end of file
+}
+
+#[derive(Debug, FromForm)]
+pub struct CalendarQuery {
+    week_start: Option<String>,
+    breakfast: Option<String>,
+    lunch: Option<String>,
+    snack: Option<String>,
+    dinner: Option<String>,
+    duration: Option<u32>
+}
+
+impl CalendarQuery {
+    fn into_options(self) -> Option<CalendarOptions> {
+        let mut options = CalendarOptions::default();
+
+        let parse_time = |time: &str| NaiveTime::parse_from_str(time, "%H:%M").ok();
+
+        if let Some(week_start) = self.week_start {
+            options.week_start = NaiveDate::parse_from_str(&week_start, "%Y-%m-%d").ok()?;
+        }
+        if let Some(breakfast) = self.breakfast {
+            options.breakfast = parse_time(&breakfast)?;
+        }
+        if let Some(lunch) = self.lunch {
+            options.lunch = parse_time(&lunch)?;
+        }
+        if let Some(snack) = self.snack {
+            options.snack = parse_time(&snack)?;
+        }
+        if let Some(dinner) = self.dinner {
+            options.dinner = parse_time(&dinner)?;
+        }
+        if let Some(duration) = self.duration {
+            options.duration_minutes = i64::from(duration);
+        }
+
+        Some(options)
+    }
+}
+
+#[get("/mealplan/<id>?<calendar..>")]
+pub async fn export_mealplan_ical(
+    id: IcsId,
+    calendar: CalendarQuery,
+    user: Option<AuthenticatedUser>,
+    mealplan_service: &State<Box<dyn MealPlanServiceTrait + Send + Sync>>,
+) -> Result<(ContentType, String), Status> {
+    let Some(options) = calendar.into_options() else {
+        return Err(Status::UnprocessableEntity);
+    };
+
+    match mealplan_service.export_mealplan_ical(id.0, user.map(|user| user.caller()).as_ref(), options).await {
+        Ok(calendar) => Ok((ContentType::Calendar, calendar)),
+        Err(MealPlanError::NotFound) => Err(Status::NotFound),
+        _ => Err(Status::InternalServerError)
+    }
+}
+
+/// The caller's own meals; other users' plans are left out even when visible.
+#[get("/mealplan/on/<date>")]
+pub async fn get_meals_on(
+    date: IsoDate,
+    user: AuthenticatedUser,
+    mealplan_service: &State<Box<dyn MealPlanServiceTrait + Send + Sync>>,
+) -> Result<Json<PlannedDayDTO>, Status> {
+    match mealplan_service.get_meals_on(date.0, &user.caller()).await {
+        Ok(planned_day) => Ok(Json(planned_day)),
+        _ => Err(Status::InternalServerError)
+    }
+}
+
+/// Ranked after `/mealplan/on/<date>`, whose path overlaps.
+#[get("/mealplan/<id>/shopping-list", rank = 2)]
+pub async fn get_shopping_list(
+    id: i64,
+    user: Option<AuthenticatedUser>,
+    mealplan_service: &State<Box<dyn MealPlanServiceTrait + Send + Sync>>,
+) -> Result<Json<ShoppingListDTO>, Status> {
+    match mealplan_service.get_shopping_list(id, user.map(|user| user.caller()).as_ref()).await {
+        Ok(shopping_list) => Ok(Json(shopping_list)),
+        Err(MealPlanError::NotFound) => Err(Status::NotFound),
+        _ => Err(Status::InternalServerError)
+    }
+}
+
+#[get("/mealplan/<id>/nutrition", rank = 2)]
+pub async fn get_mealplan_nutrition(
+    id: i64,
+    user: Option<AuthenticatedUser>,
+    mealplan_service: &State<Box<dyn MealPlanServiceTrait + Send + Sync>>,
+) -> Result<Json<MealPlanNutritionDTO>, Status> {
+    match mealplan_service.get_nutrition(id, user.map(|user| user.caller()).as_ref()).await {
+        Ok(nutrition) => Ok(Json(nutrition)),
+        Err(MealPlanError::NotFound) => Err(Status::NotFound),
+        _ => Err(Status::InternalServerError)
+    }
+}
+
+#[get("/mealplan/<id>/diet-check", rank = 2)]
+pub async fn check_mealplan_diets(
+    id: i64,
+    user: Option<AuthenticatedUser>,
+    mealplan_service: &State<Box<dyn MealPlanServiceTrait + Send + Sync>>,
+) -> Result<Json<MealPlanDietCheckDTO>, Status> {
+    match mealplan_service.check_diets(id, user.map(|user| user.caller()).as_ref()).await {
+        Ok(diet_check) => Ok(Json(diet_check)),
+        Err(MealPlanError::NotFound) => Err(Status::NotFound),
+        _ => Err(Status::InternalServerError)
+    }
+}
@@ -0,0 +1,12 @@
+use rocket::http::ContentType;
+use rocket::get;
+use rocket::State;
+
+use crate::business::services::metrics_service::MetricsServiceTrait;
+
+#[get("/metrics")]
+pub async fn get_metrics(
+    metrics_service: &State<Box<dyn MetricsServiceTrait + Send + Sync>>,
+) -> (ContentType, String) {
+    (ContentType::new("text", "plain").with_params(("version", "0.0.4")), metrics_service.render_metrics().await)
+}
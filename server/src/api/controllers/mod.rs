@@ -1,4 +1,9 @@
 pub mod ingredient_controller;
 pub mod recipe_controller;
 pub mod mealplan_controller;
+pub mod tag_controller;
+pub mod user_controller;
+pub mod api_key_controller;
+pub mod health_controller;
+pub mod metrics_controller;
 pub mod routes;
\ No newline at end of file
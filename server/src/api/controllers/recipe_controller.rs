@@ -1,15 +1,21 @@
-use rocket::serde::json::Json;
-use rocket::http::Status;
-use rocket::{get, post, put, delete};
+use rocket::serde::json::{Json, Value};
+use rocket::http::{ContentType, Status};
+use rocket::{get, post, put, delete, FromForm};
 use rocket::State;
 use validator::Validate;
 
-use crate::business::dtos::recipe_dto::RecipeDTO;
+use crate::api::guards::{AuthenticatedUser, Authorized, RecipeWrite};
+use crate::api::params::JsonLdId;
+use crate::business::dtos::recipe_dto::{RecipeDTO, RecipeFilter, RecipeImportReport, TagMatch};
+use crate::business::names;
+use crate::business::dtos::tag_dto;
+use crate::business::formats::jsonld::RecipeJsonLd;
 use crate::business::services::recipe_service::{RecipeServiceTrait, RecipeError};
 
 #[post("/add/recipe", data = "<recipe_data>")]
 pub async fn add_recipe(
     recipe_data: Json<RecipeDTO>,
+    user: Authorized<RecipeWrite>,
     recipe_service: &State<Box<dyn RecipeServiceTrait + Send + Sync>>,
 ) -> Result<String, Status> {
     let recipe = recipe_data.into_inner();
@@ -18,21 +24,56 @@ pub async fn add_recipe(
         return Err(Status::UnprocessableEntity);
     }
 
-    match recipe_service.add_recipe(recipe).await {
+    match recipe_service.add_recipe(recipe, &user.caller()).await {
         Ok(()) => Ok("Recipe added successfully".to_string()),
         Err(RecipeError::ValidationError) => Err(Status::UnprocessableEntity),
         Err(RecipeError::DatabaseError(_)) => Err(Status::InternalServerError),
         Err(RecipeError::DuplicateNameError) => Err(Status::Conflict),
         Err(RecipeError::NotFound) => Err(Status::NotFound),
-        Err(RecipeError::NotImplemented) => Err(Status::NotImplemented)
+        Err(RecipeError::Forbidden) => Err(Status::Forbidden),
+        _ => Err(Status::InternalServerError)
+    }
+}
+
+#[derive(Debug, FromForm)]
+pub struct RecipeQuery {
+    tags: Option<String>,
+    #[field(name = "match")]
+    tag_match: Option<String>,
+    exclude_allergens: Option<String>
+}
+
+impl RecipeQuery {
+    /// `tags` and `exclude_allergens` are comma-separated lists; `match` is
+    /// `any` (the default) or `all`.
+    fn into_filter(self) -> Option<RecipeFilter> {
+        let mut filter = RecipeFilter::default();
+
+        if let Some(tags) = self.tags {
+            filter.tags = tags.split(',').map(tag_dto::normalise_name).filter(|tag| !tag.is_empty()).collect();
+        }
+        if let Some(tag_match) = self.tag_match {
+            filter.tag_match = TagMatch::parse(&tag_match)?;
+        }
+        if let Some(exclude_allergens) = self.exclude_allergens {
+            filter.exclude_allergens = names::parse_list(&exclude_allergens).ok()?;
+        }
+
+        Some(filter)
     }
 }
 
-#[get("/get/recipe")]
+#[get("/get/recipe?<query..>")]
 pub async fn get_all_recipes(
+    query: RecipeQuery,
+    user: Option<AuthenticatedUser>,
     recipe_service: &State<Box<dyn RecipeServiceTrait + Send + Sync>>,
 ) -> Result<Json<Vec<RecipeDTO>>, Status> {
-    match recipe_service.get_all_recipe().await {
+    let Some(filter) = query.into_filter() else {
+        return Err(Status::UnprocessableEntity);
+    };
+
+    match recipe_service.get_all_recipe(user.map(|user| user.caller()).as_ref(), &filter).await {
         Ok(recipes) => Ok(Json(recipes)),
         Err(RecipeError::DatabaseError(_)) => Err(Status::InternalServerError),
         _ => Err(Status::InternalServerError)
@@ -42,12 +83,15 @@ pub async fn get_all_recipes(
 #[delete("/delete/recipe/<id>")]
 pub async fn delete_recipe(
     id: i64,
+    user: Authorized<RecipeWrite>,
     recipe_service: &State<Box<dyn RecipeServiceTrait + Send + Sync>>,
 ) -> Result<String, Status> {
-    match recipe_service.delete_recipe(id).await {
+    match recipe_service.delete_recipe(id, &user.caller()).await {
         Ok(()) => Ok("Recipe deleted successfully".to_string()),
         Err(RecipeError::DatabaseError(_)) => Err(Status::InternalServerError),
+        Err(RecipeError::InUse) => Err(Status::Conflict),
         Err(RecipeError::NotFound) => Err(Status::NotFound),
+        Err(RecipeError::Forbidden) => Err(Status::Forbidden),
         _ => Err(Status::InternalServerError),
     }
 }
@@ -55,6 +99,7 @@ pub async fn delete_recipe(
 #[put("/update/recipe", data = "<recipe_data>")]
 pub async fn update_recipe(
     recipe_data: Json<RecipeDTO>,
+    user: Authorized<RecipeWrite>,
     recipe_service: &State<Box<dyn RecipeServiceTrait + Send + Sync>>,
 ) -> Result<String, Status> {
     let recipe = recipe_data.into_inner();
@@ -63,12 +108,67 @@ pub async fn update_recipe(
         return Err(Status::UnprocessableEntity);
     }
 
-    match recipe_service.update_recipe(recipe).await {
+    match recipe_service.update_recipe(recipe, &user.caller()).await {
         Ok(()) => Ok("Recipe updated successfully".to_string()),
         Err(RecipeError::ValidationError) => Err(Status::UnprocessableEntity),
         Err(RecipeError::DatabaseError(_)) => Err(Status::InternalServerError),
         Err(RecipeError::DuplicateNameError) => Err(Status::Conflict),
         Err(RecipeError::NotFound) => Err(Status::NotFound),
-        Err(RecipeError::NotImplemented) => Err(Status::NotImplemented)
+        Err(RecipeError::Forbidden) => Err(Status::Forbidden),
+        _ => Err(Status::InternalServerError)
     }
-}
\ No newline at end of file
+}
+
+#[get("/recipe/<id>")]
+pub async fn export_recipe_jsonld(
+    id: JsonLdId,
+    user: Option<AuthenticatedUser>,
+    recipe_service: &State<Box<dyn RecipeServiceTrait + Send + Sync>>,
+) -> Result<(ContentType, Json<RecipeJsonLd>), Status> {
+    match recipe_service.export_recipe_jsonld(id.0, user.map(|user| user.caller()).as_ref()).await {
+        Ok(recipe) => Ok((ContentType::new("application", "ld+json"), Json(recipe))),
+        Err(RecipeError::NotFound) => Err(Status::NotFound),
+        _ => Err(Status::InternalServerError)
+    }
+}
+
+/// Ranked after the JSON-LD export, which takes `<id>.jsonld`. Without
+/// `servings` the recipe comes with its own yield.
+#[get("/recipe/<id>?<servings>", rank = 2)]
+pub async fn get_recipe(
+    id: i64,
+    servings: Option<u32>,
+    user: Option<AuthenticatedUser>,
+    recipe_service: &State<Box<dyn RecipeServiceTrait + Send + Sync>>,
+) -> Result<Json<RecipeDTO>, Status> {
+    let caller = user.map(|user| user.caller());
+
+    let result = match servings {
+        Some(servings) => recipe_service.get_scaled_recipe(id, servings, caller.as_ref()).await,
+        None => recipe_service.get_recipe(id, caller.as_ref()).await
+    };
+
+    match result {
+        Ok(recipe) => Ok(Json(recipe)),
+        Err(RecipeError::ValidationError) => Err(Status::UnprocessableEntity),
+        Err(RecipeError::NotFound) => Err(Status::NotFound),
+        _ => Err(Status::InternalServerError)
+    }
+}
+
+#[post("/recipes/import/jsonld", data = "<document>")]
+pub async fn import_recipe_jsonld(
+    document: Json<Value>,
+    user: Authorized<RecipeWrite>,
+    recipe_service: &State<Box<dyn RecipeServiceTrait + Send + Sync>>,
+) -> Result<Json<RecipeImportReport>, Status> {
+    match recipe_service.import_recipe_jsonld(document.into_inner(), &user.caller()).await {
+        Ok(report) => Ok(Json(report)),
+        Err(RecipeError::ValidationError) => Err(Status::UnprocessableEntity),
+        Err(RecipeError::DatabaseError(_)) => Err(Status::InternalServerError),
+        Err(RecipeError::DuplicateNameError) => Err(Status::Conflict),
+        Err(RecipeError::NotFound) => Err(Status::NotFound),
+        Err(RecipeError::Forbidden) => Err(Status::Forbidden),
+        _ => Err(Status::InternalServerError)
+    }
+}
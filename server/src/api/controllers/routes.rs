@@ -3,12 +3,33 @@ use rocket::Route;
 
 use crate::api::controllers::mealplan_controller::delete_mealplan;
 use crate::api::controllers::mealplan_controller::update_mealplan;
-use crate::api::controllers::mealplan_controller::{ add_mealplan, get_all_mealplans };
-use crate::api::controllers::recipe_controller::{ get_all_recipes, add_recipe, update_recipe, delete_recipe };
-use crate::api::controllers::ingredient_controller::{add_ingredient, get_all_ingredients, update_ingredient, delete_ingredient};
+use crate::api::controllers::mealplan_controller::{ add_mealplan, get_all_mealplans, export_mealplan_ical, get_meals_on, get_shopping_list, get_mealplan_nutrition, check_mealplan_diets };
+use crate::api::controllers::recipe_controller::{ get_all_recipes, add_recipe, update_recipe, delete_recipe, get_recipe, export_recipe_jsonld, import_recipe_jsonld };
+use crate::api::controllers::tag_controller::{ add_tag, get_all_tags, update_tag, delete_tag };
+use crate::api::controllers::api_key_controller::{ create_api_key, get_all_api_keys, revoke_api_key };
+use crate::api::controllers::user_controller::{ register, login, logout, get_all_users, set_user_role };
+use crate::api::controllers::health_controller::{ live, ready };
+use crate::api::controllers::metrics_controller::get_metrics;
+use crate::api::controllers::ingredient_controller::{add_ingredient, get_all_ingredients, update_ingredient, delete_ingredient, export_ingredients_csv, import_ingredients_csv, parse_ingredient_lines};
 
 pub fn routes() -> Vec<Route> {
-    routes![add_ingredient, get_all_ingredients, update_ingredient, delete_ingredient, 
+    routes![add_ingredient, get_all_ingredients, update_ingredient, delete_ingredient,
+            export_ingredients_csv, import_ingredients_csv, parse_ingredient_lines,
             add_recipe, get_all_recipes, update_recipe, delete_recipe,
-            add_mealplan, get_all_mealplans, update_mealplan, delete_mealplan]
+            get_recipe, export_recipe_jsonld, import_recipe_jsonld,
+            add_tag, get_all_tags, update_tag, delete_tag,
+            add_mealplan, get_all_mealplans, update_mealplan, delete_mealplan,
+            export_mealplan_ical, get_meals_on, get_shopping_list, get_mealplan_nutrition, check_mealplan_diets,
+            register, login, logout, get_all_users, set_user_role,
+            create_api_key, get_all_api_keys, revoke_api_key]
+}
+
+/// Probes for orchestrators, mounted outside `/api`.
+pub fn health_routes() -> Vec<Route> {
+    routes![live, ready]
+}
+
+/// The Prometheus scrape endpoint, mounted at the root.
+pub fn metrics_routes() -> Vec<Route> {
+    routes![get_metrics]
 }
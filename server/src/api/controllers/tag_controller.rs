@@ -0,0 +1,79 @@
+use rocket::serde::json::Json;
+use rocket::http::Status;
+use rocket::{get, post, put, delete};
+use rocket::State;
+use validator::Validate;
+
+use crate::api::guards::{Authorized, CatalogueWrite};
+use crate::business::dtos::tag_dto::TagDTO;
+use crate::business::services::tag_service::{TagServiceTrait, TagError};
+
+#[post("/add/tag", data = "<tag_data>")]
+pub async fn add_tag(
+    tag_data: Json<TagDTO>,
+    _user: Authorized<CatalogueWrite>,
+    tag_service: &State<Box<dyn TagServiceTrait + Send + Sync>>,
+) -> Result<String, Status> {
+    let tag = tag_data.into_inner();
+
+    if tag.validate().is_err() {
+        return Err(Status::UnprocessableEntity);
+    }
+
+    match tag_service.add_tag(tag).await {
+        Ok(()) => Ok("Tag added successfully".to_string()),
+        Err(TagError::ValidationError) => Err(Status::UnprocessableEntity),
+        Err(TagError::DatabaseError(_)) => Err(Status::InternalServerError),
+        Err(TagError::DuplicateNameError) => Err(Status::Conflict),
+        Err(TagError::NotFound) => Err(Status::NotFound),
+        _ => Err(Status::InternalServerError)
+    }
+}
+
+#[get("/get/tag")]
+pub async fn get_all_tags(
+    tag_service: &State<Box<dyn TagServiceTrait + Send + Sync>>,
+) -> Result<Json<Vec<TagDTO>>, Status> {
+    match tag_service.get_all_tags().await {
+        Ok(tags) => Ok(Json(tags)),
+        Err(TagError::DatabaseError(_)) => Err(Status::InternalServerError),
+        _ => Err(Status::InternalServerError)
+    }
+}
+
+#[put("/update/tag", data = "<tag_data>")]
+pub async fn update_tag(
+    tag_data: Json<TagDTO>,
+    _user: Authorized<CatalogueWrite>,
+    tag_service: &State<Box<dyn TagServiceTrait + Send + Sync>>,
+) -> Result<String, Status> {
+    let tag = tag_data.into_inner();
+
+    if tag.validate().is_err() {
+        return Err(Status::UnprocessableEntity);
+    }
+
+    match tag_service.update_tag(tag).await {
+        Ok(()) => Ok("Tag updated successfully".to_string()),
+        Err(TagError::ValidationError) => Err(Status::UnprocessableEntity),
+        Err(TagError::DatabaseError(_)) => Err(Status::InternalServerError),
+        Err(TagError::DuplicateNameError) => Err(Status::Conflict),
+        Err(TagError::NotFound) => Err(Status::NotFound),
+        _ => Err(Status::InternalServerError)
+    }
+}
+
+#[delete("/delete/tag/<id>")]
+pub async fn delete_tag(
+    id: i64,
+    _user: Authorized<CatalogueWrite>,
+    tag_service: &State<Box<dyn TagServiceTrait + Send + Sync>>,
+) -> Result<String, Status> {
+    match tag_service.delete_tag(id).await {
+        Ok(()) => Ok("Tag deleted successfully".to_string()),
+        Err(TagError::DatabaseError(_)) => Err(Status::InternalServerError),
+        Err(TagError::NotFound) => Err(Status::NotFound),
+        Err(TagError::InUse) => Err(Status::Conflict),
+        _ => Err(Status::InternalServerError)
+    }
+}
@@ -0,0 +1,90 @@
+use rocket::serde::json::Json;
+use rocket::http::{Cookie, CookieJar, SameSite, Status};
+use rocket::{get, post, put};
+use rocket::State;
+use rocket::time::Duration;
+use validator::Validate;
+
+use crate::api::guards::{Authorized, UserAdmin, AUTH_COOKIE};
+use crate::business::dtos::user_dto::{RoleDTO, TokenDTO, UserCredentialsDTO, UserDTO};
+use crate::business::services::user_service::{UserError, UserServiceTrait};
+use crate::config::CorsConfig;
+
+#[post("/auth/register", data = "<credentials_data>")]
+pub async fn register(
+    credentials_data: Json<UserCredentialsDTO>,
+    user_service: &State<Box<dyn UserServiceTrait + Send + Sync>>,
+) -> Result<Json<UserDTO>, Status> {
+    let credentials = credentials_data.into_inner();
+
+    if credentials.validate().is_err() {
+        return Err(Status::UnprocessableEntity);
+    }
+
+    match user_service.register(credentials).await {
+        Ok(user) => Ok(Json(user)),
+        Err(UserError::ValidationError) => Err(Status::UnprocessableEntity),
+        Err(UserError::DuplicateNameError) => Err(Status::Conflict),
+        Err(UserError::RegistrationClosed) => Err(Status::Forbidden),
+        Err(_) => Err(Status::InternalServerError)
+    }
+}
+
+#[post("/auth/login", data = "<credentials_data>")]
+pub async fn login(
+    credentials_data: Json<UserCredentialsDTO>,
+    cookies: &CookieJar<'_>,
+    cors: &State<CorsConfig>,
+    user_service: &State<Box<dyn UserServiceTrait + Send + Sync>>,
+) -> Result<Json<TokenDTO>, Status> {
+    // Browsers only send cookies on cross-site requests when they are `SameSite=None`
+    // and `Secure`, which frontends on another origin need with credentials enabled.
+    let cross_site = cors.allow_credentials;
+
+    match user_service.login(credentials_data.into_inner()).await {
+        Ok(token) => {
+            cookies.add_private(
+                Cookie::build((AUTH_COOKIE, token.token.clone()))
+                    .http_only(true)
+                    .same_site(if cross_site { SameSite::None } else { SameSite::Lax })
+                    .secure(cross_site)
+                    .max_age(Duration::seconds(token.expires_in))
+            );
+            Ok(Json(token))
+        }
+        Err(UserError::InvalidCredentials) => Err(Status::Unauthorized),
+        Err(_) => Err(Status::InternalServerError)
+    }
+}
+
+#[post("/auth/logout")]
+pub async fn logout(cookies: &CookieJar<'_>) -> String {
+    cookies.remove_private(AUTH_COOKIE);
+    "Logged out successfully".to_string()
+}
+
+#[get("/admin/users")]
+pub async fn get_all_users(
+    _user: Authorized<UserAdmin>,
+    user_service: &State<Box<dyn UserServiceTrait + Send + Sync>>,
+) -> Result<Json<Vec<UserDTO>>, Status> {
+    match user_service.get_all_users().await {
+        Ok(users) => Ok(Json(users)),
+        Err(_) => Err(Status::InternalServerError)
+    }
+}
+
+#[put("/admin/users/<id>/role", data = "<role_data>")]
+pub async fn set_user_role(
+    id: i64,
+    role_data: Json<RoleDTO>,
+    _user: Authorized<UserAdmin>,
+    user_service: &State<Box<dyn UserServiceTrait + Send + Sync>>,
+) -> Result<String, Status> {
+    match user_service.set_role(id, role_data.into_inner().role).await {
+        Ok(()) => Ok("Role updated successfully".to_string()),
+        Err(UserError::NotFound) => Err(Status::NotFound),
+        Err(UserError::LastAdminError) => Err(Status::Conflict),
+        Err(_) => Err(Status::InternalServerError)
+    }
+}
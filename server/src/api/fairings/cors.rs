@@ -0,0 +1,143 @@
+use std::collections::BTreeMap;
+
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::{Method, Status};
+use rocket::route::{Handler, Outcome};
+use rocket::{Build, Data, Request, Response, Rocket, Route};
+
+use crate::config::CorsConfig;
+
+/// Response headers a browser frontend may read besides the safelisted ones.
+const EXPOSED_HEADERS: &str = "X-Request-Id, RateLimit-Limit, RateLimit-Remaining, RateLimit-Reset, Retry-After";
+
+/// Answers cross-origin requests from the configured origins. Every mounted
+/// route gets an `OPTIONS` route so preflights succeed, and the CORS headers are
+/// added on the way out. Requests from other origins get no CORS headers, which
+/// makes the browser block them.
+pub struct Cors {
+    config: CorsConfig,
+    allow_methods: String,
+    allow_headers: String
+}
+
+impl Cors {
+    pub fn new(config: CorsConfig) -> Self {
+        Cors {
+            allow_methods: config.allowed_methods.join(", "),
+            allow_headers: config.allowed_headers.join(", "),
+            config
+        }
+    }
+
+    fn allows_any_origin(&self) -> bool {
+        self.config.allowed_origins.iter().any(|origin| origin == "*")
+    }
+
+    fn allows_origin(&self, origin: &str) -> bool {
+        self.allows_any_origin() || self.config.allowed_origins.iter().any(|allowed| allowed.eq_ignore_ascii_case(origin))
+    }
+}
+
+/// Answers a preflight with the methods routed at its path.
+#[derive(Clone)]
+struct Preflight {
+    allow: String
+}
+
+#[rocket::async_trait]
+impl Handler for Preflight {
+    async fn handle<'r>(&self, _: &'r Request<'_>, _: Data<'r>) -> Outcome<'r> {
+        Outcome::Success(
+            Response::build()
+                .status(Status::NoContent)
+                .raw_header("Allow", self.allow.clone())
+                .finalize()
+        )
+    }
+}
+
+/// The path of a route with its parameters unnamed, so `/recipe/<id>` and
+/// `/recipe/<name>` share one preflight route.
+fn path_shape(route: &Route) -> String {
+    route.uri.path()
+        .split('/')
+        .map(|segment| match segment {
+            _ if segment.starts_with('<') && segment.ends_with("..>") => "<_..>",
+            _ if segment.starts_with('<') => "<_>",
+            _ => segment
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// One `OPTIONS` route per path of `routes`. Each gets its own rank, so paths
+/// that overlap (`/recipe/<_>` and `/recipe/import`) never collide.
+fn preflight_routes<'a>(routes: impl Iterator<Item = &'a Route>) -> Vec<Route> {
+    let mut methods: BTreeMap<String, Vec<Method>> = BTreeMap::new();
+
+    for route in routes.filter(|route| route.method != Method::Options) {
+        let path_methods = methods.entry(path_shape(route)).or_default();
+        if !path_methods.contains(&route.method) {
+            path_methods.push(route.method);
+        }
+    }
+
+    methods
+        .into_iter()
+        .enumerate()
+        .map(|(rank, (path, mut path_methods))| {
+            path_methods.push(Method::Options);
+            let allow = path_methods.iter().map(|method| method.as_str()).collect::<Vec<_>>().join(", ");
+
+            Route::ranked(rank as isize, Method::Options, &path, Preflight { allow })
+        })
+        .collect()
+}
+
+#[rocket::async_trait]
+impl Fairing for Cors {
+    fn info(&self) -> Info {
+        Info {
+            name: "CORS",
+            kind: Kind::Ignite | Kind::Response
+        }
+    }
+
+    async fn on_ignite(&self, rocket: Rocket<Build>) -> rocket::fairing::Result {
+        let preflights = preflight_routes(rocket.routes());
+
+        Ok(rocket.mount("/", preflights))
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        let Some(origin) = request.headers().get_one("Origin") else {
+            return;
+        };
+
+        if !self.allows_origin(origin) {
+            return;
+        }
+
+        if self.allows_any_origin() {
+            response.set_raw_header("Access-Control-Allow-Origin", "*");
+        } else {
+            response.set_raw_header("Access-Control-Allow-Origin", origin.to_string());
+            response.adjoin_raw_header("Vary", "Origin");
+        }
+
+        if self.config.allow_credentials {
+            response.set_raw_header("Access-Control-Allow-Credentials", "true");
+        }
+
+        let is_preflight = request.method() == Method::Options
+            && request.headers().contains("Access-Control-Request-Method");
+
+        if is_preflight {
+            response.set_raw_header("Access-Control-Allow-Methods", self.allow_methods.clone());
+            response.set_raw_header("Access-Control-Allow-Headers", self.allow_headers.clone());
+            response.set_raw_header("Access-Control-Max-Age", self.config.max_age.to_string());
+        } else {
+            response.set_raw_header("Access-Control-Expose-Headers", EXPOSED_HEADERS);
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use std::time::Instant;
+
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::{Data, Request, Response};
+
+use crate::metrics::metrics;
+
+/// Counts requests and records their latency, labelled with the route template
+/// (`/api/delete/recipe/<id>`) so ids do not blow up the number of series.
+pub struct RequestMetrics;
+
+#[derive(Clone, Copy)]
+struct RequestStart(Option<Instant>);
+
+#[rocket::async_trait]
+impl Fairing for RequestMetrics {
+    fn info(&self) -> Info {
+        Info {
+            name: "Request metrics",
+            kind: Kind::Request | Kind::Response
+        }
+    }
+
+    async fn on_request(&self, request: &mut Request<'_>, _: &mut Data<'_>) {
+        request.local_cache(|| RequestStart(Some(Instant::now())));
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        let Some(started) = request.local_cache(|| RequestStart(None)).0 else {
+            return;
+        };
+
+        let route = request.route()
+            .map(|route| route.uri.to_string())
+            .unwrap_or_else(|| "unmatched".to_string());
+        let method = request.method().as_str();
+
+        let metrics = metrics();
+        metrics.http_requests
+            .with_label_values(&[method, &route, &response.status().code.to_string()])
+            .inc();
+        metrics.http_request_duration
+            .with_label_values(&[method, &route])
+            .observe(started.elapsed().as_secs_f64());
+    }
+}
@@ -0,0 +1,5 @@
+pub mod cors;
+pub mod metrics;
+pub mod rate_limit;
+pub mod request_id;
+pub mod shutdown;
\ No newline at end of file
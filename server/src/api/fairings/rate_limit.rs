@@ -0,0 +1,248 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::uri::Origin;
+use rocket::http::{Method, Status};
+use rocket::{get, routes, Build, Data, Request, Response, Rocket};
+use sha2::{Digest, Sha256};
+
+use crate::api::guards::{AuthenticatedUser, VerifiedApiKey, API_KEY_HEADER};
+use crate::business::services::user_service::UserServiceTrait;
+use crate::config::{Limit, RateLimitConfig};
+
+/// Over-limit requests are rerouted here so no handler or database work runs for them.
+const RATE_LIMITED_PATH: &str = "/__rate_limited";
+/// Health probes and metric scrapes are never limited.
+const EXEMPT_PATHS: [&str; 2] = ["/health/", "/metrics"];
+/// Full buckets are dropped every this many requests to bound memory.
+const PRUNE_INTERVAL: u64 = 1024;
+/// How long an API key keeps its own bucket after a request authenticated with it.
+const VERIFIED_KEY_TTL: Duration = Duration::from_secs(600);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum RouteGroup {
+    Read,
+    Write,
+    Import,
+    Auth
+}
+
+impl RouteGroup {
+    fn of(request: &Request<'_>) -> RouteGroup {
+        let path = request.uri().path();
+
+        if path.starts_with("/api/auth/") {
+            RouteGroup::Auth
+        } else if path.contains("/import") {
+            RouteGroup::Import
+        } else if matches!(request.method(), Method::Get | Method::Head | Method::Options) {
+            RouteGroup::Read
+        } else {
+            RouteGroup::Write
+        }
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Decision {
+    limit: u32,
+    remaining: u32,
+    reset_seconds: u64,
+    retry_after: Option<u64>
+}
+
+impl Bucket {
+    fn refill(&mut self, limit: Limit, now: Instant) {
+        let per_second = f64::from(limit.per_minute) / 60.0;
+        let elapsed = now.duration_since(self.updated).as_secs_f64();
+
+        self.tokens = (self.tokens + elapsed * per_second).min(f64::from(limit.burst));
+        self.updated = now;
+    }
+
+    fn take(&mut self, limit: Limit, now: Instant) -> Decision {
+        self.refill(limit, now);
+
+        let per_second = f64::from(limit.per_minute) / 60.0;
+        let retry_after = if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            None
+        } else {
+            Some(((1.0 - self.tokens) / per_second).ceil() as u64)
+        };
+
+        Decision {
+            limit: limit.burst,
+            remaining: self.tokens.floor() as u32,
+            reset_seconds: ((f64::from(limit.burst) - self.tokens) / per_second).ceil() as u64,
+            retry_after
+        }
+    }
+}
+
+/// Token bucket rate limiter keyed by API key, authenticated user or client IP,
+/// with separate limits for reads, writes, imports and authentication. Every
+/// response carries the `RateLimit-*` headers; rejected requests get `429` with
+/// `Retry-After`.
+///
+/// API keys are only trusted as a bucket once a request has been authenticated
+/// with them; until then, and for made-up keys, the client IP is used. A key
+/// rejected with `401`, e.g. once revoked, loses its bucket right away.
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    buckets: Mutex<HashMap<(RouteGroup, String), Bucket>>,
+    /// Id of each API key a request was authenticated with and when, by hash
+    /// of the key.
+    verified_keys: Mutex<HashMap<[u8; 32], (i64, Instant)>>,
+    requests: AtomicU64
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        RateLimiter {
+            config,
+            buckets: Mutex::new(HashMap::new()),
+            verified_keys: Mutex::new(HashMap::new()),
+            requests: AtomicU64::new(0)
+        }
+    }
+
+    fn limit(&self, group: RouteGroup) -> Limit {
+        match group {
+            RouteGroup::Read => self.config.read,
+            RouteGroup::Write => self.config.write,
+            RouteGroup::Import => self.config.import,
+            RouteGroup::Auth => self.config.auth
+        }
+    }
+
+    fn key_hash(key: &str) -> [u8; 32] {
+        Sha256::digest(key.as_bytes()).into()
+    }
+
+    fn client_key(&self, request: &Request<'_>) -> String {
+        let user = match request.headers().get_one(API_KEY_HEADER) {
+            Some(key) => {
+                let verified_keys = self.verified_keys.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+                if let Some((id_api_key, verified)) = verified_keys.get(&Self::key_hash(key)) {
+                    if verified.elapsed() < VERIFIED_KEY_TTL {
+                        return format!("key:{}", id_api_key);
+                    }
+                }
+
+                None
+            }
+            None => request.rocket()
+                .state::<Box<dyn UserServiceTrait + Send + Sync>>()
+                .and_then(|user_service| AuthenticatedUser::token_user(request, user_service.as_ref()))
+        };
+
+        // Rocket's `client_ip` prefers the `ip_header`, which clients can set
+        // to anything unless a proxy in front overwrites it.
+        let ip = if self.config.trust_ip_header {
+            request.client_ip()
+        } else {
+            request.remote().map(|remote| remote.ip())
+        };
+
+        match (user, ip) {
+            (Some(id_user), _) => format!("user:{}", id_user),
+            (None, Some(ip)) => format!("ip:{}", ip),
+            (None, None) => "ip:unknown".to_string()
+        }
+    }
+
+    fn check(&self, group: RouteGroup, key: String) -> Decision {
+        let limit = self.limit(group);
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        if self.requests.fetch_add(1, Ordering::Relaxed).is_multiple_of(PRUNE_INTERVAL) {
+            buckets.retain(|(group, _), bucket| {
+                let limit = self.limit(*group);
+                bucket.refill(limit, now);
+                bucket.tokens < f64::from(limit.burst)
+            });
+
+            self.verified_keys
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .retain(|_, (_, verified)| verified.elapsed() < VERIFIED_KEY_TTL);
+        }
+
+        buckets
+            .entry((group, key))
+            .or_insert_with(|| Bucket { tokens: f64::from(limit.burst), updated: now })
+            .take(limit, now)
+    }
+}
+
+#[get("/__rate_limited")]
+fn rate_limited() -> Status {
+    Status::TooManyRequests
+}
+
+#[rocket::async_trait]
+impl Fairing for RateLimiter {
+    fn info(&self) -> Info {
+        Info {
+            name: "Rate limiter",
+            kind: Kind::Ignite | Kind::Request | Kind::Response
+        }
+    }
+
+    async fn on_ignite(&self, rocket: Rocket<Build>) -> rocket::fairing::Result {
+        Ok(rocket.mount("/", routes![rate_limited]))
+    }
+
+    async fn on_request(&self, request: &mut Request<'_>, _: &mut Data<'_>) {
+        if !self.config.enabled || EXEMPT_PATHS.iter().any(|path| request.uri().path().starts_with(path)) {
+            return;
+        }
+
+        let decision = self.check(RouteGroup::of(request), self.client_key(request));
+        request.local_cache(|| Some(decision));
+
+        if decision.retry_after.is_some() {
+            request.set_method(Method::Get);
+            request.set_uri(Origin::parse(RATE_LIMITED_PATH).expect("valid rate limit path"));
+        }
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        let Some(decision) = *request.local_cache(|| None::<Decision>) else {
+            return;
+        };
+
+        if let Some(key) = request.headers().get_one(API_KEY_HEADER) {
+            let mut verified_keys = self.verified_keys.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+            match *request.local_cache(VerifiedApiKey::default) {
+                VerifiedApiKey(Some(id_api_key)) => {
+                    verified_keys.insert(Self::key_hash(key), (id_api_key, Instant::now()));
+                }
+                VerifiedApiKey(None) if response.status() == Status::Unauthorized => {
+                    verified_keys.remove(&Self::key_hash(key));
+                }
+                VerifiedApiKey(None) => {}
+            }
+        }
+
+        response.set_raw_header("RateLimit-Limit", decision.limit.to_string());
+        response.set_raw_header("RateLimit-Remaining", decision.remaining.to_string());
+        response.set_raw_header("RateLimit-Reset", decision.reset_seconds.to_string());
+
+        if let Some(retry_after) = decision.retry_after {
+            response.set_raw_header("Retry-After", retry_after.to_string());
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use std::time::Instant;
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use opentelemetry::global;
+use opentelemetry::propagation::Extractor;
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::{Header, HeaderMap};
+use rocket::outcome::Outcome;
+use rocket::route::{self, Handler};
+use rocket::{Data, Request, Response, Route};
+use tracing::Instrument;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+use crate::logging::REQUEST_ID;
+
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+#[derive(Debug, Clone)]
+struct RequestContext {
+    id: String,
+    started: Instant
+}
+
+/// Accepts the client's `X-Request-Id` (or generates one), echoes it on the
+/// response and writes one access log line per request with its route, status,
+/// latency and error kind.
+pub struct RequestId;
+
+impl RequestId {
+    fn accept(header: Option<&str>) -> String {
+        match header {
+            Some(id) if !id.is_empty()
+                && id.len() <= MAX_REQUEST_ID_LENGTH
+                && id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')) => id.to_string(),
+            _ => {
+                let mut random = [0u8; 16];
+                OsRng.fill_bytes(&mut random);
+                random.iter().map(|byte| format!("{:02x}", byte)).collect()
+            }
+        }
+    }
+
+    fn context<'a>(request: &'a Request<'_>) -> &'a RequestContext {
+        request.local_cache(|| RequestContext {
+            id: Self::accept(request.headers().get_one(REQUEST_ID_HEADER)),
+            started: Instant::now()
+        })
+    }
+}
+
+#[rocket::async_trait]
+impl Fairing for RequestId {
+    fn info(&self) -> Info {
+        Info {
+            name: "Request id",
+            kind: Kind::Request | Kind::Response
+        }
+    }
+
+    async fn on_request(&self, request: &mut Request<'_>, _: &mut Data<'_>) {
+        Self::context(request);
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        let context = Self::context(request);
+        let status = response.status();
+
+        response.set_header(Header::new(REQUEST_ID_HEADER, context.id.clone()));
+
+        let route = request.route()
+            .map(|route| route.uri.to_string())
+            .unwrap_or_else(|| "unmatched".to_string());
+        let method = request.method().as_str();
+        let latency_ms = context.started.elapsed().as_secs_f64() * 1000.0;
+
+        if status.code >= 400 {
+            let error_kind = status.reason_lossy().to_lowercase().replace([' ', '-'], "_");
+
+            log::info!(
+                target: "access",
+                request_id = context.id.as_str(), method, route = route.as_str(), status = status.code, latency_ms,
+                error_kind = error_kind.as_str();
+                "{} {}", method, request.uri()
+            );
+        } else {
+            log::info!(
+                target: "access",
+                request_id = context.id.as_str(), method, route = route.as_str(), status = status.code, latency_ms;
+                "{} {}", method, request.uri()
+            );
+        }
+    }
+}
+
+/// Reads the W3C `traceparent` of incoming requests.
+struct RequestHeaders<'a>(&'a HeaderMap<'a>);
+
+impl Extractor for RequestHeaders<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get_one(key)
+    }
+
+    // Only needed by baggage propagation, which is not installed.
+    fn keys(&self) -> Vec<&str> {
+        Vec::new()
+    }
+}
+
+/// Runs a route handler, with its guards, inside the request id scope so that
+/// log lines from controllers, services and repositories carry the id, and
+/// inside a `request` span that is their parent in traces.
+#[derive(Clone)]
+struct RequestScoped(Box<dyn Handler>);
+
+#[rocket::async_trait]
+impl Handler for RequestScoped {
+    async fn handle<'r>(&self, request: &'r Request<'_>, data: Data<'r>) -> route::Outcome<'r> {
+        let id = RequestId::context(request).id.clone();
+        let route = request.route().map(|route| route.uri.to_string()).unwrap_or_default();
+
+        let span = tracing::info_span!(
+            "request",
+            otel.name = format!("{} {}", request.method(), route),
+            http.request.method = request.method().as_str(),
+            http.route = route,
+            request_id = id.as_str(),
+            http.response.status_code = tracing::field::Empty
+        );
+        span.set_parent(global::get_text_map_propagator(|propagator| propagator.extract(&RequestHeaders(request.headers()))));
+
+        let outcome = REQUEST_ID.scope(id, self.0.handle(request, data)).instrument(span.clone()).await;
+
+        let status = match &outcome {
+            Outcome::Success(response) => Some(response.status()),
+            Outcome::Error(status) => Some(*status),
+            Outcome::Forward(_) => None
+        };
+
+        if let Some(status) = status {
+            span.record("http.response.status_code", status.code);
+        }
+
+        outcome
+    }
+}
+
+/// Wraps the handlers of `routes` so they run inside the request id scope.
+pub fn scoped(routes: Vec<Route>) -> Vec<Route> {
+    routes
+        .into_iter()
+        .map(|mut route| {
+            route.handler = Box::new(RequestScoped(route.handler));
+            route
+        })
+        .collect()
+}
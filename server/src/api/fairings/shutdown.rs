@@ -0,0 +1,149 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::{Build, Data, Orbit, Request, Rocket};
+use sqlx::MySqlPool;
+use tokio::time::{sleep, timeout, Instant};
+
+/// How often the shutdown hook checks whether in-flight requests have finished.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Whether the server is shutting down and how many requests it is handling.
+#[derive(Debug, Default)]
+pub struct ShutdownState {
+    draining: AtomicBool,
+    in_flight: AtomicUsize
+}
+
+impl ShutdownState {
+    pub fn is_draining(&self) -> bool {
+        self.draining.load(Ordering::Relaxed)
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::Relaxed)
+    }
+
+    fn start_draining(&self) {
+        self.draining.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Counts a request as in flight until the request, and so its response, is dropped,
+/// which also covers clients disconnecting before the response is sent.
+struct InFlight(Arc<ShutdownState>);
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.in_flight.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Signal {
+    Terminate,
+    Interrupt
+}
+
+/// Shuts the server down gracefully, replacing Rocket's own signal handling.
+///
+/// On `SIGTERM` readiness answers `503` for the `drain` period while requests
+/// are still served, so load balancers stop routing here, and then Rocket stops
+/// accepting connections. `ctrl-c` skips the drain. The shutdown hook waits up to
+/// Rocket's `shutdown.grace` for in-flight requests before closing the pool.
+pub struct GracefulShutdown {
+    state: Arc<ShutdownState>,
+    drain: Duration,
+    pool: MySqlPool
+}
+
+impl GracefulShutdown {
+    pub fn new(pool: MySqlPool, drain: Duration) -> Self {
+        GracefulShutdown {
+            state: Arc::new(ShutdownState::default()),
+            drain,
+            pool
+        }
+    }
+}
+
+async fn termination_signal() -> Signal {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                return tokio::select! {
+                    _ = terminate.recv() => Signal::Terminate,
+                    _ = tokio::signal::ctrl_c() => Signal::Interrupt
+                };
+            }
+            Err(err) => log::error!("Cannot listen for SIGTERM: {}", err)
+        }
+    }
+
+    let _ = tokio::signal::ctrl_c().await;
+    Signal::Interrupt
+}
+
+#[rocket::async_trait]
+impl Fairing for GracefulShutdown {
+    fn info(&self) -> Info {
+        Info {
+            name: "Graceful shutdown",
+            kind: Kind::Ignite | Kind::Liftoff | Kind::Request | Kind::Shutdown
+        }
+    }
+
+    async fn on_ignite(&self, rocket: Rocket<Build>) -> rocket::fairing::Result {
+        Ok(rocket.manage(self.state.clone()))
+    }
+
+    async fn on_liftoff(&self, rocket: &Rocket<Orbit>) {
+        let shutdown = rocket.shutdown();
+        let state = self.state.clone();
+        let drain = self.drain;
+
+        tokio::spawn(async move {
+            if termination_signal().await == Signal::Terminate && !drain.is_zero() {
+                state.start_draining();
+                log::info!("SIGTERM received, draining for {}s before shutting down", drain.as_secs());
+
+                tokio::select! {
+                    _ = sleep(drain) => {}
+                    _ = termination_signal() => log::info!("Second signal received, skipping the drain")
+                }
+            }
+
+            shutdown.notify();
+        });
+    }
+
+    async fn on_request(&self, request: &mut Request<'_>, _: &mut Data<'_>) {
+        self.state.in_flight.fetch_add(1, Ordering::Relaxed);
+        request.local_cache(|| InFlight(self.state.clone()));
+    }
+
+    async fn on_shutdown(&self, rocket: &Rocket<Orbit>) {
+        self.state.start_draining();
+
+        let config = &rocket.config().shutdown;
+        let deadline = Instant::now() + Duration::from_secs(u64::from(config.grace));
+
+        while self.state.in_flight() > 0 && Instant::now() < deadline {
+            sleep(POLL_INTERVAL).await;
+        }
+
+        match self.state.in_flight() {
+            0 => log::info!("All requests finished, closing the database pool"),
+            pending => log::warn!("{} requests still running after the grace period, closing the database pool", pending)
+        }
+
+        if timeout(Duration::from_secs(u64::from(config.mercy)), self.pool.close()).await.is_err() {
+            log::warn!("Database connections still checked out, giving up on closing them");
+        }
+    }
+}
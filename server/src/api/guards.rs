@@ -0,0 +1,151 @@
+use std::marker::PhantomData;
+use std::ops::Deref;
+
+use rocket::http::Status;
+use rocket::request::{FromRequest, Outcome, Request};
+use rocket::State;
+
+use crate::business::access::{ApiKeyScope, Caller, Permission, Role};
+use crate::business::services::api_key_service::{ApiKeyError, ApiKeyServiceTrait};
+use crate::business::services::user_service::{UserError, UserServiceTrait};
+
+/// Name of the private cookie holding the token of browser sessions.
+pub const AUTH_COOKIE: &str = "auth_token";
+
+/// Header carrying the API key of machine clients.
+pub const API_KEY_HEADER: &str = "X-Api-Key";
+
+/// The API key a request was authenticated with, cached on the request so the
+/// rate limiter can key later requests by it.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VerifiedApiKey(pub Option<i64>);
+
+/// A user authenticated with a bearer token in the `Authorization` header, with
+/// the private auth cookie set on login, or with an API key minted by the user.
+/// The role is read from the database on every request, so role changes apply
+/// without a new login.
+#[derive(Debug)]
+pub struct AuthenticatedUser {
+    pub id_user: i64,
+    pub username: String,
+    pub role: Role,
+    pub api_key_scope: Option<ApiKeyScope>
+}
+
+impl AuthenticatedUser {
+    pub fn caller(&self) -> Caller {
+        Caller { id_user: self.id_user, role: self.role }
+    }
+
+    /// Requests made with an API key are limited by both the role and the key scope.
+    pub fn allows(&self, permission: Permission) -> bool {
+        self.role.allows(permission) && self.api_key_scope.is_none_or(|scope| scope.allows(permission))
+    }
+
+    /// The user id of the bearer token or of the auth cookie, if valid.
+    pub(crate) fn token_user(request: &Request<'_>, user_service: &(dyn UserServiceTrait + Send + Sync)) -> Option<i64> {
+        let token = match request.headers().get_one("Authorization") {
+            Some(header) => header.strip_prefix("Bearer ").map(str::to_string),
+            None => request.cookies().get_private(AUTH_COOKIE).map(|cookie| cookie.value().to_string())
+        };
+
+        user_service.verify_token(&token?).ok()?.sub.parse().ok()
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for AuthenticatedUser {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let user_service = match request.guard::<&State<Box<dyn UserServiceTrait + Send + Sync>>>().await {
+            Outcome::Success(user_service) => user_service,
+            _ => return Outcome::Error((Status::InternalServerError, ()))
+        };
+
+        let mut api_key_scope = None;
+
+        let id_user = if let Some(key) = request.headers().get_one(API_KEY_HEADER) {
+            let api_key_service = match request.guard::<&State<Box<dyn ApiKeyServiceTrait + Send + Sync>>>().await {
+                Outcome::Success(api_key_service) => api_key_service,
+                _ => return Outcome::Error((Status::InternalServerError, ()))
+            };
+
+            match api_key_service.authenticate(key).await {
+                Ok(api_key) => {
+                    request.local_cache(|| VerifiedApiKey(Some(api_key.id_api_key)));
+                    api_key_scope = Some(api_key.scope);
+                    api_key.id_user
+                }
+                Err(ApiKeyError::InvalidKey) => return Outcome::Error((Status::Unauthorized, ())),
+                Err(_) => return Outcome::Error((Status::InternalServerError, ()))
+            }
+        } else {
+            match Self::token_user(request, user_service.as_ref()) {
+                Some(id_user) => id_user,
+                None => return Outcome::Error((Status::Unauthorized, ()))
+            }
+        };
+
+        match user_service.get_user(id_user).await {
+            Ok(user) => Outcome::Success(AuthenticatedUser { id_user: user.id_user, username: user.username, role: user.role, api_key_scope }),
+            Err(UserError::NotFound) => Outcome::Error((Status::Unauthorized, ())),
+            Err(_) => Outcome::Error((Status::InternalServerError, ()))
+        }
+    }
+}
+
+/// Marker types naming the permission an [`Authorized`] guard checks.
+pub trait RequiredPermission: Send + Sync {
+    const PERMISSION: Permission;
+}
+
+pub struct CatalogueWrite;
+pub struct RecipeWrite;
+pub struct MealPlanWrite;
+pub struct UserAdmin;
+
+impl RequiredPermission for CatalogueWrite {
+    const PERMISSION: Permission = Permission::WriteCatalogue;
+}
+
+impl RequiredPermission for RecipeWrite {
+    const PERMISSION: Permission = Permission::WriteRecipes;
+}
+
+impl RequiredPermission for MealPlanWrite {
+    const PERMISSION: Permission = Permission::WriteMealPlans;
+}
+
+impl RequiredPermission for UserAdmin {
+    const PERMISSION: Permission = Permission::ManageUsers;
+}
+
+/// An authenticated user whose role (and API key scope) grants `P`; answers
+/// `401` without valid credentials and `403` when the permission is missing.
+pub struct Authorized<P: RequiredPermission> {
+    pub user: AuthenticatedUser,
+    permission: PhantomData<P>
+}
+
+impl<P: RequiredPermission> Deref for Authorized<P> {
+    type Target = AuthenticatedUser;
+
+    fn deref(&self) -> &Self::Target {
+        &self.user
+    }
+}
+
+#[rocket::async_trait]
+impl<'r, P: RequiredPermission> FromRequest<'r> for Authorized<P> {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match request.guard::<AuthenticatedUser>().await {
+            Outcome::Success(user) if user.allows(P::PERMISSION) => Outcome::Success(Authorized { user, permission: PhantomData }),
+            Outcome::Success(_) => Outcome::Error((Status::Forbidden, ())),
+            Outcome::Error(error) => Outcome::Error(error),
+            Outcome::Forward(status) => Outcome::Forward(status)
+        }
+    }
+}
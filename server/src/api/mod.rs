@@ -1 +1,4 @@
-pub mod controllers;
\ No newline at end of file
+pub mod controllers;
+pub mod fairings;
+pub mod guards;
+pub mod params;
\ No newline at end of file
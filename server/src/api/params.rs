@@ -0,0 +1,45 @@
+use chrono::NaiveDate;
+use rocket::request::FromParam;
+
+/// Path segment of the form `<id>.jsonld`.
+pub struct JsonLdId(pub i64);
+
+impl<'a> FromParam<'a> for JsonLdId {
+    type Error = &'a str;
+
+    fn from_param(param: &'a str) -> Result<Self, Self::Error> {
+        id_with_extension(param, "jsonld").map(JsonLdId).ok_or(param)
+    }
+}
+
+/// Path segment of the form `<id>.ics`.
+pub struct IcsId(pub i64);
+
+impl<'a> FromParam<'a> for IcsId {
+    type Error = &'a str;
+
+    fn from_param(param: &'a str) -> Result<Self, Self::Error> {
+        id_with_extension(param, "ics").map(IcsId).ok_or(param)
+    }
+}
+
+/// Path segment holding a `YYYY-MM-DD` date.
+pub struct IsoDate(pub NaiveDate);
+
+impl<'a> FromParam<'a> for IsoDate {
+    type Error = &'a str;
+
+    fn from_param(param: &'a str) -> Result<Self, Self::Error> {
+        NaiveDate::parse_from_str(param, "%Y-%m-%d").map(IsoDate).map_err(|_| param)
+    }
+}
+
+fn id_with_extension(param: &str, extension: &str) -> Option<i64> {
+    let (id, param_extension) = param.rsplit_once('.')?;
+
+    if param_extension != extension {
+        return None;
+    }
+
+    id.parse().ok()
+}
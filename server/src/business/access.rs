@@ -0,0 +1,138 @@
+use serde::{Deserialize, Serialize};
+
+/// The authenticated user a service call is made for.
+#[derive(Debug, Clone)]
+pub struct Caller {
+    pub id_user: i64,
+    pub role: Role
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Role {
+    Admin,
+    Nutritionist,
+    #[default]
+    Member,
+    ReadOnly
+}
+
+/// Actions that need more than being signed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    WriteCatalogue,
+    WriteRecipes,
+    WriteMealPlans,
+    ManageUsers
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Admin => "admin",
+            Role::Nutritionist => "nutritionist",
+            Role::Member => "member",
+            Role::ReadOnly => "read-only"
+        }
+    }
+
+    pub fn parse(role: &str) -> Role {
+        match role {
+            "admin" => Role::Admin,
+            "nutritionist" => Role::Nutritionist,
+            "member" => Role::Member,
+            _ => Role::ReadOnly
+        }
+    }
+
+    /// The permission matrix: the ingredient catalogue is shared, so only
+    /// nutritionists edit it, while every member builds recipes and meal plans.
+    pub fn allows(&self, permission: Permission) -> bool {
+        match self {
+            Role::Admin => true,
+            Role::Nutritionist => permission != Permission::ManageUsers,
+            Role::Member => matches!(permission, Permission::WriteRecipes | Permission::WriteMealPlans),
+            Role::ReadOnly => false
+        }
+    }
+}
+
+/// What requests authenticated with an API key may do, on top of the role of
+/// the admin who minted it. Keys never manage users or other keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ApiKeyScope {
+    ReadOnly,
+    CatalogueWrite,
+    Full
+}
+
+impl ApiKeyScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiKeyScope::ReadOnly => "read-only",
+            ApiKeyScope::CatalogueWrite => "catalogue-write",
+            ApiKeyScope::Full => "full"
+        }
+    }
+
+    pub fn parse(scope: &str) -> ApiKeyScope {
+        match scope {
+            "full" => ApiKeyScope::Full,
+            "catalogue-write" => ApiKeyScope::CatalogueWrite,
+            _ => ApiKeyScope::ReadOnly
+        }
+    }
+
+    pub fn allows(&self, permission: Permission) -> bool {
+        match self {
+            ApiKeyScope::ReadOnly => false,
+            ApiKeyScope::CatalogueWrite => permission == Permission::WriteCatalogue,
+            ApiKeyScope::Full => permission != Permission::ManageUsers
+        }
+    }
+}
+
+/// Who can see a recipe or meal plan besides its owner: nobody, every signed-in
+/// member of the household, or everyone including anonymous clients.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Visibility {
+    #[default]
+    Private,
+    Household,
+    Public
+}
+
+impl Visibility {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Visibility::Private => "private",
+            Visibility::Household => "household",
+            Visibility::Public => "public"
+        }
+    }
+
+    pub fn parse(visibility: &str) -> Visibility {
+        match visibility {
+            "public" => Visibility::Public,
+            "household" => Visibility::Household,
+            _ => Visibility::Private
+        }
+    }
+}
+
+pub fn can_view(owner: Option<i64>, visibility: Visibility, caller: Option<&Caller>) -> bool {
+    match (visibility, caller) {
+        (Visibility::Public, _) => true,
+        (Visibility::Household, Some(_)) => true,
+        (Visibility::Private, Some(caller)) => owner.is_none_or(|owner| owner == caller.id_user),
+        (_, None) => false
+    }
+}
+
+/// Admins can change everything, including the items without an owner that
+/// predate user accounts; everyone else only what they own.
+pub fn can_modify(owner: Option<i64>, caller: &Caller) -> bool {
+    caller.role == Role::Admin || owner == Some(caller.id_user)
+}
@@ -0,0 +1,63 @@
+use std::fmt;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::business::names::{self, Named};
+
+/// The 14 major allergens that must be declared on food in the EU
+/// (Regulation 1169/2011, Annex II).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Allergen {
+    Gluten,
+    Crustaceans,
+    Eggs,
+    Fish,
+    Peanuts,
+    Soybeans,
+    Milk,
+    Nuts,
+    Celery,
+    Mustard,
+    Sesame,
+    Sulphites,
+    Lupin,
+    Molluscs
+}
+
+impl Named for Allergen {
+    const KIND: &'static str = "allergen";
+    const NAMES: &'static [(Allergen, &'static [&'static str])] = &[
+        (Allergen::Gluten, &["gluten", "cereals", "cereales"]),
+        (Allergen::Crustaceans, &["crustaceans", "crustacean", "crustáceos", "crustaceos"]),
+        (Allergen::Eggs, &["eggs", "egg", "huevos", "huevo"]),
+        (Allergen::Fish, &["fish", "pescado"]),
+        (Allergen::Peanuts, &["peanuts", "peanut", "cacahuetes", "cacahuete", "maní", "mani"]),
+        (Allergen::Soybeans, &["soybeans", "soybean", "soy", "soya", "soja"]),
+        (Allergen::Milk, &["milk", "dairy", "lactose", "leche", "lácteos", "lacteos", "lactosa"]),
+        (Allergen::Nuts, &["nuts", "tree nuts", "frutos de cáscara", "frutos de cascara", "frutos secos"]),
+        (Allergen::Celery, &["celery", "apio"]),
+        (Allergen::Mustard, &["mustard", "mostaza"]),
+        (Allergen::Sesame, &["sesame", "sésamo", "sesamo"]),
+        (Allergen::Sulphites, &["sulphites", "sulfites", "sulphur dioxide", "sulfitos", "dióxido de azufre", "dioxido de azufre"]),
+        (Allergen::Lupin, &["lupin", "altramuces", "altramuz"]),
+        (Allergen::Molluscs, &["molluscs", "mollusks", "moluscos"])
+    ];
+}
+
+impl fmt::Display for Allergen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for Allergen {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        names::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Allergen {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        names::deserialize(deserializer)
+    }
+}
@@ -0,0 +1,239 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::business::allergens::Allergen;
+use crate::business::names::{self, Named};
+use crate::business::nutrition::Nutrition;
+use crate::data_access::entities::ingredients::Ingredient;
+use crate::data_access::entities::recipe_ingredients::RecipeIngredients;
+
+/// Keto recipes get at most this share of their energy from carbs...
+const KETO_MAX_CARB_ENERGY: f64 = 0.10;
+/// ...and at least this share from fats.
+const KETO_MIN_FAT_ENERGY: f64 = 0.60;
+const LOW_CARB_MAX_CARB_ENERGY: f64 = 0.26;
+
+/// What an ingredient is or contains that some diets leave out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum DietAttribute {
+    Meat,
+    Fish,
+    Dairy,
+    Eggs,
+    Honey
+}
+
+/// Diets recipes are checked against and meal plans can declare.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Diet {
+    Vegan,
+    Vegetarian,
+    GlutenFree,
+    Keto,
+    LowCarb
+}
+
+impl Named for DietAttribute {
+    const KIND: &'static str = "diet attribute";
+    const NAMES: &'static [(DietAttribute, &'static [&'static str])] = &[
+        (DietAttribute::Meat, &["meat", "poultry", "gelatin", "carne", "aves", "gelatina"]),
+        (DietAttribute::Fish, &["fish", "seafood", "shellfish", "pescado", "marisco", "mariscos"]),
+        (DietAttribute::Dairy, &["dairy", "milk", "lácteos", "lacteos", "leche"]),
+        (DietAttribute::Eggs, &["eggs", "egg", "huevos", "huevo"]),
+        (DietAttribute::Honey, &["honey", "miel"])
+    ];
+}
+
+impl Named for Diet {
+    const KIND: &'static str = "diet";
+    const NAMES: &'static [(Diet, &'static [&'static str])] = &[
+        (Diet::Vegan, &["vegan", "vegano", "vegana"]),
+        (Diet::Vegetarian, &["vegetarian", "vegetariano", "vegetariana"]),
+        (Diet::GlutenFree, &["gluten-free", "gluten free", "sin gluten"]),
+        (Diet::Keto, &["keto", "ketogenic", "cetogénica", "cetogenica"]),
+        (Diet::LowCarb, &["low-carb", "low carb", "baja en carbohidratos"])
+    ];
+}
+
+impl DietAttribute {
+    /// The attribute an allergen tells an ingredient has, so that ingredients
+    /// only labelled with allergens are still checked.
+    fn implied_by(allergen: Allergen) -> Option<DietAttribute> {
+        match allergen {
+            Allergen::Milk => Some(DietAttribute::Dairy),
+            Allergen::Eggs => Some(DietAttribute::Eggs),
+            Allergen::Fish | Allergen::Crustaceans | Allergen::Molluscs => Some(DietAttribute::Fish),
+            _ => None
+        }
+    }
+}
+
+impl Diet {
+    pub fn all() -> impl Iterator<Item = Diet> {
+        Diet::NAMES.iter().map(|(diet, _)| *diet)
+    }
+}
+
+/// What a recipe's diet compatibility is derived from: the attributes and
+/// allergens of its ingredients and its macros.
+#[derive(Debug, Default)]
+pub struct DietProfile {
+    pub attributes: Vec<DietAttribute>,
+    pub allergens: Vec<Allergen>,
+    pub nutrition: Nutrition,
+    /// Ingredients whose diet attributes were never given.
+    pub unlabelled_attributes: Vec<String>,
+    /// Ingredients whose allergens were never given.
+    pub unlabelled_allergens: Vec<String>,
+    /// Ingredients left out of `nutrition`: without macros, as imported ones
+    /// start, or in a unit that cannot be weighed.
+    pub unmeasured: Vec<String>
+}
+
+impl DietProfile {
+    /// The profile of `recipe_ingredients`, looked up in `ingredients`.
+    pub fn of(recipe_ingredients: &[RecipeIngredients], ingredients: &HashMap<i64, Ingredient>) -> DietProfile {
+        let mut profile = DietProfile::default();
+
+        for recipe_ingredient in recipe_ingredients {
+            let Some(ingredient) = ingredients.get(&recipe_ingredient.ingredients_id_ingredient) else {
+                continue;
+            };
+
+            match &ingredient.allergens {
+                Some(stored) => {
+                    let ingredient_allergens: Vec<Allergen> = names::read_stored(stored);
+
+                    profile.attributes.extend(ingredient_allergens.iter().filter_map(|allergen| DietAttribute::implied_by(*allergen)));
+                    profile.allergens.extend(ingredient_allergens);
+                }
+                None => profile.unlabelled_allergens.push(ingredient.name.clone())
+            }
+
+            match &ingredient.diet_attributes {
+                Some(stored) => profile.attributes.extend(names::read_stored::<DietAttribute>(stored)),
+                None => profile.unlabelled_attributes.push(ingredient.name.clone())
+            }
+
+            let has_macros = ingredient.proteins > 0.0 || ingredient.carbs > 0.0 || ingredient.fats > 0.0;
+
+            if !has_macros || !profile.nutrition.add_ingredient(ingredient, recipe_ingredient.amount, &recipe_ingredient.unit) {
+                profile.unmeasured.push(ingredient.name.clone());
+            }
+        }
+
+        profile.attributes.sort();
+        profile.attributes.dedup();
+        profile.allergens.sort();
+        profile.allergens.dedup();
+        profile.unlabelled_attributes.sort();
+        profile.unlabelled_attributes.dedup();
+        profile.unlabelled_allergens.sort();
+        profile.unlabelled_allergens.dedup();
+        profile.unmeasured.sort();
+        profile.unmeasured.dedup();
+
+        profile
+    }
+
+    /// Why the recipe does not fit `diet`; empty when it does.
+    pub fn breaks(&self, diet: Diet) -> Vec<String> {
+        match diet {
+            Diet::Vegan => self.reasons_for(&[DietAttribute::Meat, DietAttribute::Fish, DietAttribute::Dairy, DietAttribute::Eggs, DietAttribute::Honey]),
+            Diet::Vegetarian => self.reasons_for(&[DietAttribute::Meat, DietAttribute::Fish]),
+            Diet::GlutenFree if self.allergens.contains(&Allergen::Gluten) => vec!["contains gluten".to_string()],
+            Diet::GlutenFree => vec![],
+            Diet::Keto => {
+                let mut reasons = self.energy_above(self.nutrition.carbs * 4.0, "carbs", KETO_MAX_CARB_ENERGY);
+
+                if let Some(share) = self.energy_share(self.nutrition.fats * 9.0) {
+                    if share < KETO_MIN_FAT_ENERGY {
+                        reasons.push(format!("fats are {}% of the energy, at least {}% expected", percent(share), percent(KETO_MIN_FAT_ENERGY)));
+                    }
+                }
+
+                reasons
+            }
+            Diet::LowCarb => self.energy_above(self.nutrition.carbs * 4.0, "carbs", LOW_CARB_MAX_CARB_ENERGY)
+        }
+    }
+
+    /// The ingredients lacking the data `diet` is checked against. When there
+    /// are any, nothing in `breaks` does not mean that the recipe fits.
+    pub fn unverified(&self, diet: Diet) -> &[String] {
+        match diet {
+            Diet::Vegan | Diet::Vegetarian => &self.unlabelled_attributes,
+            Diet::GlutenFree => &self.unlabelled_allergens,
+            Diet::Keto | Diet::LowCarb => &self.unmeasured
+        }
+    }
+
+    pub fn compatible_diets(&self) -> Vec<Diet> {
+        Diet::all().filter(|diet| self.breaks(*diet).is_empty() && self.unverified(*diet).is_empty()).collect()
+    }
+
+    /// The diets nothing known breaks, but which some ingredients lack the
+    /// data to confirm.
+    pub fn unverified_diets(&self) -> Vec<Diet> {
+        Diet::all().filter(|diet| self.breaks(*diet).is_empty() && !self.unverified(*diet).is_empty()).collect()
+    }
+
+    fn reasons_for(&self, excluded: &[DietAttribute]) -> Vec<String> {
+        excluded
+            .iter()
+            .filter(|attribute| self.attributes.contains(attribute))
+            .map(|attribute| format!("contains {}", attribute.as_str()))
+            .collect()
+    }
+
+    /// Share of the energy `calories` are, `None` when the macros are unknown.
+    fn energy_share(&self, calories: f64) -> Option<f64> {
+        let total = self.nutrition.calories();
+
+        (total > 0.0).then(|| calories / total)
+    }
+
+    fn energy_above(&self, calories: f64, macro_name: &str, max_share: f64) -> Vec<String> {
+        match self.energy_share(calories) {
+            None => vec!["macros are unknown".to_string()],
+            Some(share) if share > max_share => vec![format!("{} are {}% of the energy, at most {}% allowed", macro_name, percent(share), percent(max_share))],
+            Some(_) => vec![]
+        }
+    }
+}
+
+fn percent(share: f64) -> f64 {
+    (share * 1000.0).round() / 10.0
+}
+
+impl fmt::Display for Diet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for DietAttribute {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        names::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for DietAttribute {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        names::deserialize(deserializer)
+    }
+}
+
+impl Serialize for Diet {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        names::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Diet {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        names::deserialize(deserializer)
+    }
+}
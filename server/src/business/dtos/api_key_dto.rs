@@ -0,0 +1,50 @@
+use chrono::NaiveDateTime;
+use validator::Validate;
+use serde::{Deserialize, Serialize};
+
+use crate::business::access::ApiKeyScope;
+use crate::data_access::entities::api_keys::ApiKey;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ApiKeyRequestDTO {
+    #[validate(length(min = 1, max = 45))]
+    pub name: String,
+
+    pub scope: ApiKeyScope
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApiKeyDTO {
+    pub id_api_key: i64,
+    pub name: String,
+    pub prefix: String,
+    pub scope: ApiKeyScope,
+    pub id_user: i64,
+    pub created_at: NaiveDateTime,
+    pub last_used_at: Option<NaiveDateTime>,
+    pub revoked_at: Option<NaiveDateTime>
+}
+
+/// Answer to minting a key; the only time the plain key is shown.
+#[derive(Debug, Serialize)]
+pub struct NewApiKeyDTO {
+    pub id_api_key: i64,
+    pub name: String,
+    pub scope: ApiKeyScope,
+    pub key: String
+}
+
+impl From<ApiKey> for ApiKeyDTO {
+    fn from(api_key: ApiKey) -> Self {
+        ApiKeyDTO {
+            id_api_key: api_key.id_api_key,
+            name: api_key.name,
+            prefix: api_key.prefix,
+            scope: ApiKeyScope::parse(&api_key.scope),
+            id_user: api_key.id_user,
+            created_at: api_key.created_at,
+            last_used_at: api_key.last_used_at,
+            revoked_at: api_key.revoked_at
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct LivenessDTO {
+    pub status: String
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReadinessDTO {
+    pub status: String,
+    pub database: bool,
+    pub pending_migrations: Vec<i64>,
+    pub pool: PoolUtilisationDTO
+}
+
+#[derive(Debug, Serialize)]
+pub struct PoolUtilisationDTO {
+    pub size: u32,
+    pub idle: u32,
+    pub in_use: u32,
+    pub max_connections: u32,
+    pub utilisation: f64
+}
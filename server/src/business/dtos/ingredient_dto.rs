@@ -1,10 +1,19 @@
 use validator::Validate;
 use serde::{Deserialize, Serialize};
 
+use crate::business::allergens::Allergen;
+use crate::business::diets::DietAttribute;
+use crate::business::names;
 use crate::data_access::entities::ingredients::Ingredient;
 
+/// Separates the allergens and diet attributes of an ingredient in a CSV cell.
+const CSV_LIST_SEPARATOR: &str = "|";
+/// Marks an empty list in a CSV cell, as an empty cell leaves the list as it is.
+const CSV_EMPTY_LIST: &str = "none";
+
 #[derive(Debug, Deserialize, Serialize, Validate)]
 pub struct IngredientDTO {
+    #[serde(default)]
     pub id_ingredient: i64,
 
     #[validate(length(min = 1, max = 45))]
@@ -17,7 +26,20 @@ pub struct IngredientDTO {
     pub carbs: f64,
 
     #[validate(range(min = 0.0))]
-    pub fats: f64
+    pub fats: f64,
+
+    #[serde(default)]
+    #[validate(length(max = 45))]
+    pub source_id: Option<String>,
+
+    /// Left out on update, the stored allergens are kept.
+    #[serde(default)]
+    pub allergens: Option<Vec<Allergen>>,
+
+    /// Meat, fish, dairy, eggs or honey, for deriving recipe diets. Left out on
+    /// update, the stored ones are kept.
+    #[serde(default)]
+    pub diet_attributes: Option<Vec<DietAttribute>>
 }
 
 impl From<Ingredient> for IngredientDTO {
@@ -28,6 +50,119 @@ impl From<Ingredient> for IngredientDTO {
             proteins: ingredient.proteins,
             carbs: ingredient.carbs,
             fats: ingredient.fats,
+            source_id: ingredient.source_id,
+            allergens: ingredient.allergens.as_deref().map(names::read_stored),
+            diet_attributes: ingredient.diet_attributes.as_deref().map(names::read_stored)
         }
     }
 }
+
+/// An ingredient as a CSV row, with its allergens and diet attributes each in
+/// a single cell such as `gluten|milk`. A missing column or an empty cell
+/// leaves the list unset.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct IngredientCsvRow {
+    #[serde(default)]
+    pub id_ingredient: i64,
+    pub name: String,
+    pub proteins: f64,
+    pub carbs: f64,
+    pub fats: f64,
+    #[serde(default)]
+    pub source_id: Option<String>,
+    #[serde(default)]
+    pub allergens: Option<String>,
+    #[serde(default)]
+    pub diet_attributes: Option<String>
+}
+
+impl From<IngredientDTO> for IngredientCsvRow {
+    fn from(ingredient: IngredientDTO) -> Self {
+        IngredientCsvRow {
+            id_ingredient: ingredient.id_ingredient,
+            name: ingredient.name,
+            proteins: ingredient.proteins,
+            carbs: ingredient.carbs,
+            fats: ingredient.fats,
+            source_id: ingredient.source_id,
+            allergens: ingredient.allergens.map(|list| csv_list(&names::join(&list, CSV_LIST_SEPARATOR))),
+            diet_attributes: ingredient.diet_attributes.map(|list| csv_list(&names::join(&list, CSV_LIST_SEPARATOR)))
+        }
+    }
+}
+
+impl TryFrom<IngredientCsvRow> for IngredientDTO {
+    /// The column that could not be read and why.
+    type Error = (&'static str, String);
+
+    fn try_from(row: IngredientCsvRow) -> Result<Self, Self::Error> {
+        Ok(IngredientDTO {
+            id_ingredient: row.id_ingredient,
+            name: row.name,
+            proteins: row.proteins,
+            carbs: row.carbs,
+            fats: row.fats,
+            source_id: row.source_id,
+            allergens: row.allergens
+                .map(|cell| names::parse_list(&read_csv_list(cell)))
+                .transpose()
+                .map_err(|name| ("allergens", format!("unknown allergen: {}", name)))?,
+            diet_attributes: row.diet_attributes
+                .map(|cell| names::parse_list(&read_csv_list(cell)))
+                .transpose()
+                .map_err(|name| ("diet_attributes", format!("unknown diet attribute: {}", name)))?
+        })
+    }
+}
+
+fn csv_list(joined: &str) -> String {
+    if joined.is_empty() {
+        CSV_EMPTY_LIST.to_string()
+    } else {
+        joined.to_string()
+    }
+}
+
+fn read_csv_list(cell: String) -> String {
+    if cell.trim().eq_ignore_ascii_case(CSV_EMPTY_LIST) {
+        String::new()
+    } else {
+        cell
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct IngredientImportReport {
+    pub created: usize,
+    pub updated: usize,
+    pub errors: Vec<IngredientImportLineError>
+}
+
+#[derive(Debug, Serialize)]
+pub struct IngredientImportLineError {
+    pub line: u64,
+    pub field: Option<String>,
+    pub message: String
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct IngredientLinesDTO {
+    #[validate(length(min = 1, max = 100))]
+    pub lines: Vec<String>
+}
+
+#[derive(Debug, Serialize)]
+pub struct ParsedIngredientDTO {
+    pub line: String,
+    pub amount: Option<f64>,
+    pub unit: Option<String>,
+    pub name: String,
+    pub matches: Vec<IngredientMatchDTO>
+}
+
+#[derive(Debug, Serialize)]
+pub struct IngredientMatchDTO {
+    pub id_ingredient: i64,
+    pub name: String,
+    pub score: f64
+}
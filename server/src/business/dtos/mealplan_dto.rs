@@ -1,6 +1,13 @@
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 use validator::Validate;
 
+use crate::business::access::Visibility;
+use crate::business::allergens::Allergen;
+use crate::business::diets::Diet;
+use crate::business::meal_slots::{Day, MealType};
+use crate::business::nutrition::Nutrition;
+
 #[derive(Debug, Deserialize, Serialize, Validate)]
 pub struct MealPlanDTO {
     pub id_mealplan: i64,
@@ -11,12 +18,158 @@ pub struct MealPlanDTO {
     #[validate(length(min = 1, max = 45))]
     pub category: String,
 
-    pub recipes: Vec<RecipeMealsDTO>
+    #[validate]
+    pub recipes: Vec<RecipeMealsDTO>,
+
+    #[serde(default)]
+    pub visibility: Visibility,
+
+    #[serde(default)]
+    pub id_owner: Option<i64>,
+
+    /// First day of a dated plan. Plans without one describe a generic week.
+    #[serde(default)]
+    pub start_date: Option<NaiveDate>,
+
+    #[serde(default = "default_length_days")]
+    #[validate(range(min = 1, max = 366))]
+    pub length_days: u32,
+
+    /// Diets the recipes of the plan should fit.
+    #[serde(default)]
+    pub diets: Vec<Diet>
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+fn default_length_days() -> u32 {
+    7
+}
+
+/// A slot of a meal plan: a `date` in dated plans, where `day` is derived from
+/// it, or a `day` of the week otherwise.
+#[derive(Debug, Deserialize, Serialize, Validate)]
 pub struct RecipeMealsDTO {
     pub id_recipe: i64,
-    pub day: String,
-    pub meal_type: String
-}
\ No newline at end of file
+
+    #[serde(default)]
+    pub date: Option<NaiveDate>,
+
+    #[serde(default)]
+    pub day: Option<Day>,
+
+    pub meal_type: MealType,
+
+    /// Servings cooked in this slot, by default the recipe's own yield.
+    #[serde(default)]
+    #[validate(range(min = 1, max = 100))]
+    pub servings: Option<u32>
+}
+
+/// Narrows the meal plan list. An empty filter keeps every meal plan.
+#[derive(Debug, Default)]
+pub struct MealPlanFilter {
+    /// Meal plans with a recipe containing any of these, or with unlabelled
+    /// ingredients, are left out.
+    pub exclude_allergens: Vec<Allergen>
+}
+
+/// What is planned on a date across the dated meal plans running that day.
+#[derive(Debug, Serialize)]
+pub struct PlannedDayDTO {
+    pub date: NaiveDate,
+    pub day: Day,
+    pub meals: Vec<PlannedMealDTO>
+}
+
+#[derive(Debug, Serialize)]
+pub struct PlannedMealDTO {
+    pub id_mealplan: i64,
+    pub mealplan_name: String,
+    pub id_recipe: i64,
+    pub recipe_name: String,
+    pub meal_type: MealType
+}
+/// What to buy for a meal plan: each ingredient with the amounts of all its
+/// slots added up, weights in grams or kilograms and volumes in millilitres or
+/// litres. Amounts in pieces or other units are listed separately.
+#[derive(Debug, Serialize)]
+pub struct ShoppingListDTO {
+    pub id_mealplan: i64,
+    pub items: Vec<ShoppingItemDTO>
+}
+
+#[derive(Debug, Serialize)]
+pub struct ShoppingItemDTO {
+    pub id_ingredient: i64,
+    pub name: String,
+    pub amount: f64,
+    pub unit: String
+}
+
+/// Nutrition of a meal plan as a whole and per day.
+#[derive(Debug, Serialize)]
+pub struct MealPlanNutritionDTO {
+    pub id_mealplan: i64,
+    pub total: NutritionDTO,
+    pub days: Vec<DayNutritionDTO>
+}
+
+#[derive(Debug, Serialize)]
+pub struct DayNutritionDTO {
+    pub date: Option<NaiveDate>,
+    pub day: Day,
+    pub nutrition: NutritionDTO
+}
+
+#[derive(Debug, Serialize)]
+pub struct NutritionDTO {
+    pub calories: f64,
+    pub proteins: f64,
+    pub carbs: f64,
+    pub fats: f64
+}
+
+impl From<Nutrition> for NutritionDTO {
+    fn from(nutrition: Nutrition) -> Self {
+        let round = |grams: f64| (grams * 100.0).round() / 100.0;
+
+        NutritionDTO {
+            calories: nutrition.calories().round(),
+            proteins: round(nutrition.proteins),
+            carbs: round(nutrition.carbs),
+            fats: round(nutrition.fats)
+        }
+    }
+}
+
+/// The slots of a meal plan whose recipe does not fit the plan's diets, or
+/// cannot be confirmed to. The plan is only valid when there are none.
+#[derive(Debug, Serialize)]
+pub struct MealPlanDietCheckDTO {
+    pub id_mealplan: i64,
+    pub diets: Vec<Diet>,
+    pub valid: bool,
+    pub slots: Vec<SlotDietCheckDTO>
+}
+
+#[derive(Debug, Serialize)]
+pub struct SlotDietCheckDTO {
+    pub id_recipe: i64,
+    pub date: Option<NaiveDate>,
+    pub day: Day,
+    pub meal_type: MealType,
+    pub breaks: Vec<BrokenDietDTO>,
+    pub unverified: Vec<UnverifiedDietDTO>
+}
+
+#[derive(Debug, Serialize)]
+pub struct BrokenDietDTO {
+    pub diet: Diet,
+    pub reasons: Vec<String>
+}
+
+/// A diet nothing known breaks, and the ingredients lacking the data to confirm it.
+#[derive(Debug, Serialize)]
+pub struct UnverifiedDietDTO {
+    pub diet: Diet,
+    pub ingredients: Vec<String>
+}
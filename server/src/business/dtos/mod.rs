@@ -1,3 +1,7 @@
 pub mod ingredient_dto;
 pub mod recipe_dto;
-pub mod mealplan_dto;
\ No newline at end of file
+pub mod mealplan_dto;
+pub mod user_dto;
+pub mod api_key_dto;
+pub mod health_dto;
+pub mod tag_dto;
\ No newline at end of file
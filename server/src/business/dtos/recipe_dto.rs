@@ -1,6 +1,9 @@
-use validator::Validate;
+use validator::{Validate, ValidationError};
 use serde::{Deserialize, Serialize};
 
+use crate::business::access::Visibility;
+use crate::business::allergens::Allergen;
+use crate::business::diets::Diet;
 use crate::data_access::entities::recipes::Recipe;
 
 #[derive(Debug, Deserialize, Serialize, Validate)]
@@ -13,10 +16,95 @@ pub struct RecipeDTO {
     #[validate(length(min = 1, max = 45))]
     pub category: String,
 
-    #[validate(length(min = 1, max = 1000))]
+    /// Free-text instructions, or an introduction to `steps`.
+    #[serde(default)]
+    #[validate(length(max = 1000))]
     pub instructions: String,
 
-    pub ingredients: Vec<IngredientAmount>
+    #[serde(default)]
+    #[validate(length(max = 100))]
+    #[validate]
+    pub steps: Vec<RecipeStepDTO>,
+
+    /// Sum of the step durations, when any step has one. Ignored on input.
+    #[serde(default)]
+    pub total_time_minutes: Option<u32>,
+
+    /// People the ingredient amounts are for.
+    #[serde(default = "default_servings")]
+    #[validate(range(min = 1, max = 100))]
+    pub servings: u32,
+
+    pub ingredients: Vec<IngredientAmount>,
+
+    /// Other recipes used in this one, such as a sauce or a dough.
+    #[serde(default)]
+    #[validate]
+    pub components: Vec<RecipeComponentDTO>,
+
+    /// Besides these, the category is always a tag of the recipe.
+    #[serde(default)]
+    #[validate(length(max = 20), custom = "validate_tags")]
+    pub tags: Vec<String>,
+
+    /// Allergens of the ingredients, including those of the components.
+    /// Ignored on input.
+    #[serde(default)]
+    pub allergens: Vec<Allergen>,
+
+    /// Ingredients, including those of the components, whose allergens were
+    /// never given, so `allergens` may be missing some. Ignored on input.
+    #[serde(default)]
+    pub unlabelled_allergens: Vec<String>,
+
+    /// Diets the recipe fits, derived like `allergens`. Ignored on input.
+    #[serde(default)]
+    pub diets: Vec<Diet>,
+
+    /// Diets nothing known breaks, but which some ingredients lack the
+    /// allergens, diet attributes or macros to confirm. Ignored on input.
+    #[serde(default)]
+    pub unverified_diets: Vec<Diet>,
+
+    #[serde(default)]
+    pub visibility: Visibility,
+
+    #[serde(default)]
+    pub id_owner: Option<i64>
+}
+
+/// A step of a recipe. Steps are stored in the order they are given.
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct RecipeStepDTO {
+    #[validate(length(min = 1, max = 2000))]
+    pub text: String,
+
+    #[serde(default)]
+    #[validate(range(min = 1, max = 10080))]
+    pub duration_minutes: Option<u32>,
+
+    /// Ingredients of the recipe used in this step.
+    #[serde(default)]
+    pub ingredients: Vec<i64>
+}
+
+impl RecipeDTO {
+    /// A recipe needs instructions or at least one step.
+    pub fn has_instructions(&self) -> bool {
+        !self.instructions.trim().is_empty() || !self.steps.is_empty()
+    }
+}
+
+fn default_servings() -> u32 {
+    1
+}
+
+fn validate_tags(tags: &[String]) -> Result<(), ValidationError> {
+    if tags.iter().any(|tag| tag.trim().is_empty() || tag.chars().count() > 45) {
+        return Err(ValidationError::new("tag"));
+    }
+
+    Ok(())
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -33,7 +121,73 @@ impl From<Recipe> for RecipeDTO {
             name: recipe.name,
             category: recipe.category,
             instructions: recipe.instructions,
-            ingredients: vec![]
+            steps: vec![],
+            total_time_minutes: None,
+            servings: u32::try_from(recipe.servings).unwrap_or(1),
+            ingredients: vec![],
+            components: vec![],
+            tags: vec![],
+            allergens: vec![],
+            unlabelled_allergens: vec![],
+            diets: vec![],
+            unverified_diets: vec![],
+            visibility: Visibility::parse(&recipe.visibility),
+            id_owner: recipe.id_owner
+        }
+    }
+}
+
+/// A recipe used inside another one, in servings of the component recipe.
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct RecipeComponentDTO {
+    pub id_recipe: i64,
+
+    #[validate(range(min = 0.01, max = 100.0))]
+    pub servings: f64
+}
+
+/// Whether a recipe needs any or all of the tags it is filtered by.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TagMatch {
+    #[default]
+    Any,
+    All
+}
+
+impl TagMatch {
+    pub fn parse(tag_match: &str) -> Option<TagMatch> {
+        match tag_match.trim().to_lowercase().as_str() {
+            "any" => Some(TagMatch::Any),
+            "all" => Some(TagMatch::All),
+            _ => None
         }
     }
-}
\ No newline at end of file
+}
+
+/// Narrows the recipe list. An empty filter keeps every recipe.
+#[derive(Debug, Default)]
+pub struct RecipeFilter {
+    pub tags: Vec<String>,
+    pub tag_match: TagMatch,
+    /// Recipes containing any of these, or with unlabelled ingredients, are
+    /// left out.
+    pub exclude_allergens: Vec<Allergen>
+}
+
+impl RecipeFilter {
+    /// Whether a recipe passes the allergen exclusions. The tags are applied
+    /// by the query that lists the recipes.
+    pub fn matches(&self, recipe: &RecipeDTO) -> bool {
+        if !self.exclude_allergens.is_empty() && !recipe.unlabelled_allergens.is_empty() {
+            return false;
+        }
+
+        !self.exclude_allergens.iter().any(|allergen| recipe.allergens.contains(allergen))
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecipeImportReport {
+    pub name: String,
+    pub created_ingredients: Vec<String>
+}
@@ -0,0 +1,27 @@
+use validator::Validate;
+use serde::{Deserialize, Serialize};
+
+use crate::data_access::entities::tags::Tag;
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct TagDTO {
+    #[serde(default)]
+    pub id_tag: i64,
+
+    #[validate(length(min = 1, max = 45))]
+    pub name: String
+}
+
+impl From<Tag> for TagDTO {
+    fn from(tag: Tag) -> Self {
+        TagDTO {
+            id_tag: tag.id_tag,
+            name: tag.name
+        }
+    }
+}
+
+/// The stored form of a tag name: trimmed and lowercased.
+pub fn normalise_name(name: &str) -> String {
+    name.trim().to_lowercase()
+}
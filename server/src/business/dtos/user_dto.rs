@@ -0,0 +1,43 @@
+use validator::Validate;
+use serde::{Deserialize, Serialize};
+
+use crate::business::access::Role;
+use crate::data_access::entities::users::User;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct UserCredentialsDTO {
+    #[validate(length(min = 3, max = 45))]
+    pub username: String,
+
+    #[validate(length(min = 8, max = 128))]
+    pub password: String
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserDTO {
+    pub id_user: i64,
+    pub username: String,
+    pub role: Role
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RoleDTO {
+    pub role: Role
+}
+
+#[derive(Debug, Serialize)]
+pub struct TokenDTO {
+    pub token: String,
+    pub token_type: String,
+    pub expires_in: i64
+}
+
+impl From<User> for UserDTO {
+    fn from(user: User) -> Self {
+        UserDTO {
+            id_user: user.id_user,
+            username: user.username,
+            role: Role::parse(&user.role)
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use std::fs;
+use std::path::Path;
+
+use crate::business::services::ingredient_service::csv_delimiter;
+
+use super::{ingredient_name, Dataset, DatasetError, DatasetFood};
+
+/// Loads the CIQUAL composition table exported to CSV (`alim_code`, `alim_nom_fr`
+/// and the "(g/100 g)" nutrient columns). Values use a decimal comma; "traces" and
+/// "< x" are read as zero and "-" as missing.
+pub fn load(path: &Path) -> Result<Dataset, DatasetError> {
+    let data = fs::read(path)?;
+    let data = String::from_utf8_lossy(&data);
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(csv_delimiter(&data))
+        .trim(csv::Trim::All)
+        .from_reader(data.as_bytes());
+
+    let headers = reader.headers()?.clone();
+
+    let column = |prefixes: &[&str]| {
+        prefixes.iter().find_map(|prefix| {
+            headers.iter().position(|header| header.to_lowercase().starts_with(&prefix.to_lowercase()))
+        })
+    };
+
+    let code_column = column(&["alim_code"]);
+    let name_column = column(&["alim_nom_fr", "alim_nom_eng"]);
+    let proteins_column = column(&["Protéines, N x facteur de Jones", "Protéines, N x 6.25", "Protéines"]);
+    let carbs_column = column(&["Glucides"]);
+    let fats_column = column(&["Lipides"]);
+
+    let (Some(code_column), Some(name_column), Some(proteins_column), Some(carbs_column), Some(fats_column)) =
+        (code_column, name_column, proteins_column, carbs_column, fats_column) else {
+        return Err(DatasetError::Format(
+            "expected alim_code, alim_nom_fr, Protéines, Glucides and Lipides columns".to_string()
+        ));
+    };
+
+    let mut dataset = Dataset::default();
+
+    for record in reader.records() {
+        let record = record?;
+
+        let code = record.get(code_column).unwrap_or_default();
+        let name = record.get(name_column).unwrap_or_default();
+
+        if code.is_empty() || name.is_empty() {
+            continue;
+        }
+
+        let proteins = record.get(proteins_column).and_then(parse_value);
+        let carbs = record.get(carbs_column).and_then(parse_value);
+        let fats = record.get(fats_column).and_then(parse_value);
+
+        match (proteins, carbs, fats) {
+            (Some(proteins), Some(carbs), Some(fats)) => dataset.foods.push(DatasetFood {
+                source_id: format!("ciqual:{}", code),
+                name: ingredient_name(name),
+                proteins,
+                carbs,
+                fats
+            }),
+            _ => dataset.skipped.push(format!("ciqual:{} ({}): missing protein, carbohydrate or fat values", code, name))
+        }
+    }
+
+    Ok(dataset)
+}
+
+fn parse_value(value: &str) -> Option<f64> {
+    let value = value.trim();
+
+    if value.eq_ignore_ascii_case("traces") || value.starts_with('<') {
+        return Some(0.0);
+    }
+
+    value.replace(',', ".").parse::<f64>().ok().filter(|value| *value >= 0.0)
+}
@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use super::{ingredient_name, Dataset, DatasetError, DatasetFood};
+
+const PROTEIN: i64 = 1003;
+const TOTAL_FAT: i64 = 1004;
+const CARBOHYDRATE_BY_DIFFERENCE: i64 = 1005;
+const CARBOHYDRATE_BY_SUMMATION: i64 = 1050;
+const TOTAL_FAT_NLEA: i64 = 1085;
+
+#[derive(Debug, Deserialize)]
+struct FdcFood {
+    #[serde(rename = "fdcId")]
+    fdc_id: i64,
+    description: String,
+    #[serde(rename = "foodNutrients", default)]
+    food_nutrients: Vec<FdcFoodNutrient>
+}
+
+#[derive(Debug, Deserialize)]
+struct FdcFoodNutrient {
+    nutrient: Option<FdcNutrient>,
+    amount: Option<f64>
+}
+
+#[derive(Debug, Deserialize)]
+struct FdcNutrient {
+    id: i64
+}
+
+#[derive(Debug, Deserialize)]
+struct FdcCsvFood {
+    fdc_id: i64,
+    description: String
+}
+
+#[derive(Debug, Deserialize)]
+struct FdcCsvFoodNutrient {
+    fdc_id: i64,
+    nutrient_id: i64,
+    amount: Option<f64>
+}
+
+/// Loads a FoodData Central download: either a JSON file (Foundation, SR Legacy,
+/// Survey or Branded foods) or the directory of a CSV download, which must contain
+/// `food.csv` and `food_nutrient.csv`.
+pub fn load(path: &Path) -> Result<Dataset, DatasetError> {
+    if path.is_dir() {
+        load_csv(path)
+    } else {
+        load_json(path)
+    }
+}
+
+fn load_json(path: &Path) -> Result<Dataset, DatasetError> {
+    let reader = BufReader::new(File::open(path)?);
+    let document: HashMap<String, Vec<FdcFood>> = serde_json::from_reader(reader)?;
+
+    let mut dataset = Dataset::default();
+
+    for fdc_food in document.into_values().flatten() {
+        let nutrients: HashMap<i64, f64> = fdc_food.food_nutrients
+            .iter()
+            .filter_map(|food_nutrient| Some((food_nutrient.nutrient.as_ref()?.id, food_nutrient.amount?)))
+            .collect();
+
+        push_food(&mut dataset, fdc_food.fdc_id, &fdc_food.description, &nutrients);
+    }
+
+    Ok(dataset)
+}
+
+fn load_csv(directory: &Path) -> Result<Dataset, DatasetError> {
+    let food_path = directory.join("food.csv");
+    let food_nutrient_path = directory.join("food_nutrient.csv");
+
+    if !food_path.is_file() || !food_nutrient_path.is_file() {
+        return Err(DatasetError::Format(format!(
+            "{} must contain food.csv and food_nutrient.csv",
+            directory.display()
+        )));
+    }
+
+    let mut nutrients: HashMap<i64, HashMap<i64, f64>> = HashMap::new();
+
+    for row in csv::Reader::from_path(food_nutrient_path)?.deserialize::<FdcCsvFoodNutrient>() {
+        let row = row?;
+
+        if let (PROTEIN | TOTAL_FAT | CARBOHYDRATE_BY_DIFFERENCE | CARBOHYDRATE_BY_SUMMATION | TOTAL_FAT_NLEA, Some(amount)) = (row.nutrient_id, row.amount) {
+            nutrients.entry(row.fdc_id).or_default().insert(row.nutrient_id, amount);
+        }
+    }
+
+    let mut dataset = Dataset::default();
+    let no_nutrients = HashMap::new();
+
+    for row in csv::Reader::from_path(food_path)?.deserialize::<FdcCsvFood>() {
+        let row = row?;
+        let food_nutrients = nutrients.get(&row.fdc_id).unwrap_or(&no_nutrients);
+
+        push_food(&mut dataset, row.fdc_id, &row.description, food_nutrients);
+    }
+
+    Ok(dataset)
+}
+
+fn push_food(dataset: &mut Dataset, fdc_id: i64, description: &str, nutrients: &HashMap<i64, f64>) {
+    let proteins = nutrients.get(&PROTEIN);
+    let carbs = nutrients.get(&CARBOHYDRATE_BY_DIFFERENCE).or_else(|| nutrients.get(&CARBOHYDRATE_BY_SUMMATION));
+    let fats = nutrients.get(&TOTAL_FAT).or_else(|| nutrients.get(&TOTAL_FAT_NLEA));
+
+    match (proteins, carbs, fats) {
+        (Some(proteins), Some(carbs), Some(fats)) => dataset.foods.push(DatasetFood {
+            source_id: format!("fdc:{}", fdc_id),
+            name: ingredient_name(description),
+            proteins: proteins.max(0.0),
+            carbs: carbs.max(0.0),
+            fats: fats.max(0.0)
+        }),
+        _ => dataset.skipped.push(format!("fdc:{} ({}): missing protein, carbohydrate or fat values", fdc_id, description))
+    }
+}
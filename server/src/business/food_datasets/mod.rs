@@ -0,0 +1,78 @@
+pub mod ciqual;
+pub mod fooddata_central;
+
+use std::fmt;
+use std::path::Path;
+
+/// Maximum length of `Ingredients.Name`; longer dataset descriptions are cut.
+const MAX_NAME_LENGTH: usize = 45;
+
+#[derive(Debug, Clone, Copy)]
+pub enum DatasetFormat {
+    FoodDataCentral,
+    Ciqual
+}
+
+#[derive(Debug)]
+pub enum DatasetError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    Csv(csv::Error),
+    Format(String)
+}
+
+impl fmt::Display for DatasetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DatasetError::Io(err) => write!(f, "{}", err),
+            DatasetError::Json(err) => write!(f, "invalid JSON: {}", err),
+            DatasetError::Csv(err) => write!(f, "invalid CSV: {}", err),
+            DatasetError::Format(message) => write!(f, "{}", message)
+        }
+    }
+}
+
+impl From<std::io::Error> for DatasetError {
+    fn from(err: std::io::Error) -> Self {
+        DatasetError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for DatasetError {
+    fn from(err: serde_json::Error) -> Self {
+        DatasetError::Json(err)
+    }
+}
+
+impl From<csv::Error> for DatasetError {
+    fn from(err: csv::Error) -> Self {
+        DatasetError::Csv(err)
+    }
+}
+
+/// A food read from a dataset, with macros per 100 g.
+#[derive(Debug, Clone)]
+pub struct DatasetFood {
+    pub source_id: String,
+    pub name: String,
+    pub proteins: f64,
+    pub carbs: f64,
+    pub fats: f64
+}
+
+#[derive(Debug, Default)]
+pub struct Dataset {
+    pub foods: Vec<DatasetFood>,
+    pub skipped: Vec<String>
+}
+
+pub fn load(format: DatasetFormat, path: &Path) -> Result<Dataset, DatasetError> {
+    match format {
+        DatasetFormat::FoodDataCentral => fooddata_central::load(path),
+        DatasetFormat::Ciqual => ciqual::load(path)
+    }
+}
+
+fn ingredient_name(description: &str) -> String {
+    description.trim().chars().take(MAX_NAME_LENGTH).collect::<String>().trim_end().to_string()
+}
@@ -0,0 +1,129 @@
+use chrono::{Datelike, Duration, Local, NaiveDate, NaiveTime, Utc};
+
+use crate::business::meal_slots::{Day, MealType};
+
+const PRODUCT_ID: &str = "-//rust-api-rest-rocket//Meal Plan//EN";
+const MAX_LINE_OCTETS: usize = 75;
+
+/// When each meal starts and which week the plan is laid on.
+#[derive(Debug, Clone)]
+pub struct CalendarOptions {
+    pub week_start: NaiveDate,
+    pub breakfast: NaiveTime,
+    pub lunch: NaiveTime,
+    pub snack: NaiveTime,
+    pub dinner: NaiveTime,
+    pub duration_minutes: i64
+}
+
+impl Default for CalendarOptions {
+    fn default() -> Self {
+        let today = Local::now().date_naive();
+
+        CalendarOptions {
+            week_start: today - Duration::days(today.weekday().num_days_from_monday() as i64),
+            breakfast: NaiveTime::from_hms_opt(8, 0, 0).unwrap_or_default(),
+            lunch: NaiveTime::from_hms_opt(13, 0, 0).unwrap_or_default(),
+            snack: NaiveTime::from_hms_opt(17, 0, 0).unwrap_or_default(),
+            dinner: NaiveTime::from_hms_opt(21, 0, 0).unwrap_or_default(),
+            duration_minutes: 60
+        }
+    }
+}
+
+impl CalendarOptions {
+    fn meal_time(&self, meal_type: &MealType) -> Option<NaiveTime> {
+        match meal_type {
+            MealType::Breakfast => Some(self.breakfast),
+            MealType::Lunch => Some(self.lunch),
+            MealType::Snack => Some(self.snack),
+            MealType::Dinner => Some(self.dinner),
+            MealType::Custom(_) => None
+        }
+    }
+}
+
+/// One meal plan slot: a recipe on a date, or on a weekday of a generic week, at a meal.
+#[derive(Debug)]
+pub struct CalendarEvent {
+    pub uid: String,
+    pub date: Option<NaiveDate>,
+    pub day: Day,
+    pub meal_type: MealType,
+    pub summary: String,
+    pub description: String
+}
+
+/// Renders a meal plan as an iCalendar feed. Slots with a date become a VEVENT on
+/// that date; the others repeat weekly from the week of `options.week_start`.
+/// Meal types without a configured time, the custom ones, become all-day events.
+pub fn render(calendar_name: &str, events: &[CalendarEvent], options: &CalendarOptions) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{}", PRODUCT_ID),
+        "CALSCALE:GREGORIAN".to_string(),
+        format!("X-WR-CALNAME:{}", escape(calendar_name))
+    ];
+
+    let timestamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+
+    for event in events {
+        let date = event.date
+            .unwrap_or(options.week_start + Duration::days(event.day.num_days_from_monday()));
+
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}", event.uid));
+        lines.push(format!("DTSTAMP:{}", timestamp));
+
+        match options.meal_time(&event.meal_type) {
+            Some(time) => {
+                let start = date.and_time(time);
+                let end = start + Duration::minutes(options.duration_minutes);
+                lines.push(format!("DTSTART:{}", start.format("%Y%m%dT%H%M%S")));
+                lines.push(format!("DTEND:{}", end.format("%Y%m%dT%H%M%S")));
+            }
+            None => {
+                lines.push(format!("DTSTART;VALUE=DATE:{}", date.format("%Y%m%d")));
+                lines.push(format!("DTEND;VALUE=DATE:{}", (date + Duration::days(1)).format("%Y%m%d")));
+            }
+        }
+
+        if event.date.is_none() {
+            lines.push("RRULE:FREQ=WEEKLY".to_string());
+        }
+        lines.push(format!("SUMMARY:{}", escape(&event.summary)));
+        lines.push(format!("DESCRIPTION:{}", escape(&event.description)));
+        lines.push("END:VEVENT".to_string());
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|line| fold(line)).collect::<Vec<_>>().join("")
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Folds a content line at 75 octets without splitting characters and adds CRLF.
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 8);
+    let mut line_octets = 0;
+
+    for c in line.chars() {
+        if line_octets + c.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            line_octets = 1;
+        }
+        folded.push(c);
+        line_octets += c.len_utf8();
+    }
+
+    folded.push_str("\r\n");
+    folded
+}
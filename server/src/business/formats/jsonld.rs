@@ -0,0 +1,220 @@
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::business::dtos::recipe_dto::RecipeDTO;
+use crate::business::nutrition::Nutrition;
+use crate::business::units;
+
+/// Free-text instructions longer than this are imported as one step per line.
+const MAX_IMPORTED_INSTRUCTIONS_LENGTH: usize = 1000;
+
+const SCHEMA_CONTEXT: &str = "https://schema.org";
+
+#[derive(Debug, Serialize)]
+pub struct RecipeJsonLd {
+    #[serde(rename = "@context")]
+    pub context: &'static str,
+    #[serde(rename = "@type")]
+    pub kind: &'static str,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(rename = "recipeCategory")]
+    pub recipe_category: String,
+    #[serde(rename = "recipeYield")]
+    pub recipe_yield: String,
+    #[serde(rename = "totalTime", skip_serializing_if = "Option::is_none")]
+    pub total_time: Option<String>,
+    #[serde(rename = "recipeInstructions")]
+    pub recipe_instructions: RecipeInstructions,
+    #[serde(rename = "recipeIngredient")]
+    pub recipe_ingredient: Vec<String>,
+    pub nutrition: NutritionInformation
+}
+
+/// Instructions as text, or as `HowToStep`s for recipes with steps.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum RecipeInstructions {
+    Text(String),
+    Steps(Vec<HowToStep>)
+}
+
+#[derive(Debug, Serialize)]
+pub struct HowToStep {
+    #[serde(rename = "@type")]
+    pub kind: &'static str,
+    pub text: String
+}
+
+#[derive(Debug, Serialize)]
+pub struct NutritionInformation {
+    #[serde(rename = "@type")]
+    pub kind: &'static str,
+    pub calories: String,
+    #[serde(rename = "proteinContent")]
+    pub protein_content: String,
+    #[serde(rename = "carbohydrateContent")]
+    pub carbohydrate_content: String,
+    #[serde(rename = "fatContent")]
+    pub fat_content: String
+}
+
+/// Recipe fields read from a schema.org `Recipe` node.
+#[derive(Debug, Default)]
+pub struct ImportedRecipe {
+    pub name: String,
+    pub category: Option<String>,
+    pub servings: Option<u32>,
+    pub instructions: String,
+    pub steps: Vec<String>,
+    pub ingredients: Vec<String>
+}
+
+impl RecipeJsonLd {
+    /// With steps, the instructions become the `description`.
+    pub fn new(recipe: &RecipeDTO, ingredients: Vec<String>, nutrition: &Nutrition) -> Self {
+        let (description, recipe_instructions) = if recipe.steps.is_empty() {
+            (None, RecipeInstructions::Text(recipe.instructions.clone()))
+        } else {
+            let steps = recipe.steps
+                .iter()
+                .map(|step| HowToStep { kind: "HowToStep", text: step.text.clone() })
+                .collect();
+
+            (Some(recipe.instructions.clone()).filter(|instructions| !instructions.trim().is_empty()), RecipeInstructions::Steps(steps))
+        };
+
+        RecipeJsonLd {
+            context: SCHEMA_CONTEXT,
+            kind: "Recipe",
+            name: recipe.name.clone(),
+            description,
+            recipe_category: recipe.category.clone(),
+            recipe_yield: format!("{} {}", recipe.servings, if recipe.servings == 1 { "serving" } else { "servings" }),
+            total_time: recipe.total_time_minutes.map(iso_duration),
+            recipe_instructions,
+            recipe_ingredient: ingredients,
+            nutrition: NutritionInformation {
+                kind: "NutritionInformation",
+                calories: format!("{} kcal", nutrition.calories().round()),
+                protein_content: format!("{} g", units::format_amount(nutrition.proteins)),
+                carbohydrate_content: format!("{} g", units::format_amount(nutrition.carbs)),
+                fat_content: format!("{} g", units::format_amount(nutrition.fats))
+            }
+        }
+    }
+}
+
+/// An ISO 8601 duration such as `PT1H30M`.
+fn iso_duration(minutes: u32) -> String {
+    match (minutes / 60, minutes % 60) {
+        (0, minutes) => format!("PT{}M", minutes),
+        (hours, 0) => format!("PT{}H", hours),
+        (hours, minutes) => format!("PT{}H{}M", hours, minutes)
+    }
+}
+
+pub fn ingredient_line(amount: f64, unit: &str, name: &str) -> String {
+    format!("{} {} {}", units::format_amount(amount), unit, name)
+}
+
+/// Finds the `Recipe` node of a JSON-LD document, which may be the document itself,
+/// an element of a top-level array or an element of `@graph`.
+pub fn parse_recipe(document: &Value) -> Option<ImportedRecipe> {
+    let recipe = find_recipe(document)?;
+    let (instructions, steps) = recipe.get("recipeInstructions").map(instructions).unwrap_or_default();
+
+    Some(ImportedRecipe {
+        name: text(recipe.get("name")?)?,
+        category: recipe.get("recipeCategory").and_then(text),
+        servings: recipe.get("recipeYield").and_then(servings),
+        instructions,
+        steps,
+        ingredients: recipe
+            .get("recipeIngredient")
+            .or_else(|| recipe.get("ingredients"))
+            .map(strings)
+            .unwrap_or_default()
+    })
+}
+
+fn find_recipe(value: &Value) -> Option<&Value> {
+    match value {
+        Value::Array(items) => items.iter().find_map(find_recipe),
+        Value::Object(object) => {
+            if object.get("@type").is_some_and(is_recipe_type) {
+                Some(value)
+            } else {
+                object.get("@graph").and_then(find_recipe)
+            }
+        }
+        _ => None
+    }
+}
+
+fn is_recipe_type(kind: &Value) -> bool {
+    match kind {
+        Value::String(kind) => kind == "Recipe" || kind.ends_with("/Recipe"),
+        Value::Array(kinds) => kinds.iter().any(is_recipe_type),
+        _ => false
+    }
+}
+
+fn text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(text) => Some(text.trim().to_string()).filter(|text| !text.is_empty()),
+        Value::Array(items) => items.iter().find_map(text),
+        Value::Object(object) => object.get("text").or_else(|| object.get("name")).and_then(text),
+        _ => None
+    }
+}
+
+/// The first number of a yield such as `4`, `"4 servings"`, `"Serves 4-6"` or
+/// `["4", "4 servings"]`.
+fn servings(value: &Value) -> Option<u32> {
+    let count = match value {
+        Value::Number(number) => number.as_u64().and_then(|servings| u32::try_from(servings).ok()),
+        Value::Array(items) => items.iter().find_map(servings),
+        value => {
+            let text = text(value)?;
+            let digits: String = text.chars().skip_while(|c| !c.is_ascii_digit()).take_while(|c| c.is_ascii_digit()).collect();
+            digits.parse().ok()
+        }
+    };
+
+    count.filter(|count| *count > 0)
+}
+
+fn strings(value: &Value) -> Vec<String> {
+    match value {
+        Value::Array(items) => items.iter().filter_map(text).collect(),
+        value => text(value).into_iter().collect()
+    }
+}
+
+/// Text instructions, or the steps of a list of `HowToStep`s or `HowToSection`s.
+/// Text too long for the instructions is split into one step per line.
+fn instructions(value: &Value) -> (String, Vec<String>) {
+    match value {
+        Value::Array(_) => (String::new(), steps(value)),
+        Value::Object(object) if object.contains_key("itemListElement") => (String::new(), steps(value)),
+        value => {
+            let text = text(value).unwrap_or_default();
+
+            if text.chars().count() <= MAX_IMPORTED_INSTRUCTIONS_LENGTH {
+                (text, vec![])
+            } else {
+                (String::new(), text.lines().map(str::trim).filter(|line| !line.is_empty()).map(str::to_string).collect())
+            }
+        }
+    }
+}
+
+fn steps(value: &Value) -> Vec<String> {
+    match value {
+        Value::Array(items) => items.iter().flat_map(steps).collect(),
+        Value::Object(object) if object.contains_key("itemListElement") => steps(&object["itemListElement"]),
+        value => text(value).into_iter().collect()
+    }
+}
@@ -0,0 +1,2 @@
+pub mod ical;
+pub mod jsonld;
\ No newline at end of file
@@ -0,0 +1,311 @@
+use crate::business::units::Unit;
+
+/// Matches below this score are not suggested.
+const MIN_MATCH_SCORE: f64 = 0.35;
+
+const NUMBER_WORDS: [(&str, f64); 30] = [
+    ("a", 1.0), ("an", 1.0), ("one", 1.0), ("two", 2.0), ("three", 3.0), ("four", 4.0), ("five", 5.0),
+    ("six", 6.0), ("seven", 7.0), ("eight", 8.0), ("nine", 9.0), ("ten", 10.0), ("half", 0.5), ("dozen", 12.0),
+    ("un", 1.0), ("una", 1.0), ("uno", 1.0), ("dos", 2.0), ("tres", 3.0), ("cuatro", 4.0), ("cinco", 5.0),
+    ("seis", 6.0), ("siete", 7.0), ("ocho", 8.0), ("nueve", 9.0), ("diez", 10.0), ("medio", 0.5), ("media", 0.5),
+    ("docena", 12.0), ("doce", 12.0)
+];
+
+const CONNECTORS: [&str; 4] = ["de", "del", "of", "the"];
+
+const NAME_SUFFIXES: [&str; 5] = ["to taste", "a gusto", "al gusto", "as needed", "cantidad necesaria"];
+
+const STOPWORDS: [&str; 9] = ["de", "del", "la", "el", "los", "las", "of", "the", "and"];
+
+#[derive(Debug, PartialEq)]
+pub struct ParsedLine {
+    pub amount: Option<f64>,
+    pub unit: Option<Unit>,
+    pub name: String
+}
+
+/// Splits a free-text ingredient line such as "2 tbsp olive oil", "200 g de harina"
+/// or "1½ tazas de azúcar" into amount, unit and ingredient name.
+pub fn parse_line(line: &str) -> ParsedLine {
+    let line = line.trim();
+
+    let (mut amount, rest) = match leading_amount(line) {
+        Some((amount, rest)) => (Some(amount), rest),
+        None => (None, line)
+    };
+
+    let (unit, rest) = match leading_unit(rest) {
+        Some((unit, rest)) => (Some(unit), rest),
+        None => (None, rest)
+    };
+
+    if amount.is_none() && unit.is_some() {
+        amount = Some(1.0);
+    }
+
+    ParsedLine { amount, unit, name: ingredient_name(rest) }
+}
+
+/// Similarity between two ingredient names in `[0, 1]`, ignoring case, accents,
+/// plurals and connectors such as "de" or "of".
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let a = normalize(a);
+    let b = normalize(b);
+
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+
+    if a == b {
+        return 1.0;
+    }
+
+    let a_tokens: Vec<&str> = a.split(' ').collect();
+    let b_tokens: Vec<&str> = b.split(' ').collect();
+    let (shorter, longer) = if a_tokens.len() <= b_tokens.len() { (&a_tokens, &b_tokens) } else { (&b_tokens, &a_tokens) };
+
+    let containment = if shorter.iter().all(|token| longer.contains(token)) {
+        0.6 + 0.4 * shorter.len() as f64 / longer.len() as f64
+    } else {
+        0.0
+    };
+
+    containment.max(dice_coefficient(&a, &b))
+}
+
+/// Returns the candidates that resemble `name`, best first.
+pub fn rank_matches<'a, T>(name: &str, candidates: &'a [T], candidate_name: fn(&T) -> &str, limit: usize) -> Vec<(&'a T, f64)> {
+    let mut matches: Vec<(&T, f64)> = candidates
+        .iter()
+        .map(|candidate| (candidate, similarity(name, candidate_name(candidate))))
+        .filter(|(_, score)| *score >= MIN_MATCH_SCORE)
+        .collect();
+
+    matches.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+    matches.truncate(limit);
+    matches
+}
+
+fn leading_amount(text: &str) -> Option<(f64, &str)> {
+    let (mut amount, mut rest) = match number(text) {
+        Some(number) => number,
+        None => return number_word(text)
+    };
+
+    if amount.fract() == 0.0 {
+        if let Some((fraction, remaining)) = fraction(rest.trim_start()) {
+            amount += fraction;
+            rest = remaining;
+        }
+    }
+
+    // Ranges such as "2-3" or "2 a 3" keep the lower bound.
+    let trimmed = rest.trim_start();
+    for separator in ["-", "–", "to ", "a ", "o ", "or "] {
+        if let Some(upper) = trimmed.strip_prefix(separator) {
+            if let Some((_, remaining)) = number(upper.trim_start()) {
+                rest = remaining;
+                break;
+            }
+        }
+    }
+
+    Some((amount, rest))
+}
+
+fn number(text: &str) -> Option<(f64, &str)> {
+    if let Some(fraction) = fraction(text) {
+        return Some(fraction);
+    }
+
+    let end = text
+        .char_indices()
+        .find(|(index, c)| !(c.is_ascii_digit() || (*index > 0 && (*c == '.' || *c == ','))))
+        .map(|(index, _)| index)
+        .unwrap_or(text.len());
+
+    let digits = text[..end].trim_end_matches(['.', ',']);
+
+    if digits.is_empty() {
+        return None;
+    }
+
+    let value = digits.replace(',', ".").parse::<f64>().ok()?;
+
+    Some((value, &text[digits.len()..]))
+}
+
+fn fraction(text: &str) -> Option<(f64, &str)> {
+    let first = text.chars().next()?;
+
+    if let Some(fraction) = unicode_fraction(first) {
+        return Some((fraction, &text[first.len_utf8()..]));
+    }
+
+    let (numerator, rest) = text.split_once('/')?;
+    let numerator: f64 = numerator.parse().ok().filter(|_| numerator.chars().all(|c| c.is_ascii_digit()))?;
+
+    let end = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+    let denominator: f64 = rest[..end].parse().ok().filter(|denominator| *denominator > 0.0)?;
+
+    Some((numerator / denominator, &rest[end..]))
+}
+
+fn unicode_fraction(c: char) -> Option<f64> {
+    match c {
+        '½' => Some(1.0 / 2.0),
+        '⅓' => Some(1.0 / 3.0),
+        '⅔' => Some(2.0 / 3.0),
+        '¼' => Some(1.0 / 4.0),
+        '¾' => Some(3.0 / 4.0),
+        '⅕' => Some(1.0 / 5.0),
+        '⅖' => Some(2.0 / 5.0),
+        '⅗' => Some(3.0 / 5.0),
+        '⅘' => Some(4.0 / 5.0),
+        '⅙' => Some(1.0 / 6.0),
+        '⅚' => Some(5.0 / 6.0),
+        '⅛' => Some(1.0 / 8.0),
+        '⅜' => Some(3.0 / 8.0),
+        '⅝' => Some(5.0 / 8.0),
+        '⅞' => Some(7.0 / 8.0),
+        _ => None
+    }
+}
+
+fn number_word(text: &str) -> Option<(f64, &str)> {
+    let (word, rest) = text.split_once(char::is_whitespace)?;
+    let word = word.to_lowercase();
+
+    NUMBER_WORDS
+        .iter()
+        .find(|(number_word, _)| *number_word == word)
+        .map(|(_, value)| (*value, rest))
+}
+
+fn leading_unit(text: &str) -> Option<(Unit, &str)> {
+    let text = text.trim_start();
+    let (first, rest) = text.split_once(char::is_whitespace)?;
+
+    if let Some((second, remaining)) = rest.trim_start().split_once(char::is_whitespace) {
+        if let Some(unit) = Unit::parse(&format!("{} {}", first, second)) {
+            return Some((unit, remaining));
+        }
+    }
+
+    Unit::parse(first).map(|unit| (unit, rest))
+}
+
+fn ingredient_name(text: &str) -> String {
+    let mut name = text.trim();
+
+    if let Some((before, _)) = name.split_once(',') {
+        name = before;
+    }
+
+    let mut name = strip_parentheses(name);
+
+    loop {
+        let rest = CONNECTORS
+            .iter()
+            .find_map(|connector| strip_prefix_ignore_case(&name, &format!("{} ", connector)));
+
+        match rest {
+            Some(rest) => name = rest.trim_start().to_string(),
+            None => break
+        }
+    }
+
+    for suffix in NAME_SUFFIXES {
+        if let Some(rest) = strip_suffix_ignore_case(&name, suffix) {
+            name = rest.to_string();
+        }
+    }
+
+    name.trim().to_string()
+}
+
+/// `text` without `prefix`, compared ignoring case. Counts chars rather than
+/// bytes, since lowercasing can change the byte length of non-ASCII text.
+fn strip_prefix_ignore_case<'a>(text: &'a str, prefix: &str) -> Option<&'a str> {
+    let end = text.char_indices().nth(prefix.chars().count()).map_or(text.len(), |(index, _)| index);
+
+    (text[..end].to_lowercase() == prefix).then(|| &text[end..])
+}
+
+/// `text` without `suffix`, compared ignoring case, like `strip_prefix_ignore_case`.
+fn strip_suffix_ignore_case<'a>(text: &'a str, suffix: &str) -> Option<&'a str> {
+    let start = text.char_indices().rev().nth(suffix.chars().count().checked_sub(1)?)?.0;
+
+    (text[start..].to_lowercase() == suffix).then(|| &text[..start])
+}
+
+fn strip_parentheses(text: &str) -> String {
+    let mut depth = 0;
+    let mut result = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth > 0 => depth -= 1,
+            c if depth == 0 => result.push(c),
+            _ => {}
+        }
+    }
+
+    result.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn normalize(text: &str) -> String {
+    let text: String = text
+        .to_lowercase()
+        .chars()
+        .map(|c| match c {
+            'á' | 'à' | 'â' | 'ä' => 'a',
+            'é' | 'è' | 'ê' | 'ë' => 'e',
+            'í' | 'ì' | 'î' | 'ï' => 'i',
+            'ó' | 'ò' | 'ô' | 'ö' => 'o',
+            'ú' | 'ù' | 'û' | 'ü' => 'u',
+            'ñ' => 'n',
+            'ç' => 'c',
+            c if c.is_alphanumeric() => c,
+            _ => ' '
+        })
+        .collect();
+
+    text.split_whitespace()
+        .filter(|word| !STOPWORDS.contains(word))
+        .map(singular)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn singular(word: &str) -> &str {
+    if word.len() > 4 && (word.ends_with("oes") || ["nes", "res", "les", "des"].iter().any(|suffix| word.ends_with(suffix))) {
+        &word[..word.len() - 2]
+    } else if word.len() > 3 && word.ends_with('s') && !word.ends_with("ss") {
+        &word[..word.len() - 1]
+    } else {
+        word
+    }
+}
+
+fn dice_coefficient(a: &str, b: &str) -> f64 {
+    let bigrams = |text: &str| -> Vec<(char, char)> {
+        let chars: Vec<char> = format!(" {} ", text).chars().collect();
+        chars.windows(2).map(|pair| (pair[0], pair[1])).collect()
+    };
+
+    let a_bigrams = bigrams(a);
+    let mut b_bigrams = bigrams(b);
+    let total = a_bigrams.len() + b_bigrams.len();
+
+    let mut shared = 0;
+    for bigram in a_bigrams {
+        if let Some(index) = b_bigrams.iter().position(|other| *other == bigram) {
+            b_bigrams.swap_remove(index);
+            shared += 1;
+        }
+    }
+
+    2.0 * shared as f64 / total as f64
+}
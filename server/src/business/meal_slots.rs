@@ -0,0 +1,166 @@
+use std::fmt;
+
+use chrono::{Datelike, NaiveDate};
+use serde::de::{self, Deserializer};
+use serde::{Deserialize, Serialize, Serializer};
+
+/// Day of the week of a meal plan slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Day {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday
+}
+
+/// Accepted spellings, English and Spanish; the first one is the stored form.
+const DAY_NAMES: [(Day, &[&str]); 7] = [
+    (Day::Monday, &["monday", "mon", "lunes", "lun"]),
+    (Day::Tuesday, &["tuesday", "tue", "martes", "mar"]),
+    (Day::Wednesday, &["wednesday", "wed", "miércoles", "miercoles", "mié", "mie"]),
+    (Day::Thursday, &["thursday", "thu", "jueves", "jue"]),
+    (Day::Friday, &["friday", "fri", "viernes", "vie"]),
+    (Day::Saturday, &["saturday", "sat", "sábado", "sabado", "sáb", "sab"]),
+    (Day::Sunday, &["sunday", "sun", "domingo", "dom"])
+];
+
+impl Day {
+    pub fn parse(day: &str) -> Option<Day> {
+        let day = day.trim().trim_end_matches('.').to_lowercase();
+
+        DAY_NAMES
+            .iter()
+            .find(|(_, names)| names.contains(&day.as_str()))
+            .map(|(day, _)| *day)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        DAY_NAMES
+            .iter()
+            .find(|(day, _)| day == self)
+            .map(|(_, names)| names[0])
+            .unwrap_or_default()
+    }
+
+    pub fn num_days_from_monday(&self) -> i64 {
+        DAY_NAMES.iter().position(|(day, _)| day == self).unwrap_or_default() as i64
+    }
+
+    /// The day of the week `date` falls on.
+    pub fn of(date: NaiveDate) -> Day {
+        DAY_NAMES[date.weekday().num_days_from_monday() as usize].0
+    }
+}
+
+/// Meal of a meal plan slot. Meal types other than the four built-in ones are
+/// kept as `Custom`, lowercased; which of them are accepted is configured.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MealType {
+    Breakfast,
+    Lunch,
+    Snack,
+    Dinner,
+    Custom(String)
+}
+
+const MEAL_TYPE_NAMES: [(MealType, &[&str]); 4] = [
+    (MealType::Breakfast, &["breakfast", "desayuno"]),
+    (MealType::Lunch, &["lunch", "almuerzo", "comida"]),
+    (MealType::Snack, &["snack", "merienda"]),
+    (MealType::Dinner, &["dinner", "cena"])
+];
+
+impl MealType {
+    /// Reads a meal type, `None` only for blank input.
+    pub fn parse(meal_type: &str) -> Option<MealType> {
+        let meal_type = meal_type.trim().to_lowercase();
+
+        if meal_type.is_empty() {
+            return None;
+        }
+
+        let built_in = MEAL_TYPE_NAMES
+            .iter()
+            .find(|(_, names)| names.contains(&meal_type.as_str()))
+            .map(|(meal_type, _)| meal_type.clone());
+
+        Some(built_in.unwrap_or(MealType::Custom(meal_type)))
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            MealType::Custom(name) => name,
+            built_in => MEAL_TYPE_NAMES
+                .iter()
+                .find(|(meal_type, _)| meal_type == built_in)
+                .map(|(_, names)| names[0])
+                .unwrap_or_default()
+        }
+    }
+
+    pub fn is_custom(&self) -> bool {
+        matches!(self, MealType::Custom(_))
+    }
+
+    /// Position in a day: the built-in meals in order, then the custom ones.
+    pub fn order(&self) -> usize {
+        MEAL_TYPE_NAMES
+            .iter()
+            .position(|(meal_type, _)| meal_type == self)
+            .unwrap_or(MEAL_TYPE_NAMES.len())
+    }
+
+    /// Capitalised name, e.g. for calendar event titles.
+    pub fn label(&self) -> String {
+        let name = self.as_str();
+        let mut chars = name.chars();
+
+        match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => String::new()
+        }
+    }
+}
+
+impl fmt::Display for Day {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Display for MealType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for Day {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Day {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let day = String::deserialize(deserializer)?;
+
+        Day::parse(&day).ok_or_else(|| de::Error::custom(format!("unknown day: {}", day)))
+    }
+}
+
+impl Serialize for MealType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for MealType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let meal_type = String::deserialize(deserializer)?;
+
+        MealType::parse(&meal_type).ok_or_else(|| de::Error::custom("meal type cannot be empty"))
+    }
+}
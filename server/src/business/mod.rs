@@ -1,2 +1,12 @@
+pub mod access;
+pub mod allergens;
+pub mod diets;
 pub mod dtos;
-pub mod services;
\ No newline at end of file
+pub mod food_datasets;
+pub mod formats;
+pub mod ingredient_parser;
+pub mod meal_slots;
+pub mod names;
+pub mod nutrition;
+pub mod services;
+pub mod units;
\ No newline at end of file
@@ -0,0 +1,90 @@
+use serde::de::{self, Deserializer};
+use serde::{Deserialize, Serializer};
+
+/// Separators accepted between the names of a list given by clients.
+const LIST_SEPARATORS: [char; 3] = [',', ';', '|'];
+const STORED_SEPARATOR: &str = ",";
+
+/// Values known by several names, such as allergens and diets.
+pub trait Named: Copy + Ord + 'static {
+    /// What a value is called in error messages, e.g. "allergen".
+    const KIND: &'static str;
+    /// Accepted spellings, English and Spanish; the first one is the stored form.
+    const NAMES: &'static [(Self, &'static [&'static str])];
+
+    fn parse(name: &str) -> Option<Self> {
+        let name = name.trim().to_lowercase();
+
+        Self::NAMES
+            .iter()
+            .find(|(_, names)| names.contains(&name.as_str()))
+            .map(|(value, _)| *value)
+    }
+
+    fn as_str(&self) -> &'static str {
+        Self::NAMES
+            .iter()
+            .find(|(value, _)| value == self)
+            .map(|(_, names)| names[0])
+            .unwrap_or_default()
+    }
+}
+
+/// Reads a list of names separated by `,`, `;` or `|`, sorted and without
+/// repetitions. Fails with the first unknown name.
+pub fn parse_list<T: Named>(names: &str) -> Result<Vec<T>, String> {
+    let mut parsed = names
+        .split(LIST_SEPARATORS)
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| T::parse(name).ok_or_else(|| name.to_string()))
+        .collect::<Result<Vec<T>, String>>()?;
+
+    parsed.sort();
+    parsed.dedup();
+
+    Ok(parsed)
+}
+
+/// Reads a stored list, skipping unknown names.
+pub fn read_stored<T: Named>(stored: &str) -> Vec<T> {
+    merge_stored(&[stored])
+}
+
+/// The values of several stored lists together, e.g. the allergens of the
+/// ingredients of a recipe.
+pub fn merge_stored<T: Named, S: AsRef<str>>(lists: &[S]) -> Vec<T> {
+    let mut parsed: Vec<T> = lists
+        .iter()
+        .flat_map(|stored| stored.as_ref().split(STORED_SEPARATOR))
+        .filter_map(T::parse)
+        .collect();
+
+    parsed.sort();
+    parsed.dedup();
+    parsed
+}
+
+pub fn to_stored<T: Named>(values: &[T]) -> String {
+    join(values, STORED_SEPARATOR)
+}
+
+/// Joins values with `separator`, sorted and in their stored form.
+pub fn join<T: Named>(values: &[T], separator: &str) -> String {
+    let mut values = values.to_vec();
+
+    values.sort();
+    values.dedup();
+
+    values.iter().map(T::as_str).collect::<Vec<_>>().join(separator)
+}
+
+pub fn serialize<T: Named, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(value.as_str())
+}
+
+pub fn deserialize<'de, T: Named, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+    let name = String::deserialize(deserializer)?;
+
+    T::parse(&name).ok_or_else(|| de::Error::custom(format!("unknown {}: {}", T::KIND, name)))
+}
@@ -0,0 +1,50 @@
+use std::ops::AddAssign;
+
+use serde::Serialize;
+
+use crate::business::units;
+use crate::data_access::entities::ingredients::Ingredient;
+
+/// Macros in grams and the energy they provide, for an amount of food.
+#[derive(Debug, Default, Clone, Copy, Serialize)]
+pub struct Nutrition {
+    pub proteins: f64,
+    pub carbs: f64,
+    pub fats: f64
+}
+
+impl Nutrition {
+    /// Adds `amount` `unit` of an ingredient whose macros are given per 100 g.
+    /// Returns `false` when the unit cannot be converted to grams.
+    pub fn add_ingredient(&mut self, ingredient: &Ingredient, amount: f64, unit: &str) -> bool {
+        match units::to_grams(amount, unit) {
+            Some(grams) => {
+                self.proteins += ingredient.proteins * grams / 100.0;
+                self.carbs += ingredient.carbs * grams / 100.0;
+                self.fats += ingredient.fats * grams / 100.0;
+                true
+            }
+            None => false
+        }
+    }
+
+    pub fn calories(&self) -> f64 {
+        4.0 * self.proteins + 4.0 * self.carbs + 9.0 * self.fats
+    }
+
+    pub fn scaled(&self, factor: f64) -> Nutrition {
+        Nutrition {
+            proteins: self.proteins * factor,
+            carbs: self.carbs * factor,
+            fats: self.fats * factor
+        }
+    }
+}
+
+impl AddAssign for Nutrition {
+    fn add_assign(&mut self, other: Nutrition) {
+        self.proteins += other.proteins;
+        self.carbs += other.carbs;
+        self.fats += other.fats;
+    }
+}
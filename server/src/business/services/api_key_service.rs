@@ -0,0 +1,105 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use rocket::async_trait;
+use sha2::{Digest, Sha256};
+use sqlx::{Error, MySqlPool};
+use validator::Validate;
+
+use crate::business::access::Caller;
+use crate::business::dtos::api_key_dto::{ApiKeyDTO, ApiKeyRequestDTO, NewApiKeyDTO};
+use crate::data_access::entities::api_keys::ApiKey;
+use crate::data_access::repository::api_key_repository::ApiKeyRepository;
+
+const KEY_MARKER: &str = "mpk_";
+const KEY_RANDOM_BYTES: usize = 24;
+/// Characters of the key kept in clear so admins can tell keys apart.
+const PREFIX_LENGTH: usize = 12;
+
+#[derive(Debug)]
+pub enum ApiKeyError {
+    ValidationError,
+    DatabaseError(Error),
+    NotFound,
+    InvalidKey
+}
+
+pub struct ApiKeyService {
+    pub api_key_repository: ApiKeyRepository
+}
+
+impl ApiKeyService {
+    pub fn new(db_pool: MySqlPool) -> Self {
+        let api_key_repository = ApiKeyRepository::new(db_pool.clone());
+        Self { api_key_repository }
+    }
+
+    fn hash_key(key: &str) -> String {
+        Sha256::digest(key.as_bytes()).iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+}
+
+#[async_trait]
+pub trait ApiKeyServiceTrait: Send + Sync {
+    async fn create_api_key(&self, request: ApiKeyRequestDTO, caller: &Caller) -> Result<NewApiKeyDTO, ApiKeyError>;
+    async fn get_all_api_keys(&self) -> Result<Vec<ApiKeyDTO>, ApiKeyError>;
+    async fn revoke_api_key(&self, id: i64) -> Result<(), ApiKeyError>;
+    async fn authenticate(&self, key: &str) -> Result<ApiKeyDTO, ApiKeyError>;
+}
+
+#[async_trait]
+impl ApiKeyServiceTrait for ApiKeyService {
+    async fn create_api_key(&self, request: ApiKeyRequestDTO, caller: &Caller) -> Result<NewApiKeyDTO, ApiKeyError> {
+        if request.validate().is_err() {
+            return Err(ApiKeyError::ValidationError);
+        }
+
+        let mut random = [0u8; KEY_RANDOM_BYTES];
+        OsRng.fill_bytes(&mut random);
+
+        let key = format!("{}{}", KEY_MARKER, random.iter().map(|byte| format!("{:02x}", byte)).collect::<String>());
+
+        let api_key = ApiKey {
+            id_api_key: 0,
+            name: request.name,
+            prefix: key[..PREFIX_LENGTH].to_string(),
+            key_hash: Self::hash_key(&key),
+            scope: request.scope.as_str().to_string(),
+            id_user: caller.id_user,
+            created_at: chrono::Utc::now().naive_utc(),
+            last_used_at: None,
+            revoked_at: None
+        };
+
+        let id_api_key = self.api_key_repository.add_api_key(&api_key).await.map_err(ApiKeyError::DatabaseError)?;
+
+        Ok(NewApiKeyDTO {
+            id_api_key,
+            name: api_key.name,
+            scope: request.scope,
+            key
+        })
+    }
+
+    async fn get_all_api_keys(&self) -> Result<Vec<ApiKeyDTO>, ApiKeyError> {
+        let api_keys = self.api_key_repository.get_all_api_keys().await.map_err(ApiKeyError::DatabaseError)?;
+
+        Ok(api_keys.into_iter().map(ApiKeyDTO::from).collect())
+    }
+
+    async fn revoke_api_key(&self, id: i64) -> Result<(), ApiKeyError> {
+        if self.api_key_repository.get_by_id(id).await.map_err(ApiKeyError::DatabaseError)?.is_none() {
+            return Err(ApiKeyError::NotFound);
+        }
+
+        self.api_key_repository.revoke_api_key(id).await.map_err(ApiKeyError::DatabaseError)
+    }
+
+    async fn authenticate(&self, key: &str) -> Result<ApiKeyDTO, ApiKeyError> {
+        let api_key = self.api_key_repository.get_active_by_hash(&Self::hash_key(key)).await
+            .map_err(ApiKeyError::DatabaseError)?
+            .ok_or(ApiKeyError::InvalidKey)?;
+
+        self.api_key_repository.touch_api_key(api_key.id_api_key).await.map_err(ApiKeyError::DatabaseError)?;
+
+        Ok(api_key.into())
+    }
+}
@@ -0,0 +1,152 @@
+use std::collections::{HashMap, HashSet};
+
+use rocket::async_trait;
+use sqlx::MySqlPool;
+
+use crate::business::food_datasets::DatasetFood;
+use crate::business::services::ingredient_service::IngredientError;
+use crate::data_access::entities::ingredients::Ingredient;
+use crate::data_access::repository::ingredient_repository::IngredientRepository;
+
+#[derive(Debug)]
+pub enum FoodImportAction {
+    Create,
+    Update(Vec<String>),
+    Unchanged,
+    Skip(String)
+}
+
+#[derive(Debug)]
+pub struct FoodImportChange {
+    pub source_id: String,
+    pub name: String,
+    pub action: FoodImportAction
+}
+
+#[derive(Debug, Default)]
+pub struct FoodImportSummary {
+    pub changes: Vec<FoodImportChange>
+}
+
+impl FoodImportSummary {
+    pub fn count(&self, matches: fn(&FoodImportAction) -> bool) -> usize {
+        self.changes.iter().filter(|change| matches(&change.action)).count()
+    }
+}
+
+pub struct FoodImportService {
+    pub ingredient_repository: IngredientRepository,
+}
+
+impl FoodImportService {
+    pub fn new(db_pool: MySqlPool) -> Self {
+        let ingredient_repository = IngredientRepository::new(db_pool.clone());
+        Self { ingredient_repository }
+    }
+}
+
+#[async_trait]
+pub trait FoodImportServiceTrait: Send + Sync {
+    async fn import_foods(&self, foods: Vec<DatasetFood>, dry_run: bool) -> Result<FoodImportSummary, IngredientError>;
+}
+
+#[async_trait]
+impl FoodImportServiceTrait for FoodImportService {
+    /// Upserts dataset foods into the ingredient catalogue. Ingredients are matched by
+    /// source id first and then by name; matched ingredients keep their current name and
+    /// only get their macros and source id updated.
+    async fn import_foods(&self, foods: Vec<DatasetFood>, dry_run: bool) -> Result<FoodImportSummary, IngredientError> {
+        let existing = self.ingredient_repository.get_all_ingredients().await.map_err(IngredientError::DatabaseError)?;
+
+        let by_source: HashMap<String, &Ingredient> = existing
+            .iter()
+            .filter_map(|ingredient| Some((ingredient.source_id.clone()?, ingredient)))
+            .collect();
+        let by_name: HashMap<String, &Ingredient> = existing
+            .iter()
+            .map(|ingredient| (ingredient.name.to_lowercase(), ingredient))
+            .collect();
+
+        let mut seen_sources: HashSet<String> = HashSet::new();
+        let mut created_names: HashMap<String, String> = HashMap::new();
+        let mut summary = FoodImportSummary::default();
+        let mut ingredients: Vec<Ingredient> = Vec::new();
+
+        for food in foods {
+            let name_key = food.name.to_lowercase();
+
+            let action = if !seen_sources.insert(food.source_id.clone()) {
+                FoodImportAction::Skip("appears more than once in the dataset".to_string())
+            } else if let Some(ingredient) = by_source.get(&food.source_id) {
+                plan_update(ingredient, &food, &mut ingredients)
+            } else if let Some(ingredient) = by_name.get(&name_key) {
+                match &ingredient.source_id {
+                    Some(source_id) => FoodImportAction::Skip(format!("name already used by {}", source_id)),
+                    None => plan_update(ingredient, &food, &mut ingredients)
+                }
+            } else if let Some(source_id) = created_names.get(&name_key) {
+                FoodImportAction::Skip(format!("name already used by {}", source_id))
+            } else {
+                created_names.insert(name_key, food.source_id.clone());
+                ingredients.push(Ingredient {
+                    id_ingredient: 0,
+                    name: food.name.clone(),
+                    proteins: food.proteins,
+                    carbs: food.carbs,
+                    fats: food.fats,
+                    source_id: Some(food.source_id.clone()),
+                    allergens: None,
+                    diet_attributes: None
+                });
+                FoodImportAction::Create
+            };
+
+            summary.changes.push(FoodImportChange {
+                source_id: food.source_id,
+                name: food.name,
+                action
+            });
+        }
+
+        if !dry_run && !ingredients.is_empty() {
+            self.ingredient_repository.save_sourced_ingredients(&ingredients).await.map_err(IngredientError::DatabaseError)?;
+        }
+
+        Ok(summary)
+    }
+}
+
+fn plan_update(ingredient: &Ingredient, food: &DatasetFood, ingredients: &mut Vec<Ingredient>) -> FoodImportAction {
+    let mut differences = Vec::new();
+
+    for (field, current, imported) in [
+        ("proteins", ingredient.proteins, food.proteins),
+        ("carbs", ingredient.carbs, food.carbs),
+        ("fats", ingredient.fats, food.fats)
+    ] {
+        if (current - imported).abs() > f64::EPSILON {
+            differences.push(format!("{}: {} -> {}", field, current, imported));
+        }
+    }
+
+    if ingredient.source_id.as_deref() != Some(food.source_id.as_str()) {
+        differences.push(format!("source_id: none -> {}", food.source_id));
+    }
+
+    if differences.is_empty() {
+        return FoodImportAction::Unchanged;
+    }
+
+    ingredients.push(Ingredient {
+        id_ingredient: ingredient.id_ingredient,
+        name: ingredient.name.clone(),
+        proteins: food.proteins,
+        carbs: food.carbs,
+        fats: food.fats,
+        source_id: Some(food.source_id.clone()),
+        allergens: ingredient.allergens.clone(),
+        diet_attributes: ingredient.diet_attributes.clone()
+    });
+
+    FoodImportAction::Update(differences)
+}
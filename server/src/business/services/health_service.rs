@@ -0,0 +1,71 @@
+use std::time::Duration;
+
+use rocket::async_trait;
+use sqlx::MySqlPool;
+
+use crate::business::dtos::health_dto::{PoolUtilisationDTO, ReadinessDTO};
+use crate::data_access::repository::health_repository::HealthRepository;
+use crate::data_access::MIGRATOR;
+
+/// Readiness checks give up on the database after this long instead of waiting
+/// for the pool's acquire timeout.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+pub struct HealthService {
+    pub health_repository: HealthRepository
+}
+
+impl HealthService {
+    pub fn new(db_pool: MySqlPool) -> Self {
+        let health_repository = HealthRepository::new(db_pool.clone());
+        Self { health_repository }
+    }
+}
+
+#[async_trait]
+pub trait HealthServiceTrait: Send + Sync {
+    async fn check_readiness(&self, draining: bool) -> ReadinessDTO;
+}
+
+#[async_trait]
+impl HealthServiceTrait for HealthService {
+    /// Ready means the server is not shutting down, the database answers and every
+    /// embedded migration has been applied.
+    async fn check_readiness(&self, draining: bool) -> ReadinessDTO {
+        let database = matches!(tokio::time::timeout(CHECK_TIMEOUT, self.health_repository.ping()).await, Ok(Ok(())));
+
+        let pending_migrations = if database {
+            match tokio::time::timeout(CHECK_TIMEOUT, self.health_repository.get_applied_migrations()).await {
+                Ok(Ok(applied)) => MIGRATOR.iter()
+                    .map(|migration| migration.version)
+                    .filter(|version| !applied.contains(version))
+                    .collect(),
+                _ => MIGRATOR.iter().map(|migration| migration.version).collect()
+            }
+        } else {
+            vec![]
+        };
+
+        let (size, idle, max_connections) = self.health_repository.pool_stats();
+        let in_use = size.saturating_sub(idle);
+
+        ReadinessDTO {
+            status: if draining {
+                "draining"
+            } else if database && pending_migrations.is_empty() {
+                "ready"
+            } else {
+                "degraded"
+            }.to_string(),
+            database,
+            pending_migrations,
+            pool: PoolUtilisationDTO {
+                size,
+                idle,
+                in_use,
+                max_connections,
+                utilisation: if max_connections > 0 { f64::from(in_use) / f64::from(max_connections) } else { 0.0 }
+            }
+        }
+    }
+}
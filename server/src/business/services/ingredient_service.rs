@@ -1,16 +1,26 @@
+use std::collections::{HashMap, HashSet};
+
 use rocket::async_trait;
-use crate::business::dtos::ingredient_dto::IngredientDTO;
+use crate::business::names;
+use crate::business::dtos::ingredient_dto::{IngredientDTO, IngredientCsvRow, IngredientImportReport, IngredientImportLineError, ParsedIngredientDTO, IngredientMatchDTO};
+use crate::business::ingredient_parser;
 use crate::data_access::entities::ingredients::Ingredient;
 use crate::data_access::repository::ingredient_repository::IngredientRepository;
-use validator::Validate;
+use validator::{Validate, ValidationError};
 use sqlx::{MySqlPool, Error};
+use tracing::instrument;
+
+const CSV_REQUIRED_COLUMNS: [&str; 4] = ["name", "proteins", "carbs", "fats"];
+const MAX_SUGGESTED_MATCHES: usize = 3;
 
 #[derive(Debug)]
 pub enum IngredientError {
     ValidationError,
     DatabaseError(Error),
     DuplicateNameError,
-    NotFound
+    NotFound,
+    CsvError(csv::Error),
+    ImportError(IngredientImportReport)
 }
 
 pub struct IngredientService {
@@ -30,10 +40,14 @@ pub trait IngredientServiceTrait: Send + Sync {
     async fn get_all_ingredients(&self) -> Result<Vec<IngredientDTO>, IngredientError>;
     async fn update_ingredient(&self, ingredient: IngredientDTO) -> Result<(), IngredientError>;
     async fn delete_ingredient(&self, id: i64) -> Result<(), IngredientError>;
+    async fn export_ingredients_csv(&self) -> Result<String, IngredientError>;
+    async fn import_ingredients_csv(&self, data: &str) -> Result<IngredientImportReport, IngredientError>;
+    async fn parse_ingredient_lines(&self, lines: Vec<String>) -> Result<Vec<ParsedIngredientDTO>, IngredientError>;
 }
 
 #[async_trait]
 impl IngredientServiceTrait for IngredientService  {
+    #[instrument(name = "IngredientService::add_ingredient", skip_all)]
     async fn add_ingredient(&self, ingredient: IngredientDTO) -> Result<(), IngredientError> {
         if ingredient.validate().is_err() {
             return Err(IngredientError::ValidationError);
@@ -48,7 +62,10 @@ impl IngredientServiceTrait for IngredientService  {
             name: ingredient.name.clone(),
             proteins: ingredient.proteins,
             carbs: ingredient.carbs,
-            fats: ingredient.fats
+            fats: ingredient.fats,
+            source_id: None,
+            allergens: ingredient.allergens.as_deref().map(names::to_stored),
+            diet_attributes: ingredient.diet_attributes.as_deref().map(names::to_stored)
         };
 
         match self.ingredient_repository.add_ingredient(&new_ingredient).await {
@@ -57,6 +74,7 @@ impl IngredientServiceTrait for IngredientService  {
         }
     }
      
+    #[instrument(name = "IngredientService::get_all_ingredients", skip_all)]
     async fn get_all_ingredients(&self) -> Result<Vec<IngredientDTO>, IngredientError> {
         let ingredients = self.ingredient_repository.get_all_ingredients().await.map_err(IngredientError::DatabaseError)?;
     
@@ -66,6 +84,7 @@ impl IngredientServiceTrait for IngredientService  {
     }
 
      
+    #[instrument(name = "IngredientService::update_ingredient", skip_all)]
     async fn update_ingredient(&self, ingredient_dto: IngredientDTO) -> Result<(), IngredientError> {
         if ingredient_dto.validate().is_err() {
             return Err(IngredientError::ValidationError);
@@ -82,6 +101,14 @@ impl IngredientServiceTrait for IngredientService  {
                 ingredient.proteins = ingredient_dto.proteins;
                 ingredient.fats = ingredient_dto.fats;
 
+                if let Some(ingredient_allergens) = &ingredient_dto.allergens {
+                    ingredient.allergens = Some(names::to_stored(ingredient_allergens));
+                }
+
+                if let Some(diet_attributes) = &ingredient_dto.diet_attributes {
+                    ingredient.diet_attributes = Some(names::to_stored(diet_attributes));
+                }
+
                 match self.ingredient_repository.update_ingredient(&ingredient).await {
                     Ok(_) => Ok(()),
                     Err(err) => Err(IngredientError::DatabaseError(err)),
@@ -97,6 +124,7 @@ impl IngredientServiceTrait for IngredientService  {
         }
     }
 
+    #[instrument(name = "IngredientService::delete_ingredient", skip_all, fields(id = id))]
     async fn delete_ingredient(&self, id: i64) -> Result<(), IngredientError> {
         if self.ingredient_repository.is_ingredient_in_recipe_ingredient(id).await.map_err(IngredientError::DatabaseError)? {
             return Err(IngredientError::DuplicateNameError);
@@ -107,4 +135,234 @@ impl IngredientServiceTrait for IngredientService  {
             Err(err) => Err(IngredientError::DatabaseError(err))
         }
     }
-}
\ No newline at end of file
+
+    #[instrument(name = "IngredientService::export_ingredients_csv", skip_all)]
+    async fn export_ingredients_csv(&self) -> Result<String, IngredientError> {
+        let ingredients = self.get_all_ingredients().await?;
+
+        let mut writer = csv::Writer::from_writer(Vec::new());
+
+        for ingredient in ingredients {
+            writer.serialize(IngredientCsvRow::from(ingredient)).map_err(IngredientError::CsvError)?;
+        }
+
+        let data = writer.into_inner().map_err(|err| IngredientError::CsvError(err.into_error().into()))?;
+
+        Ok(String::from_utf8_lossy(&data).into_owned())
+    }
+
+    #[instrument(name = "IngredientService::import_ingredients_csv", skip_all)]
+    async fn import_ingredients_csv(&self, data: &str) -> Result<IngredientImportReport, IngredientError> {
+        let existing = self.ingredient_repository.get_all_ingredients().await.map_err(IngredientError::DatabaseError)?;
+
+        let ids_by_name: HashMap<String, i64> = existing
+            .iter()
+            .map(|ingredient| (ingredient.name.to_lowercase(), ingredient.id_ingredient))
+            .collect();
+        // Rows without allergens or diet attributes keep the stored ones.
+        let existing: HashMap<i64, Ingredient> = existing
+            .into_iter()
+            .map(|ingredient| (ingredient.id_ingredient, ingredient))
+            .collect();
+
+        let mut report = IngredientImportReport::default();
+
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(csv_delimiter(data))
+            .trim(csv::Trim::All)
+            .from_reader(data.as_bytes());
+
+        let headers = match reader.headers() {
+            Ok(headers) => headers.clone(),
+            Err(err) => {
+                report.errors.push(line_error(1, None, err.to_string()));
+                return Err(IngredientError::ImportError(report));
+            }
+        };
+
+        for column in CSV_REQUIRED_COLUMNS {
+            if !headers.iter().any(|header| header == column) {
+                report.errors.push(line_error(1, Some(column.to_string()), "missing column".to_string()));
+            }
+        }
+
+        if !report.errors.is_empty() {
+            return Err(IngredientError::ImportError(report));
+        }
+
+        let mut seen_names: HashSet<String> = HashSet::new();
+        let mut seen_ids: HashSet<i64> = HashSet::new();
+        let mut ingredients: Vec<Ingredient> = Vec::new();
+
+        for record in reader.records() {
+            let record = match record {
+                Ok(record) => record,
+                Err(err) => {
+                    let line = err.position().map(|position| position.line()).unwrap_or(0);
+                    report.errors.push(line_error(line, None, err.to_string()));
+                    continue;
+                }
+            };
+
+            let line = record.position().map(|position| position.line()).unwrap_or(0);
+
+            let row: IngredientCsvRow = match record.deserialize(Some(&headers)) {
+                Ok(row) => row,
+                Err(err) => {
+                    let (field, message) = match err.kind() {
+                        csv::ErrorKind::Deserialize { err, .. } => (
+                            err.field().and_then(|index| headers.get(index as usize)).map(str::to_string),
+                            err.kind().to_string()
+                        ),
+                        _ => (None, err.to_string())
+                    };
+                    report.errors.push(line_error(line, field, message));
+                    continue;
+                }
+            };
+
+            let ingredient_dto = match IngredientDTO::try_from(row) {
+                Ok(ingredient_dto) => ingredient_dto,
+                Err((field, message)) => {
+                    report.errors.push(line_error(line, Some(field.to_string()), message));
+                    continue;
+                }
+            };
+
+            if let Err(errors) = ingredient_dto.validate() {
+                let mut field_errors: Vec<_> = errors.field_errors().into_iter().collect();
+                field_errors.sort_by_key(|(field, _)| *field);
+
+                for (field, errors) in field_errors {
+                    for error in errors {
+                        report.errors.push(line_error(line, Some(field.to_string()), validation_message(error)));
+                    }
+                }
+                continue;
+            }
+
+            let name_key = ingredient_dto.name.to_lowercase();
+
+            if !seen_names.insert(name_key.clone()) {
+                report.errors.push(line_error(line, Some("name".to_string()), "name appears more than once in the file".to_string()));
+                continue;
+            }
+
+            let id_ingredient = if ingredient_dto.id_ingredient > 0 {
+                if !existing.contains_key(&ingredient_dto.id_ingredient) {
+                    report.errors.push(line_error(line, Some("id_ingredient".to_string()), "ingredient not found".to_string()));
+                    continue;
+                }
+
+                if !seen_ids.insert(ingredient_dto.id_ingredient) {
+                    report.errors.push(line_error(line, Some("id_ingredient".to_string()), "id appears more than once in the file".to_string()));
+                    continue;
+                }
+
+                if ids_by_name.get(&name_key).is_some_and(|id| *id != ingredient_dto.id_ingredient) {
+                    report.errors.push(line_error(line, Some("name".to_string()), "name already used by another ingredient".to_string()));
+                    continue;
+                }
+
+                ingredient_dto.id_ingredient
+            } else {
+                match ids_by_name.get(&name_key) {
+                    Some(id) if !seen_ids.insert(*id) => {
+                        report.errors.push(line_error(line, Some("name".to_string()), "ingredient is updated more than once in the file".to_string()));
+                        continue;
+                    }
+                    Some(id) => *id,
+                    None => 0
+                }
+            };
+
+            let stored = existing.get(&id_ingredient);
+
+            ingredients.push(Ingredient {
+                id_ingredient,
+                name: ingredient_dto.name,
+                proteins: ingredient_dto.proteins,
+                carbs: ingredient_dto.carbs,
+                fats: ingredient_dto.fats,
+                source_id: None,
+                allergens: match &ingredient_dto.allergens {
+                    Some(ingredient_allergens) => Some(names::to_stored(ingredient_allergens)),
+                    None => stored.and_then(|ingredient| ingredient.allergens.clone())
+                },
+                diet_attributes: match &ingredient_dto.diet_attributes {
+                    Some(diet_attributes) => Some(names::to_stored(diet_attributes)),
+                    None => stored.and_then(|ingredient| ingredient.diet_attributes.clone())
+                }
+            });
+        }
+
+        if !report.errors.is_empty() {
+            return Err(IngredientError::ImportError(report));
+        }
+
+        self.ingredient_repository.import_ingredients(&ingredients).await.map_err(IngredientError::DatabaseError)?;
+
+        report.created = ingredients.iter().filter(|ingredient| ingredient.id_ingredient == 0).count();
+        report.updated = ingredients.len() - report.created;
+
+        Ok(report)
+    }
+
+    #[instrument(name = "IngredientService::parse_ingredient_lines", skip_all)]
+    async fn parse_ingredient_lines(&self, lines: Vec<String>) -> Result<Vec<ParsedIngredientDTO>, IngredientError> {
+        let catalogue = self.ingredient_repository.get_all_ingredients().await.map_err(IngredientError::DatabaseError)?;
+
+        let parsed_lines = lines
+            .into_iter()
+            .map(|line| {
+                let parsed = ingredient_parser::parse_line(&line);
+
+                let matches = ingredient_parser::rank_matches(&parsed.name, &catalogue, |ingredient| &ingredient.name, MAX_SUGGESTED_MATCHES)
+                    .into_iter()
+                    .map(|(ingredient, score)| IngredientMatchDTO {
+                        id_ingredient: ingredient.id_ingredient,
+                        name: ingredient.name.clone(),
+                        score: (score * 100.0).round() / 100.0
+                    })
+                    .collect();
+
+                ParsedIngredientDTO {
+                    line,
+                    amount: parsed.amount,
+                    unit: parsed.unit.map(|unit| unit.symbol().to_string()),
+                    name: parsed.name,
+                    matches
+                }
+            })
+            .collect();
+
+        Ok(parsed_lines)
+    }
+}
+
+
+pub(crate) fn csv_delimiter(data: &str) -> u8 {
+    let header = data.lines().next().unwrap_or_default();
+
+    if header.matches(';').count() > header.matches(',').count() {
+        b';'
+    } else {
+        b','
+    }
+}
+
+fn line_error(line: u64, field: Option<String>, message: String) -> IngredientImportLineError {
+    IngredientImportLineError { line, field, message }
+}
+
+fn validation_message(error: &ValidationError) -> String {
+    match error.code.as_ref() {
+        "length" => format!(
+            "length must be between {} and {} characters",
+            error.params.get("min").map(|min| min.to_string()).unwrap_or_default(),
+            error.params.get("max").map(|max| max.to_string()).unwrap_or_default()
+        ),
+        "range" => "must not be negative".to_string(),
+        code => format!("failed {} validation", code)
+    }
+}
@@ -1,7 +1,20 @@
+use std::collections::{BTreeMap, HashMap};
+use std::collections::hash_map::Entry;
+
+use chrono::NaiveDate;
 use rocket::async_trait;
-use crate::{business::dtos::mealplan_dto::{MealPlanDTO, RecipeMealsDTO}, data_access::{repository::{mealplan_repository::MealPlanRepository, recipe_repository::RecipeRepository, recipemeal_repository::MealRecipeRepository}, entities::{meal_plans::MealPlans, recipe_meals::RecipeMeals}}};
+use crate::business::access::{self, Caller, Visibility};
+use crate::business::allergens::Allergen;
+use crate::business::diets::DietProfile;
+use crate::business::formats::ical::{self, CalendarEvent, CalendarOptions};
+use crate::business::meal_slots::{Day, MealType};
+use crate::business::names;
+use crate::business::nutrition::Nutrition;
+use crate::business::units::{self, Unit};
+use crate::{business::dtos::mealplan_dto::{BrokenDietDTO, DayNutritionDTO, MealPlanDietCheckDTO, MealPlanDTO, MealPlanFilter, SlotDietCheckDTO, MealPlanNutritionDTO, PlannedDayDTO, PlannedMealDTO, RecipeMealsDTO, ShoppingItemDTO, ShoppingListDTO, UnverifiedDietDTO}, data_access::{repository::{finish_transaction, ingredient_repository::IngredientRepository, mealplan_repository::MealPlanRepository, recipe_repository::RecipeRepository, recipeingredients_repository::RecipeIngredientsRepository, recipemeal_repository::MealRecipeRepository, recipestep_repository::RecipeStepRepository}, entities::{ingredients::Ingredient, meal_plans::MealPlans, recipe_ingredients::RecipeIngredients, recipe_meals::RecipeMeals, recipes::Recipe}}};
 use validator::Validate;
 use sqlx::{MySqlPool, Error};
+use tracing::instrument;
 
 #[derive(Debug)]
 pub enum MealPlanError {
@@ -9,125 +22,584 @@ pub enum MealPlanError {
     DatabaseError(Error),
     DuplicateNameError,
     NotFound,
-    NotImplemented
+    Forbidden
 }
 
 pub struct MealPlanService {
+    pub db_pool: MySqlPool,
     pub mealplan_repository: MealPlanRepository,
     pub recipe_repository: RecipeRepository,
-    pub recipemeal_repository: MealRecipeRepository
+    pub recipemeal_repository: MealRecipeRepository,
+    pub recipeingredients_repository: RecipeIngredientsRepository,
+    pub ingredient_repository: IngredientRepository,
+    pub recipestep_repository: RecipeStepRepository,
+    custom_meal_types: Vec<MealType>
+}
+
+/// A slot with its recipe's ingredients and how much to scale them by to cook
+/// the slot's servings.
+struct CookedSlot {
+    slot: RecipeMeals,
+    factor: f64,
+    ingredients: Vec<RecipeIngredients>
 }
 
 impl MealPlanService {
-    pub fn new(db_pool: MySqlPool) -> Self {
+    /// Slots may use the built-in meal types and `custom_meal_types`.
+    pub fn new(db_pool: MySqlPool, custom_meal_types: Vec<MealType>) -> Self {
         let mealplan_repository = MealPlanRepository::new(db_pool.clone());
         let recipe_repository = RecipeRepository::new(db_pool.clone());
         let recipemeal_repository = MealRecipeRepository::new(db_pool.clone());
-        Self { mealplan_repository, recipe_repository, recipemeal_repository }
+        let recipeingredients_repository = RecipeIngredientsRepository::new(db_pool.clone());
+        let ingredient_repository = IngredientRepository::new(db_pool.clone());
+        let recipestep_repository = RecipeStepRepository::new(db_pool.clone());
+        Self {
+            db_pool,
+            mealplan_repository,
+            recipe_repository,
+            recipemeal_repository,
+            recipeingredients_repository,
+            ingredient_repository,
+            recipestep_repository,
+            custom_meal_types
+        }
+    }
+
+    fn are_meal_types_allowed(&self, recipes: &[RecipeMealsDTO]) -> bool {
+        recipes
+            .iter()
+            .all(|recipe| !recipe.meal_type.is_custom() || self.custom_meal_types.contains(&recipe.meal_type))
+    }
+
+    /// Reads a stored slot. Rows the normalising migration could not map are skipped.
+    fn map_slot(recipe_meal: &RecipeMeals) -> Option<(Day, MealType)> {
+        let day = Day::parse(&recipe_meal.day);
+        let meal_type = MealType::parse(&recipe_meal.meal_type);
+
+        if day.is_none() || meal_type.is_none() {
+            log::warn!(
+                "Skipping meal plan slot {} with unknown day or meal type: {} {}",
+                recipe_meal.id_recipe_meal, recipe_meal.day, recipe_meal.meal_type
+            );
+        }
+
+        day.zip(meal_type)
+    }
+
+    /// Slots of a dated plan need a date within the plan, matching `day` if given;
+    /// slots of a generic week need a day and no date.
+    fn map_recipe_meals(id_mealplan: i64, meal_plan: &MealPlanDTO) -> Result<Vec<RecipeMeals>, MealPlanError> {
+        meal_plan.recipes
+            .iter()
+            .map(|recipe| {
+                let (day, date) = match (meal_plan.start_date, recipe.date, recipe.day) {
+                    (Some(start_date), Some(date), day) => {
+                        let offset = (date - start_date).num_days();
+
+                        if offset < 0 || offset >= i64::from(meal_plan.length_days) || day.is_some_and(|day| day != Day::of(date)) {
+                            return Err(MealPlanError::ValidationError);
+                        }
+
+                        (Day::of(date), Some(date))
+                    }
+                    (None, None, Some(day)) => (day, None),
+                    _ => return Err(MealPlanError::ValidationError)
+                };
+
+                Ok(RecipeMeals {
+                    id_recipe_meal: 0,
+                    day: day.as_str().to_string(),
+                    meal_type: recipe.meal_type.as_str().to_string(),
+                    date,
+                    servings: recipe.servings.map(|servings| servings as i32),
+                    recipes_id: recipe.id_recipe,
+                    mealplan_id: id_mealplan
+                })
+            })
+            .collect()
+    }
+
+    /// A concurrent write of the same name hits the owner's unique index.
+    fn write_error(err: Error) -> MealPlanError {
+        match err {
+            Error::Database(ref db_err) if db_err.is_unique_violation() => MealPlanError::DuplicateNameError,
+            err => MealPlanError::DatabaseError(err)
+        }
+    }
+
+    /// Loads a meal plan the caller is allowed to change.
+    async fn get_owned_mealplan(&self, id: i64, caller: &Caller) -> Result<MealPlans, MealPlanError> {
+        let meal_plan = self.mealplan_repository.get_by_id(id).await.map_err(MealPlanError::DatabaseError)?
+            .ok_or(MealPlanError::NotFound)?;
+
+        if !access::can_modify(meal_plan.id_owner, caller) {
+            return Err(MealPlanError::Forbidden);
+        }
+
+        Ok(meal_plan)
+    }
+
+    /// Loads a meal plan the caller is allowed to see.
+    async fn get_visible_mealplan(&self, id: i64, caller: Option<&Caller>) -> Result<MealPlans, MealPlanError> {
+        self.mealplan_repository.get_by_id(id).await.map_err(MealPlanError::DatabaseError)?
+            .filter(|meal_plan| access::can_view(meal_plan.id_owner, Visibility::parse(&meal_plan.visibility), caller))
+            .ok_or(MealPlanError::NotFound)
+    }
+
+    /// The slots of a meal plan with their recipes' ingredients, including those
+    /// of component recipes. Slots with an unknown day or meal type, or whose
+    /// recipe is gone, are skipped.
+    async fn get_cooked_slots(&self, id_mealplan: i64) -> Result<Vec<CookedSlot>, MealPlanError> {
+        let recipe_meals = self.recipemeal_repository.get_all_recipe_mealplan(id_mealplan).await.map_err(MealPlanError::DatabaseError)?;
+
+        let mut recipes = HashMap::new();
+        let mut cooked_slots = Vec::new();
+
+        for recipe_meal in recipe_meals {
+            if Self::map_slot(&recipe_meal).is_none() {
+                continue;
+            }
+
+            let recipe = match recipes.entry(recipe_meal.recipes_id) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => {
+                    let recipe = self.recipe_repository.get_by_id(recipe_meal.recipes_id).await.map_err(MealPlanError::DatabaseError)?;
+                    let ingredients = match &recipe {
+                        Some(recipe) => self.recipeingredients_repository.get_expanded_recipe_ingredients(recipe.id_recipe).await.map_err(MealPlanError::DatabaseError)?,
+                        None => vec![]
+                    };
+
+                    entry.insert(recipe.map(|recipe| (recipe.servings.max(1), ingredients)))
+                }
+            };
+
+            let Some((recipe_servings, ingredients)) = recipe else {
+                continue;
+            };
+
+            let factor = f64::from(recipe_meal.servings.unwrap_or(*recipe_servings)) / f64::from(*recipe_servings);
+
+            cooked_slots.push(CookedSlot {
+                slot: recipe_meal,
+                factor,
+                ingredients: ingredients.clone()
+            });
+        }
+
+        Ok(cooked_slots)
+    }
+
+    /// The name, instructions and numbered steps of a recipe, for calendar events.
+    async fn describe_recipe(&self, recipe: &Recipe) -> Result<String, MealPlanError> {
+        let steps = self.recipestep_repository.get_recipe_steps(&[recipe.id_recipe]).await.map_err(MealPlanError::DatabaseError)?;
+
+        let mut description = recipe.name.clone();
+
+        if !recipe.instructions.trim().is_empty() {
+            description.push_str("\n\n");
+            description.push_str(&recipe.instructions);
+        }
+
+        if !steps.is_empty() {
+            description.push('\n');
+        }
+
+        for (number, step) in steps.iter().enumerate() {
+            description.push_str(&format!("\n{}. {}", number + 1, step.text));
+        }
+
+        Ok(description)
+    }
+
+    /// Allergens of a recipe, looked up once per recipe in `known`; `None`
+    /// when some of its ingredients were never labelled.
+    async fn get_recipe_allergens(&self, id_recipe: i64, known: &mut HashMap<i64, Option<Vec<Allergen>>>) -> Result<Option<Vec<Allergen>>, MealPlanError> {
+        if let Some(recipe_allergens) = known.get(&id_recipe) {
+            return Ok(recipe_allergens.clone());
+        }
+
+        let stored = self.recipeingredients_repository.get_recipe_allergens(id_recipe).await.map_err(MealPlanError::DatabaseError)?;
+        let recipe_allergens = stored
+            .into_iter()
+            .collect::<Option<Vec<String>>>()
+            .map(|stored| names::merge_stored(&stored));
+
+        known.insert(id_recipe, recipe_allergens.clone());
+        Ok(recipe_allergens)
+    }
+
+    async fn get_catalogue(&self) -> Result<HashMap<i64, Ingredient>, MealPlanError> {
+        Ok(self.ingredient_repository.get_all_ingredients().await
+            .map_err(MealPlanError::DatabaseError)?
+            .into_iter()
+            .map(|ingredient| (ingredient.id_ingredient, ingredient))
+            .collect())
     }
 }
 
 #[async_trait]
 pub trait MealPlanServiceTrait: Send + Sync {
-    async fn add_mealplan(&self, meal_plan: MealPlanDTO) -> Result<(), MealPlanError>;
-    async fn get_all_mealplan(&self) -> Result<Vec<MealPlanDTO>, MealPlanError>;
-    async fn update_mealplan(&self, meal_plan: MealPlanDTO) -> Result<(), MealPlanError>;
-    async fn delete_mealplan(&self, id: i64) -> Result<(), MealPlanError>;
-    async fn check_recipe_existence(&self, recipes: &[RecipeMealsDTO]) -> Result<bool, MealPlanError>;
+    async fn add_mealplan(&self, meal_plan: MealPlanDTO, caller: &Caller) -> Result<(), MealPlanError>;
+    async fn get_all_mealplan(&self, caller: Option<&Caller>, filter: &MealPlanFilter) -> Result<Vec<MealPlanDTO>, MealPlanError>;
+    async fn update_mealplan(&self, meal_plan: MealPlanDTO, caller: &Caller) -> Result<(), MealPlanError>;
+    async fn delete_mealplan(&self, id: i64, caller: &Caller) -> Result<(), MealPlanError>;
+    async fn export_mealplan_ical(&self, id: i64, caller: Option<&Caller>, options: CalendarOptions) -> Result<String, MealPlanError>;
+    async fn get_meals_on(&self, date: NaiveDate, caller: &Caller) -> Result<PlannedDayDTO, MealPlanError>;
+    async fn get_shopping_list(&self, id: i64, caller: Option<&Caller>) -> Result<ShoppingListDTO, MealPlanError>;
+    async fn get_nutrition(&self, id: i64, caller: Option<&Caller>) -> Result<MealPlanNutritionDTO, MealPlanError>;
+    async fn check_diets(&self, id: i64, caller: Option<&Caller>) -> Result<MealPlanDietCheckDTO, MealPlanError>;
+    async fn check_recipe_existence(&self, recipes: &[RecipeMealsDTO], caller: &Caller) -> Result<bool, MealPlanError>;
     fn map_mealplan(&self, mealplan_dto: &MealPlanDTO) -> MealPlans;
 }
 
 #[async_trait]
 impl MealPlanServiceTrait for MealPlanService  {
-    async fn add_mealplan(&self, meal_plan_dto: MealPlanDTO) -> Result<(), MealPlanError> {        
-        if meal_plan_dto.validate().is_err() {
+    #[instrument(name = "MealPlanService::add_mealplan", skip_all)]
+    async fn add_mealplan(&self, meal_plan_dto: MealPlanDTO, caller: &Caller) -> Result<(), MealPlanError> {        
+        if meal_plan_dto.validate().is_err() || !self.are_meal_types_allowed(&meal_plan_dto.recipes) {
             return Err(MealPlanError::ValidationError);
         }
         
-        if self.mealplan_repository.does_name_exist(&meal_plan_dto.name).await.map_err(MealPlanError::DatabaseError)? {
+        if self.mealplan_repository.does_name_exist(&meal_plan_dto.name, Some(caller.id_user)).await.map_err(MealPlanError::DatabaseError)? {
             return Err(MealPlanError::DuplicateNameError);
         }
 
-        if !self.check_recipe_existence(&meal_plan_dto.recipes).await? {
+        let mut recipe_list = Self::map_recipe_meals(0, &meal_plan_dto)?;
+
+        if !self.check_recipe_existence(&meal_plan_dto.recipes, caller).await? {
             return Err(MealPlanError::NotFound);
         }
 
-        let mealplan = self.map_mealplan(&meal_plan_dto);
+        let mut mealplan = self.map_mealplan(&meal_plan_dto);
+        mealplan.id_owner = Some(caller.id_user);
 
-        let result = self.mealplan_repository.add_mealplan(&mealplan).await;
+        let mut transaction = self.db_pool.begin().await.map_err(MealPlanError::DatabaseError)?;
+        let result = async {
+            let id_mealplan = self.mealplan_repository.add_mealplan(&mut transaction, &mealplan).await?;
 
-        match result {
-            Ok(_) => {
-                let last_inserted_id: i64 = result.unwrap();
-                
-                let mut recipe_list = Vec::new();
-    
-                for recipe in &meal_plan_dto.recipes {
-                    let new_recipe = RecipeMeals {
-                        id_recipe_meal: 0,
-                        day: recipe.day.clone(),
-                        meal_type: recipe.meal_type.clone(),
-                        recipes_id: recipe.id_recipe,
-                        mealplan_id: last_inserted_id
-                    };
-                    
-                    recipe_list.push(new_recipe);
-                }
-    
-                match self.recipemeal_repository.add_recipe_meal(&recipe_list).await {
-                    Ok(_) => Ok(()),
-                    Err(err) => Err(MealPlanError::DatabaseError(err))
-                }
-            }
-            Err(err) => {
-                Err(MealPlanError::DatabaseError(err))
+            for recipe_meal in &mut recipe_list {
+                recipe_meal.mealplan_id = id_mealplan;
             }
+
+            self.recipemeal_repository.add_recipe_meal(&mut transaction, &recipe_list).await
         }
+        .await;
+
+        finish_transaction(transaction, result).await.map_err(Self::write_error)
     }
 
-    async fn get_all_mealplan(&self) -> Result<Vec<MealPlanDTO>, MealPlanError> {
-        let meal_plans = self.mealplan_repository.get_all_mealplans().await.map_err(MealPlanError::DatabaseError)?;
+    #[instrument(name = "MealPlanService::get_all_mealplan", skip_all)]
+    async fn get_all_mealplan(&self, caller: Option<&Caller>, filter: &MealPlanFilter) -> Result<Vec<MealPlanDTO>, MealPlanError> {
+        let meal_plans = self.mealplan_repository.get_visible_mealplans(caller.map(|caller| caller.id_user)).await.map_err(MealPlanError::DatabaseError)?;
         
         let mut meal_plan_dto: Vec<MealPlanDTO> = Vec::new();
+        let mut known_allergens: HashMap<i64, Option<Vec<Allergen>>> = HashMap::new();
 
-        for meal_plan in meal_plans {
+        'meal_plans: for meal_plan in meal_plans {
             let recipe_meals = self.recipemeal_repository.get_all_recipe_mealplan(meal_plan.id_mealplan).await.map_err(MealPlanError::DatabaseError)?;
+
+            if !filter.exclude_allergens.is_empty() {
+                for recipe_meal in &recipe_meals {
+                    let Some(recipe_allergens) = self.get_recipe_allergens(recipe_meal.recipes_id, &mut known_allergens).await? else {
+                        continue 'meal_plans;
+                    };
+
+                    if filter.exclude_allergens.iter().any(|allergen| recipe_allergens.contains(allergen)) {
+                        continue 'meal_plans;
+                    }
+                }
+            }
     
             meal_plan_dto.push(MealPlanDTO {
                 id_mealplan: meal_plan.id_mealplan,
                 name: meal_plan.name,
                 category: meal_plan.category,
                 recipes: recipe_meals
-                    .into_iter()
-                    .map(|recipe_meals| RecipeMealsDTO {
-                        id_recipe: recipe_meals.recipes_id,
-                        day: recipe_meals.day,
-                        meal_type: recipe_meals.meal_type
+                    .iter()
+                    .filter_map(|recipe_meal| {
+                        let (day, meal_type) = Self::map_slot(recipe_meal)?;
+                        Some(RecipeMealsDTO {
+                            id_recipe: recipe_meal.recipes_id,
+                            date: recipe_meal.date,
+                            day: Some(day),
+                            meal_type,
+                            servings: recipe_meal.servings.and_then(|servings| u32::try_from(servings).ok())
+                        })
                     })
                     .collect(),
+                visibility: Visibility::parse(&meal_plan.visibility),
+                id_owner: meal_plan.id_owner,
+                start_date: meal_plan.start_date,
+                length_days: u32::try_from(meal_plan.length_days).unwrap_or_default(),
+                diets: names::read_stored(&meal_plan.diets)
             });
         }
     
         Ok(meal_plan_dto)
     }
 
-    async fn delete_mealplan(&self, id: i64) -> Result<(), MealPlanError> {
-        self.recipemeal_repository.delete_recipemeal(id).await
-            .map_err(|err| MealPlanError::DatabaseError(err))?;
-    
-        self.mealplan_repository.delete_mealplan(id).await
-            .map_err(|err| MealPlanError::DatabaseError(err))?;
-    
-        Ok(())
+    #[instrument(name = "MealPlanService::delete_mealplan", skip_all, fields(id = id))]
+    async fn delete_mealplan(&self, id: i64, caller: &Caller) -> Result<(), MealPlanError> {
+        self.get_owned_mealplan(id, caller).await?;
+
+        let mut transaction = self.db_pool.begin().await.map_err(MealPlanError::DatabaseError)?;
+        let result = async {
+            self.recipemeal_repository.delete_recipemeal(&mut transaction, id).await?;
+            self.mealplan_repository.delete_mealplan(&mut transaction, id).await
+        }
+        .await;
+
+        finish_transaction(transaction, result).await.map_err(MealPlanError::DatabaseError)
+    }
+
+    #[instrument(name = "MealPlanService::update_mealplan", skip_all)]
+    async fn update_mealplan(&self, meal_plan_dto: MealPlanDTO, caller: &Caller) -> Result<(), MealPlanError> {
+        if meal_plan_dto.validate().is_err() || !self.are_meal_types_allowed(&meal_plan_dto.recipes) {
+            return Err(MealPlanError::ValidationError);
+        }
+
+        let current = self.get_owned_mealplan(meal_plan_dto.id_mealplan, caller).await?;
+
+        if self.mealplan_repository.does_name_exist_and_id(current.id_mealplan, &meal_plan_dto.name, current.id_owner).await.map_err(MealPlanError::DatabaseError)? {
+            return Err(MealPlanError::DuplicateNameError);
+        }
+
+        let recipe_list = Self::map_recipe_meals(current.id_mealplan, &meal_plan_dto)?;
+
+        if !self.check_recipe_existence(&meal_plan_dto.recipes, caller).await? {
+            return Err(MealPlanError::NotFound);
+        }
+
+        let mut mealplan = self.map_mealplan(&meal_plan_dto);
+        mealplan.id_mealplan = current.id_mealplan;
+        mealplan.id_owner = current.id_owner;
+
+        let mut transaction = self.db_pool.begin().await.map_err(MealPlanError::DatabaseError)?;
+        let result = async {
+            self.mealplan_repository.update_mealplan(&mut transaction, &mealplan).await?;
+            self.recipemeal_repository.delete_recipemeal(&mut transaction, mealplan.id_mealplan).await?;
+            self.recipemeal_repository.add_recipe_meal(&mut transaction, &recipe_list).await
+        }
+        .await;
+
+        finish_transaction(transaction, result).await.map_err(Self::write_error)
+    }
+
+    #[instrument(name = "MealPlanService::export_mealplan_ical", skip_all, fields(id = id))]
+    async fn export_mealplan_ical(&self, id: i64, caller: Option<&Caller>, options: CalendarOptions) -> Result<String, MealPlanError> {
+        let meal_plan = self.get_visible_mealplan(id, caller).await?;
+
+        let recipe_meals = self.recipemeal_repository.get_all_recipe_mealplan(id).await.map_err(MealPlanError::DatabaseError)?;
+
+        let mut recipes = HashMap::new();
+        let mut events = Vec::new();
+
+        for recipe_meal in recipe_meals {
+            let Some((day, meal_type)) = Self::map_slot(&recipe_meal) else {
+                continue;
+            };
+
+            let recipe = match recipes.entry(recipe_meal.recipes_id) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => {
+                    let recipe = match self.recipe_repository.get_by_id(recipe_meal.recipes_id).await.map_err(MealPlanError::DatabaseError)? {
+                        Some(recipe) => {
+                            let description = self.describe_recipe(&recipe).await?;
+                            Some((recipe, description))
+                        }
+                        None => None
+                    };
+
+                    entry.insert(recipe)
+                }
+            };
+
+            let Some((recipe, description)) = recipe else {
+                continue;
+            };
+
+            events.push(CalendarEvent {
+                uid: format!("mealplan-{}-slot-{}@rust-api-rest-rocket", meal_plan.id_mealplan, recipe_meal.id_recipe_meal),
+                summary: format!("{}: {}", meal_type.label(), recipe.name),
+                description: description.clone(),
+                date: recipe_meal.date,
+                day,
+                meal_type
+            });
+        }
+
+        Ok(ical::render(&meal_plan.name, &events, &options))
+    }
+
+    #[instrument(name = "MealPlanService::get_meals_on", skip_all)]
+    async fn get_meals_on(&self, date: NaiveDate, caller: &Caller) -> Result<PlannedDayDTO, MealPlanError> {
+        let planned_meals = self.recipemeal_repository.get_planned_meals_on(date, caller.id_user).await
+            .map_err(MealPlanError::DatabaseError)?;
+
+        let mut meals: Vec<PlannedMealDTO> = planned_meals
+            .into_iter()
+            .filter_map(|planned_meal| Some(PlannedMealDTO {
+                meal_type: MealType::parse(&planned_meal.meal_type)?,
+                id_mealplan: planned_meal.mealplan_id,
+                mealplan_name: planned_meal.mealplan_name,
+                id_recipe: planned_meal.recipes_id,
+                recipe_name: planned_meal.recipe_name
+            }))
+            .collect();
+
+        meals.sort_by_key(|meal| meal.meal_type.order());
+
+        Ok(PlannedDayDTO { date, day: Day::of(date), meals })
     }
 
-    async fn update_mealplan(&self, _meal_plan: MealPlanDTO) -> Result<(), MealPlanError> {
-        Err(MealPlanError::NotImplemented)
+    #[instrument(name = "MealPlanService::get_shopping_list", skip_all, fields(id = id))]
+    async fn get_shopping_list(&self, id: i64, caller: Option<&Caller>) -> Result<ShoppingListDTO, MealPlanError> {
+        let meal_plan = self.get_visible_mealplan(id, caller).await?;
+        let cooked_slots = self.get_cooked_slots(meal_plan.id_mealplan).await?;
+        let catalogue = self.get_catalogue().await?;
+
+        let mut totals: BTreeMap<(i64, String), f64> = BTreeMap::new();
+
+        for cooked_slot in &cooked_slots {
+            for ingredient in &cooked_slot.ingredients {
+                let amount = ingredient.amount * cooked_slot.factor;
+
+                let (amount, unit) = match Unit::parse(&ingredient.unit) {
+                    Some(unit) => match (unit.base(), unit.grams()) {
+                        (Some(base), Some(grams)) => (amount * grams, base.symbol().to_string()),
+                        _ => (amount, unit.symbol().to_string())
+                    },
+                    None => (amount, ingredient.unit.clone())
+                };
+
+                *totals.entry((ingredient.ingredients_id_ingredient, unit)).or_default() += amount;
+            }
+        }
+
+        let mut items: Vec<ShoppingItemDTO> = totals
+            .into_iter()
+            .filter_map(|((id_ingredient, unit), amount)| {
+                let (amount, unit) = match Unit::parse(&unit) {
+                    Some(Unit::Gram) if amount >= 1000.0 => (amount / 1000.0, Unit::Kilogram.symbol().to_string()),
+                    Some(Unit::Milliliter) if amount >= 1000.0 => (amount / 1000.0, Unit::Liter.symbol().to_string()),
+                    _ => (amount, unit)
+                };
+
+                Some(ShoppingItemDTO {
+                    id_ingredient,
+                    name: catalogue.get(&id_ingredient)?.name.clone(),
+                    amount: units::round(amount, &unit),
+                    unit
+                })
+            })
+            .collect();
+
+        items.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.unit.cmp(&b.unit)));
+
+        Ok(ShoppingListDTO { id_mealplan: meal_plan.id_mealplan, items })
     }
 
-    async fn check_recipe_existence(&self, recipes: &[RecipeMealsDTO]) -> Result<bool, MealPlanError> {
+    /// Totals of the ingredients whose unit can be converted to grams.
+    #[instrument(name = "MealPlanService::get_nutrition", skip_all, fields(id = id))]
+    async fn get_nutrition(&self, id: i64, caller: Option<&Caller>) -> Result<MealPlanNutritionDTO, MealPlanError> {
+        let meal_plan = self.get_visible_mealplan(id, caller).await?;
+        let cooked_slots = self.get_cooked_slots(meal_plan.id_mealplan).await?;
+        let catalogue = self.get_catalogue().await?;
+
+        let mut total = Nutrition::default();
+        let mut days: BTreeMap<(Option<NaiveDate>, i64), (Day, Nutrition)> = BTreeMap::new();
+
+        for cooked_slot in &cooked_slots {
+            let Some((day, _)) = Self::map_slot(&cooked_slot.slot) else {
+                continue;
+            };
+
+            let mut nutrition = Nutrition::default();
+
+            for ingredient in &cooked_slot.ingredients {
+                if let Some(catalogue_ingredient) = catalogue.get(&ingredient.ingredients_id_ingredient) {
+                    nutrition.add_ingredient(catalogue_ingredient, ingredient.amount * cooked_slot.factor, &ingredient.unit);
+                }
+            }
+
+            total += nutrition;
+
+            let (_, day_nutrition) = days
+                .entry((cooked_slot.slot.date, day.num_days_from_monday()))
+                .or_insert((day, Nutrition::default()));
+            *day_nutrition += nutrition;
+        }
+
+        Ok(MealPlanNutritionDTO {
+            id_mealplan: meal_plan.id_mealplan,
+            total: total.into(),
+            days: days
+                .into_iter()
+                .map(|((date, _), (day, nutrition))| DayNutritionDTO { date, day, nutrition: nutrition.into() })
+                .collect()
+        })
+    }
+
+    /// Checks every slot against the diets the meal plan declares.
+    #[instrument(name = "MealPlanService::check_diets", skip_all, fields(id = id))]
+    async fn check_diets(&self, id: i64, caller: Option<&Caller>) -> Result<MealPlanDietCheckDTO, MealPlanError> {
+        let meal_plan = self.get_visible_mealplan(id, caller).await?;
+        let declared = names::read_stored(&meal_plan.diets);
+
+        let mut slots = Vec::new();
+
+        if !declared.is_empty() {
+            let cooked_slots = self.get_cooked_slots(meal_plan.id_mealplan).await?;
+            let catalogue = self.get_catalogue().await?;
+
+            for cooked_slot in cooked_slots {
+                let Some((day, meal_type)) = Self::map_slot(&cooked_slot.slot) else {
+                    continue;
+                };
+
+                let profile = DietProfile::of(&cooked_slot.ingredients, &catalogue);
+
+                let breaks: Vec<BrokenDietDTO> = declared
+                    .iter()
+                    .map(|diet| BrokenDietDTO { diet: *diet, reasons: profile.breaks(*diet) })
+                    .filter(|broken| !broken.reasons.is_empty())
+                    .collect();
+
+                let unverified: Vec<UnverifiedDietDTO> = declared
+                    .iter()
+                    .filter(|diet| !breaks.iter().any(|broken| broken.diet == **diet))
+                    .map(|diet| UnverifiedDietDTO { diet: *diet, ingredients: profile.unverified(*diet).to_vec() })
+                    .filter(|unverified| !unverified.ingredients.is_empty())
+                    .collect();
+
+                if !breaks.is_empty() || !unverified.is_empty() {
+                    slots.push(SlotDietCheckDTO {
+                        id_recipe: cooked_slot.slot.recipes_id,
+                        date: cooked_slot.slot.date,
+                        day,
+                        meal_type,
+                        breaks,
+                        unverified
+                    });
+                }
+            }
+        }
+
+        Ok(MealPlanDietCheckDTO {
+            id_mealplan: meal_plan.id_mealplan,
+            diets: declared,
+            valid: slots.is_empty(),
+            slots
+        })
+    }
+
+    #[instrument(name = "MealPlanService::check_recipe_existence", skip_all)]
+    async fn check_recipe_existence(&self, recipes: &[RecipeMealsDTO], caller: &Caller) -> Result<bool, MealPlanError> {
         for recipe in recipes {
-            if !self.recipe_repository.recipe_exist(recipe.id_recipe).await.map_err(MealPlanError::DatabaseError)? {
+            let visible = self.recipe_repository.get_by_id(recipe.id_recipe).await.map_err(MealPlanError::DatabaseError)?
+                .is_some_and(|recipe| access::can_view(recipe.id_owner, Visibility::parse(&recipe.visibility), Some(caller)));
+
+            if !visible {
                 return Ok(false);
             }
         }
@@ -138,7 +610,12 @@ impl MealPlanServiceTrait for MealPlanService  {
         MealPlans {
             id_mealplan: 0,
             name: mealplan_dto.name.clone(),
-            category: mealplan_dto.category.clone()
+            category: mealplan_dto.category.clone(),
+            id_owner: None,
+            visibility: mealplan_dto.visibility.as_str().to_string(),
+            start_date: mealplan_dto.start_date,
+            length_days: mealplan_dto.length_days as i32,
+            diets: names::to_stored(&mealplan_dto.diets)
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,54 @@
+use rocket::async_trait;
+use sqlx::MySqlPool;
+
+use crate::data_access::repository::health_repository::HealthRepository;
+use crate::data_access::repository::metrics_repository::MetricsRepository;
+use crate::metrics::metrics;
+
+pub struct MetricsService {
+    pub health_repository: HealthRepository,
+    pub metrics_repository: MetricsRepository
+}
+
+impl MetricsService {
+    pub fn new(db_pool: MySqlPool) -> Self {
+        let health_repository = HealthRepository::new(db_pool.clone());
+        let metrics_repository = MetricsRepository::new(db_pool.clone());
+        Self { health_repository, metrics_repository }
+    }
+}
+
+#[async_trait]
+pub trait MetricsServiceTrait: Send + Sync {
+    async fn render_metrics(&self) -> String;
+}
+
+#[async_trait]
+impl MetricsServiceTrait for MetricsService {
+    /// Refreshes the pool and domain gauges and renders every metric. When the
+    /// database cannot be counted the domain gauges keep their last values.
+    async fn render_metrics(&self) -> String {
+        let metrics = metrics();
+
+        let (size, idle, max_connections) = self.health_repository.pool_stats();
+        let in_use = size.saturating_sub(idle);
+        // sqlx does not expose its wait queue: repository calls beyond the
+        // connections in use are the ones waiting for one.
+        let waiters = (metrics.db_queries_in_flight.get() - i64::from(in_use)).max(0);
+
+        metrics.db_pool_connections.with_label_values(&["size"]).set(i64::from(size));
+        metrics.db_pool_connections.with_label_values(&["idle"]).set(i64::from(idle));
+        metrics.db_pool_connections.with_label_values(&["in_use"]).set(i64::from(in_use));
+        metrics.db_pool_connections.with_label_values(&["max"]).set(i64::from(max_connections));
+        metrics.db_pool_connections.with_label_values(&["waiters"]).set(waiters);
+
+        if let Ok((ingredients, recipes, meal_plans, users)) = self.metrics_repository.count_items().await {
+            metrics.domain_items.with_label_values(&["ingredients"]).set(ingredients);
+            metrics.domain_items.with_label_values(&["recipes"]).set(recipes);
+            metrics.domain_items.with_label_values(&["meal_plans"]).set(meal_plans);
+            metrics.domain_items.with_label_values(&["users"]).set(users);
+        }
+
+        metrics.render()
+    }
+}
@@ -1,3 +1,9 @@
 pub mod ingredient_service;
 pub mod recipe_service;
-pub mod mealplan_service;
\ No newline at end of file
+pub mod mealplan_service;
+pub mod food_import_service;
+pub mod user_service;
+pub mod api_key_service;
+pub mod health_service;
+pub mod metrics_service;
+pub mod tag_service;
\ No newline at end of file
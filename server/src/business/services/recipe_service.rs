@@ -1,7 +1,26 @@
-use crate::{data_access::{repository::{recipe_repository::RecipeRepository, recipeingredients_repository::RecipeIngredientsRepository, ingredient_repository::IngredientRepository}, entities::{recipes::Recipe, recipe_ingredients::RecipeIngredients}}, business::dtos::recipe_dto::{RecipeDTO, IngredientAmount}};
+use std::collections::{HashMap, HashSet};
+
+use crate::{data_access::{repository::{finish_transaction, recipe_repository::RecipeRepository, recipeingredients_repository::{RecipeIngredientsRepository, MAX_COMPONENT_DEPTH}, ingredient_repository::IngredientRepository, tag_repository::TagRepository, recipestep_repository::RecipeStepRepository, recipecomponent_repository::RecipeComponentRepository}, entities::{recipes::Recipe, recipe_ingredients::RecipeIngredients, recipe_steps::RecipeStep, recipe_components::RecipeComponent, ingredients::Ingredient}}, business::dtos::recipe_dto::{RecipeDTO, RecipeFilter, TagMatch, RecipeStepDTO, RecipeComponentDTO, IngredientAmount, RecipeImportReport}};
+use crate::business::dtos::tag_dto;
+use crate::business::access::{self, Caller, Visibility};
+use crate::business::diets::DietProfile;
+use crate::business::formats::jsonld::{self, RecipeJsonLd};
+use crate::business::ingredient_parser;
+use crate::business::nutrition::Nutrition;
+use crate::business::units::{self, Unit};
 use rocket::async_trait;
-use sqlx::{Error, MySqlPool};
+use serde_json::Value;
+use sqlx::{Error, MySql, MySqlPool, Transaction};
 use validator::Validate;
+use tracing::instrument;
+
+const MAX_INGREDIENT_NAME_LENGTH: usize = 45;
+/// Imported ingredient names scoring at least this much reuse the catalogue ingredient.
+const IMPORT_MATCH_SCORE: f64 = 0.9;
+const IMPORTED_RECIPE_CATEGORY: &str = "Uncategorized";
+const TO_TASTE_UNIT: &str = "to taste";
+/// Unit of component recipes in exported ingredient lines.
+const COMPONENT_UNIT: &str = "servings";
 
 #[derive(Debug)]
 pub enum RecipeError {
@@ -9,13 +28,18 @@ pub enum RecipeError {
     DatabaseError(Error),
     DuplicateNameError,
     NotFound,
-    NotImplemented
+    Forbidden,
+    InUse
 }
 
 pub struct RecipeService {
+    pub db_pool: MySqlPool,
     pub ingredient_repository: IngredientRepository,
     pub recipe_repository: RecipeRepository,
-    pub recipeingredients_repository: RecipeIngredientsRepository
+    pub recipeingredients_repository: RecipeIngredientsRepository,
+    pub tag_repository: TagRepository,
+    pub recipestep_repository: RecipeStepRepository,
+    pub recipecomponent_repository: RecipeComponentRepository
 }
 
 impl RecipeService {
@@ -23,121 +47,640 @@ impl RecipeService {
         let ingredient_repository = IngredientRepository::new(db_pool.clone());
         let recipe_repository = RecipeRepository::new(db_pool.clone());
         let recipeingredients_repository = RecipeIngredientsRepository::new(db_pool.clone());
+        let tag_repository = TagRepository::new(db_pool.clone());
+        let recipestep_repository = RecipeStepRepository::new(db_pool.clone());
+        let recipecomponent_repository = RecipeComponentRepository::new(db_pool.clone());
         Self {
+            db_pool,
             ingredient_repository,
             recipe_repository,
-            recipeingredients_repository
+            recipeingredients_repository,
+            tag_repository,
+            recipestep_repository,
+            recipecomponent_repository
+        }
+    }
+
+    /// The DTOs of `recipes`, in the same order. Each kind of part is loaded
+    /// for all of them with a single query.
+    async fn to_recipe_dtos(&self, recipes: Vec<Recipe>) -> Result<Vec<RecipeDTO>, RecipeError> {
+        let id_recipes: Vec<i64> = recipes.iter().map(|recipe| recipe.id_recipe).collect();
+
+        let mut recipe_ingredients = group_by(
+            self.recipeingredients_repository.get_all_recipe_ingredients(&id_recipes).await.map_err(RecipeError::DatabaseError)?,
+            |recipe_ingredient| recipe_ingredient.recipes_id_recipe
+        );
+        let mut components = group_by(
+            self.recipecomponent_repository.get_recipe_components(&id_recipes).await.map_err(RecipeError::DatabaseError)?,
+            |component| component.recipes_id
+        );
+        let mut tags = group_by(
+            self.tag_repository.get_recipe_tags(&id_recipes).await.map_err(RecipeError::DatabaseError)?,
+            |tag| tag.recipes_id
+        );
+        let mut recipe_steps = group_by(
+            self.recipestep_repository.get_recipe_steps(&id_recipes).await.map_err(RecipeError::DatabaseError)?,
+            |step| step.recipes_id
+        );
+        let step_ingredients = group_by(
+            self.recipestep_repository.get_recipe_step_ingredients(&id_recipes).await.map_err(RecipeError::DatabaseError)?,
+            |step_ingredient| step_ingredient.steps_id
+        );
+        let expanded_ingredients = self.recipeingredients_repository.get_expanded_recipes_ingredients(&id_recipes).await.map_err(RecipeError::DatabaseError)?;
+
+        let mut id_ingredients: Vec<i64> = expanded_ingredients.iter().map(|recipe_ingredient| recipe_ingredient.ingredients_id_ingredient).collect();
+        id_ingredients.sort_unstable();
+        id_ingredients.dedup();
+
+        let used_ingredients: HashMap<i64, Ingredient> = self.ingredient_repository.get_by_ids(&id_ingredients).await
+            .map_err(RecipeError::DatabaseError)?
+            .into_iter()
+            .map(|ingredient| (ingredient.id_ingredient, ingredient))
+            .collect();
+        let mut expanded_ingredients = group_by(expanded_ingredients, |recipe_ingredient| recipe_ingredient.recipes_id_recipe);
+
+        Ok(recipes
+            .into_iter()
+            .map(|recipe| {
+                let diet_profile = DietProfile::of(&expanded_ingredients.remove(&recipe.id_recipe).unwrap_or_default(), &used_ingredients);
+
+                let steps: Vec<RecipeStepDTO> = recipe_steps
+                    .remove(&recipe.id_recipe)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|step| RecipeStepDTO {
+                        text: step.text,
+                        duration_minutes: step.duration_minutes.and_then(|minutes| u32::try_from(minutes).ok()),
+                        ingredients: step_ingredients
+                            .get(&step.id_recipe_step)
+                            .into_iter()
+                            .flatten()
+                            .map(|step_ingredient| step_ingredient.ingredients_id)
+                            .collect()
+                    })
+                    .collect();
+
+                let total_time_minutes = steps
+                    .iter()
+                    .filter_map(|step| step.duration_minutes)
+                    .reduce(|total, minutes| total + minutes);
+
+                RecipeDTO {
+                    id_recipe: recipe.id_recipe,
+                    name: recipe.name,
+                    category: recipe.category,
+                    instructions: recipe.instructions,
+                    steps,
+                    total_time_minutes,
+                    servings: u32::try_from(recipe.servings).unwrap_or(1),
+                    ingredients: recipe_ingredients
+                        .remove(&recipe.id_recipe)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|recipe_ingredient| IngredientAmount {
+                            id_ingredient: recipe_ingredient.ingredients_id_ingredient,
+                            amount: recipe_ingredient.amount,
+                            unit: recipe_ingredient.unit
+                        })
+                        .collect(),
+                    components: components
+                        .remove(&recipe.id_recipe)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|component| RecipeComponentDTO { id_recipe: component.components_id, servings: component.servings })
+                        .collect(),
+                    tags: tags.remove(&recipe.id_recipe).unwrap_or_default().into_iter().map(|tag| tag.name).collect(),
+                    diets: diet_profile.compatible_diets(),
+                    unverified_diets: diet_profile.unverified_diets(),
+                    allergens: diet_profile.allergens,
+                    unlabelled_allergens: diet_profile.unlabelled_allergens,
+                    visibility: Visibility::parse(&recipe.visibility),
+                    id_owner: recipe.id_owner
+                }
+            })
+            .collect())
+    }
+
+    fn map_recipe_components(id_recipe: i64, components: &[RecipeComponentDTO]) -> Vec<RecipeComponent> {
+        components
+            .iter()
+            .map(|component| RecipeComponent {
+                id_recipe_component: 0,
+                servings: component.servings,
+                recipes_id: id_recipe,
+                components_id: component.id_recipe
+            })
+            .collect()
+    }
+
+    /// Components must be recipes the caller can see and, for an existing recipe,
+    /// must not contain it, directly or through their own components. Nesting
+    /// deeper than `MAX_COMPONENT_DEPTH` is rejected, as deeper ingredients would
+    /// be left out of the recipe. Runs in `transaction`, which keeps the links
+    /// locked until the recipe is written.
+    async fn check_components(&self, transaction: &mut Transaction<'_, MySql>, id_recipe: Option<i64>, components: &[RecipeComponentDTO], caller: &Caller) -> Result<(), RecipeError> {
+        for component in components {
+            let visible = self.recipe_repository.get_by_id(component.id_recipe).await.map_err(RecipeError::DatabaseError)?
+                .is_some_and(|recipe| access::can_view(recipe.id_owner, Visibility::parse(&recipe.visibility), Some(caller)));
+
+            if !visible {
+                return Err(RecipeError::NotFound);
+            }
+        }
+
+        let mut graph: HashMap<i64, Vec<i64>> = HashMap::new();
+
+        for edge in self.recipecomponent_repository.get_all_recipe_components(transaction).await.map_err(RecipeError::DatabaseError)? {
+            if Some(edge.recipes_id) != id_recipe {
+                graph.entry(edge.recipes_id).or_default().push(edge.components_id);
+            }
+        }
+
+        let component_ids: Vec<i64> = components.iter().map(|component| component.id_recipe).collect();
+        let mut levels_above = 0;
+
+        if let Some(id_recipe) = id_recipe {
+            let mut pending = component_ids.clone();
+            let mut visited = HashSet::new();
+
+            while let Some(id) = pending.pop() {
+                if id == id_recipe {
+                    return Err(RecipeError::ValidationError);
+                }
+
+                if visited.insert(id) {
+                    pending.extend(graph.get(&id).into_iter().flatten());
+                }
+            }
+
+            let mut used_by: HashMap<i64, Vec<i64>> = HashMap::new();
+
+            for (id_parent, children) in &graph {
+                for id_child in children {
+                    used_by.entry(*id_child).or_default().push(*id_parent);
+                }
+            }
+
+            levels_above = Self::component_levels(&used_by, used_by.get(&id_recipe).map(Vec::as_slice).unwrap_or_default());
+        }
+
+        if levels_above + Self::component_levels(&graph, &component_ids) > MAX_COMPONENT_DEPTH {
+            return Err(RecipeError::ValidationError);
+        }
+
+        Ok(())
+    }
+
+    /// A recipe used in a meal plan or by another recipe cannot be deleted.
+    /// Runs in `transaction`, which keeps new uses out until the recipe is gone.
+    async fn check_not_in_use(&self, transaction: &mut Transaction<'_, MySql>, id_recipe: i64) -> Result<(), RecipeError> {
+        if self.recipe_repository.is_recipe_in_meal_plan(transaction, id_recipe).await.map_err(RecipeError::DatabaseError)? {
+            return Err(RecipeError::InUse);
+        }
+
+        if self.recipecomponent_repository.is_recipe_a_component(transaction, id_recipe).await.map_err(RecipeError::DatabaseError)? {
+            return Err(RecipeError::InUse);
+        }
+
+        Ok(())
+    }
+
+    /// How many levels of `links` hang from `roots`, counting `roots` as the
+    /// first one. Stops past `MAX_COMPONENT_DEPTH`.
+    fn component_levels(links: &HashMap<i64, Vec<i64>>, roots: &[i64]) -> i32 {
+        let mut level: HashSet<i64> = roots.iter().copied().collect();
+        let mut levels = 0;
+
+        while !level.is_empty() && levels <= MAX_COMPONENT_DEPTH {
+            levels += 1;
+            level = level.iter().flat_map(|id| links.get(id).into_iter().flatten().copied()).collect();
+        }
+
+        levels
+    }
+
+    /// Steps may only refer to ingredients of their recipe.
+    fn map_recipe_steps(id_recipe: i64, recipe_dto: &RecipeDTO) -> Result<Vec<(RecipeStep, Vec<i64>)>, RecipeError> {
+        recipe_dto.steps
+            .iter()
+            .enumerate()
+            .map(|(position, step)| {
+                let uses_other_ingredients = step.ingredients
+                    .iter()
+                    .any(|id_ingredient| !recipe_dto.ingredients.iter().any(|ingredient| ingredient.id_ingredient == *id_ingredient));
+
+                if uses_other_ingredients {
+                    return Err(RecipeError::ValidationError);
+                }
+
+                let mut ingredients = step.ingredients.clone();
+                ingredients.sort_unstable();
+                ingredients.dedup();
+
+                let recipe_step = RecipeStep {
+                    id_recipe_step: 0,
+                    position: position as i32 + 1,
+                    text: step.text.clone(),
+                    duration_minutes: step.duration_minutes.map(|minutes| minutes as i32),
+                    recipes_id: id_recipe
+                };
+
+                Ok((recipe_step, ingredients))
+            })
+            .collect()
+    }
+
+    /// The tags stored for a recipe: its own ones and its category, normalised
+    /// and without repetitions.
+    fn map_recipe_tags(recipe_dto: &RecipeDTO) -> Vec<String> {
+        let mut tags: Vec<String> = Vec::new();
+
+        for tag in std::iter::once(&recipe_dto.category).chain(&recipe_dto.tags) {
+            let tag = tag_dto::normalise_name(tag);
+
+            if !tag.is_empty() && !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+
+        tags
+    }
+
+    fn map_recipe_ingredients(id_recipe: i64, ingredients: &[IngredientAmount]) -> Vec<RecipeIngredients> {
+        ingredients
+            .iter()
+            .map(|ingredient_amount| RecipeIngredients {
+                id_recipe_ingredients: 0,
+                amount: ingredient_amount.amount,
+                unit: ingredient_amount.unit.clone(),
+                ingredients_id_ingredient: ingredient_amount.id_ingredient,
+                recipes_id_recipe: id_recipe
+            })
+            .collect()
+    }
+
+    /// The ingredients and components a recipe refers to must exist and be
+    /// usable. Runs in `transaction`, so that ingredients created in it count.
+    async fn check_references(&self, transaction: &mut Transaction<'_, MySql>, id_recipe: Option<i64>, recipe_dto: &RecipeDTO, caller: &Caller) -> Result<(), RecipeError> {
+        for ingredient in &recipe_dto.ingredients {
+            if !self.ingredient_repository.ingredient_exist(transaction, ingredient.id_ingredient).await.map_err(RecipeError::DatabaseError)? {
+                return Err(RecipeError::NotFound);
+            }
+        }
+
+        self.check_components(transaction, id_recipe, &recipe_dto.components, caller).await
+    }
+
+    /// Creates a recipe owned by the caller in `transaction`, with the same
+    /// checks for every way recipes are created. Returns its id.
+    async fn create_recipe(&self, transaction: &mut Transaction<'_, MySql>, recipe_dto: &RecipeDTO, caller: &Caller) -> Result<i64, RecipeError> {
+        if recipe_dto.validate().is_err() || !recipe_dto.has_instructions() {
+            return Err(RecipeError::ValidationError);
+        }
+
+        if self.recipe_repository.does_name_exist(&recipe_dto.name, Some(caller.id_user)).await.map_err(RecipeError::DatabaseError)? {
+            return Err(RecipeError::DuplicateNameError);
+        }
+
+        let mut recipe_steps = Self::map_recipe_steps(0, recipe_dto)?;
+
+        self.check_references(transaction, None, recipe_dto, caller).await?;
+
+        let mut recipe = self.map_recipe(recipe_dto);
+        recipe.id_owner = Some(caller.id_user);
+
+        let id_recipe = self.recipe_repository.add_recipe(transaction, &recipe).await.map_err(Self::write_error)?;
+        self.add_recipe_parts(transaction, id_recipe, recipe_dto, &mut recipe_steps).await.map_err(RecipeError::DatabaseError)?;
+
+        Ok(id_recipe)
+    }
+
+    /// Commits `transaction` if `result` is ok and rolls it back otherwise.
+    async fn finish_recipe_transaction<T>(transaction: Transaction<'_, MySql>, result: Result<T, RecipeError>) -> Result<T, RecipeError> {
+        match result {
+            Ok(value) => {
+                transaction.commit().await.map_err(RecipeError::DatabaseError)?;
+                Ok(value)
+            }
+            Err(err) => {
+                transaction.rollback().await.map_err(RecipeError::DatabaseError)?;
+                Err(err)
+            }
         }
     }
+
+    /// A concurrent write of the same name hits the owner's unique index.
+    fn write_error(err: Error) -> RecipeError {
+        match err {
+            Error::Database(ref db_err) if db_err.is_unique_violation() => RecipeError::DuplicateNameError,
+            err => RecipeError::DatabaseError(err)
+        }
+    }
+
+    /// Loads a recipe the caller is allowed to change.
+    async fn get_owned_recipe(&self, id: i64, caller: &Caller) -> Result<Recipe, RecipeError> {
+        let recipe = self.recipe_repository.get_by_id(id).await.map_err(RecipeError::DatabaseError)?
+            .ok_or(RecipeError::NotFound)?;
+
+        if !access::can_modify(recipe.id_owner, caller) {
+            return Err(RecipeError::Forbidden);
+        }
+
+        Ok(recipe)
+    }
+
+    /// Adds the ingredients, steps, components and tags of recipe `id_recipe`.
+    async fn add_recipe_parts(&self, transaction: &mut Transaction<'_, MySql>, id_recipe: i64, recipe_dto: &RecipeDTO, recipe_steps: &mut [(RecipeStep, Vec<i64>)]) -> Result<(), Error> {
+        self.recipeingredients_repository.add_ingredient_recipe(transaction, &Self::map_recipe_ingredients(id_recipe, &recipe_dto.ingredients)).await?;
+
+        for (recipe_step, _) in recipe_steps.iter_mut() {
+            recipe_step.recipes_id = id_recipe;
+        }
+
+        self.recipestep_repository.add_recipe_steps(transaction, recipe_steps).await?;
+        self.recipecomponent_repository.add_recipe_components(transaction, &Self::map_recipe_components(id_recipe, &recipe_dto.components)).await?;
+        self.tag_repository.set_recipe_tags(transaction, id_recipe, &Self::map_recipe_tags(recipe_dto)).await
+    }
+
+    async fn delete_recipe_parts(&self, transaction: &mut Transaction<'_, MySql>, id_recipe: i64) -> Result<(), Error> {
+        self.recipecomponent_repository.delete_recipe_components(transaction, id_recipe).await?;
+        self.recipestep_repository.delete_recipe_steps(transaction, id_recipe).await?;
+        self.recipeingredients_repository.delete_recipe_ingredients(transaction, id_recipe).await?;
+        self.tag_repository.delete_recipe_tags(transaction, id_recipe).await
+    }
+}
+
+/// `items` by the key `key_of` gives each, keeping their order.
+fn group_by<T>(items: Vec<T>, key_of: impl Fn(&T) -> i64) -> HashMap<i64, Vec<T>> {
+    let mut groups: HashMap<i64, Vec<T>> = HashMap::new();
+
+    for item in items {
+        groups.entry(key_of(&item)).or_default().push(item);
+    }
+
+    groups
 }
 
 #[async_trait]
 pub trait RecipeServiceTrait: Send + Sync {
-    async fn add_recipe(&self, recipe: RecipeDTO) -> Result<(), RecipeError>;
-    async fn get_all_recipe(&self) -> Result<Vec<RecipeDTO>, RecipeError>;
-    async fn update_recipe(&self, recipe: RecipeDTO) -> Result<(), RecipeError>;
-    async fn delete_recipe(&self, id: i64) -> Result<(), RecipeError>;
-    async fn check_ingredients_existence(&self, ingredients: &[IngredientAmount]) -> Result<bool, RecipeError>;
+    async fn add_recipe(&self, recipe: RecipeDTO, caller: &Caller) -> Result<(), RecipeError>;
+    async fn get_all_recipe(&self, caller: Option<&Caller>, filter: &RecipeFilter) -> Result<Vec<RecipeDTO>, RecipeError>;
+    async fn get_recipe(&self, id: i64, caller: Option<&Caller>) -> Result<RecipeDTO, RecipeError>;
+    async fn get_scaled_recipe(&self, id: i64, servings: u32, caller: Option<&Caller>) -> Result<RecipeDTO, RecipeError>;
+    async fn update_recipe(&self, recipe: RecipeDTO, caller: &Caller) -> Result<(), RecipeError>;
+    async fn delete_recipe(&self, id: i64, caller: &Caller) -> Result<(), RecipeError>;
+    async fn export_recipe_jsonld(&self, id: i64, caller: Option<&Caller>) -> Result<RecipeJsonLd, RecipeError>;
+    async fn import_recipe_jsonld(&self, document: Value, caller: &Caller) -> Result<RecipeImportReport, RecipeError>;
     fn map_recipe(&self, recipe_dto: &RecipeDTO) -> Recipe;
 }
 
 #[async_trait]
 impl RecipeServiceTrait for RecipeService  {
-    async fn add_recipe(&self, recipe_dto: RecipeDTO) -> Result<(), RecipeError> {
-        if recipe_dto.validate().is_err() {
+    #[instrument(name = "RecipeService::add_recipe", skip_all)]
+    async fn add_recipe(&self, recipe_dto: RecipeDTO, caller: &Caller) -> Result<(), RecipeError> {
+        let mut transaction = self.db_pool.begin().await.map_err(RecipeError::DatabaseError)?;
+        let result = self.create_recipe(&mut transaction, &recipe_dto, caller).await;
+
+        Self::finish_recipe_transaction(transaction, result).await.map(|_| ())
+    }
+
+    #[instrument(name = "RecipeService::get_all_recipe", skip_all)]
+    async fn get_all_recipe(&self, caller: Option<&Caller>, filter: &RecipeFilter) -> Result<Vec<RecipeDTO>, RecipeError> {
+        let mut tags = filter.tags.clone();
+        tags.sort();
+        tags.dedup();
+
+        let recipes = self.recipe_repository.get_visible_recipes(caller.map(|caller| caller.id_user), &tags, filter.tag_match == TagMatch::All).await
+            .map_err(RecipeError::DatabaseError)?;
+
+        Ok(self.to_recipe_dtos(recipes).await?
+            .into_iter()
+            .filter(|recipe_dto| filter.matches(recipe_dto))
+            .collect())
+    }
+
+    #[instrument(name = "RecipeService::get_recipe", skip_all, fields(id = id))]
+    async fn get_recipe(&self, id: i64, caller: Option<&Caller>) -> Result<RecipeDTO, RecipeError> {
+        match self.recipe_repository.get_by_id(id).await.map_err(RecipeError::DatabaseError)? {
+            Some(recipe) if access::can_view(recipe.id_owner, Visibility::parse(&recipe.visibility), caller) => {
+                self.to_recipe_dtos(vec![recipe]).await?.pop().ok_or(RecipeError::NotFound)
+            }
+            _ => Err(RecipeError::NotFound)
+        }
+    }
+
+    /// The recipe with its ingredient amounts for `servings` people.
+    #[instrument(name = "RecipeService::get_scaled_recipe", skip_all, fields(id = id))]
+    async fn get_scaled_recipe(&self, id: i64, servings: u32, caller: Option<&Caller>) -> Result<RecipeDTO, RecipeError> {
+        let mut recipe = self.get_recipe(id, caller).await?;
+        let factor = f64::from(servings) / f64::from(recipe.servings);
+
+        recipe.servings = servings;
+
+        if recipe.validate().is_err() {
             return Err(RecipeError::ValidationError);
         }
-        
-        if self.recipe_repository.does_name_exist(&recipe_dto.name).await.map_err(RecipeError::DatabaseError)? {
-            return Err(RecipeError::DuplicateNameError);
+
+        for ingredient in &mut recipe.ingredients {
+            ingredient.amount = units::scale(ingredient.amount, &ingredient.unit, factor);
         }
 
-        if !self.check_ingredients_existence(&recipe_dto.ingredients).await? {
-            return Err(RecipeError::NotFound);
+        for component in &mut recipe.components {
+            component.servings = (component.servings * factor * 100.0).round() / 100.0;
         }
 
-        let recipe = self.map_recipe(&recipe_dto);
-                
-        let result = self.recipe_repository.add_recipe(&recipe).await;
+        Ok(recipe)
+    }
 
-        match result {
-            Ok(_) => {
-                let last_inserted_id: i64 = result.unwrap();
-                
-                let mut recipe_ingredients_list = Vec::new();
-    
-                for ingredient_amount in &recipe_dto.ingredients {
-                    let new_recipe_ingredient = RecipeIngredients {
-                        id_recipe_ingredients: 0,
-                        amount: ingredient_amount.amount,
-                        unit: ingredient_amount.unit.clone(),
-                        ingredients_id_ingredient: ingredient_amount.id_ingredient,
-                        recipes_id_recipe: last_inserted_id,
-                    };
-                    
-                    recipe_ingredients_list.push(new_recipe_ingredient);
-                }
-    
-                match self.recipeingredients_repository.add_ingredient_recipe(&recipe_ingredients_list).await {
-                    Ok(_) => Ok(()),
-                    Err(err) => Err(RecipeError::DatabaseError(err))
-                }
-            }
-            Err(err) => {
-                Err(RecipeError::DatabaseError(err))
-            }
+    #[instrument(name = "RecipeService::update_recipe", skip_all)]
+    async fn update_recipe(&self, recipe_dto: RecipeDTO, caller: &Caller) -> Result<(), RecipeError> {
+        if recipe_dto.validate().is_err() || !recipe_dto.has_instructions() {
+            return Err(RecipeError::ValidationError);
         }
-    }
 
-    async fn get_all_recipe(&self) -> Result<Vec<RecipeDTO>, RecipeError> {
-        let recipes = self.recipe_repository.get_all_recipes().await.map_err(RecipeError::DatabaseError)?;
-        
-        let mut recipes_dto: Vec<RecipeDTO> = Vec::new();
+        let current = self.get_owned_recipe(recipe_dto.id_recipe, caller).await?;
 
-        for recipe in recipes {
-            let recipe_ingredients = self.recipeingredients_repository.get_all_recipe_ingredients(recipe.id_recipe).await.map_err(RecipeError::DatabaseError)?;
-    
-            recipes_dto.push(RecipeDTO {
-                id_recipe: recipe.id_recipe,
-                name: recipe.name,
-                category: recipe.category,
-                instructions: recipe.instructions,
-                ingredients: recipe_ingredients
-                    .into_iter()
-                    .map(|recipe_ingredient| IngredientAmount {
-                        id_ingredient: recipe_ingredient.ingredients_id_ingredient,
-                        amount: recipe_ingredient.amount,
-                        unit: recipe_ingredient.unit
-                    })
-                    .collect(),
-            });
+        if self.recipe_repository.does_name_exist_and_id(current.id_recipe, &recipe_dto.name, current.id_owner).await.map_err(RecipeError::DatabaseError)? {
+            return Err(RecipeError::DuplicateNameError);
+        }
+
+        let mut recipe_steps = Self::map_recipe_steps(current.id_recipe, &recipe_dto)?;
+
+        let mut recipe = self.map_recipe(&recipe_dto);
+        recipe.id_recipe = current.id_recipe;
+        recipe.id_owner = current.id_owner;
+
+        let mut transaction = self.db_pool.begin().await.map_err(RecipeError::DatabaseError)?;
+
+        if let Err(err) = self.check_references(&mut transaction, Some(recipe.id_recipe), &recipe_dto, caller).await {
+            transaction.rollback().await.map_err(RecipeError::DatabaseError)?;
+            return Err(err);
+        }
+
+        let result = async {
+            self.recipe_repository.update_recipe(&mut transaction, &recipe).await?;
+            self.delete_recipe_parts(&mut transaction, recipe.id_recipe).await?;
+            self.add_recipe_parts(&mut transaction, recipe.id_recipe, &recipe_dto, &mut recipe_steps).await
         }
-    
-        Ok(recipes_dto)
+        .await;
+
+        finish_transaction(transaction, result).await.map_err(Self::write_error)
     }
 
-    async fn update_recipe(&self, _recipe: RecipeDTO) -> Result<(), RecipeError> {
-        Err(RecipeError::NotImplemented)
+    #[instrument(name = "RecipeService::delete_recipe", skip_all, fields(id = id))]
+    async fn delete_recipe(&self, id: i64, caller: &Caller) -> Result<(), RecipeError> {
+        self.get_owned_recipe(id, caller).await?;
+
+        let mut transaction = self.db_pool.begin().await.map_err(RecipeError::DatabaseError)?;
+
+        if let Err(err) = self.check_not_in_use(&mut transaction, id).await {
+            transaction.rollback().await.map_err(RecipeError::DatabaseError)?;
+            return Err(err);
+        }
+
+        let result = async {
+            self.delete_recipe_parts(&mut transaction, id).await?;
+            self.recipe_repository.delete_recipe(&mut transaction, id).await
+        }
+        .await;
+
+        finish_transaction(transaction, result).await.map_err(RecipeError::DatabaseError)
     }
 
-    async fn delete_recipe(&self, id: i64) -> Result<(), RecipeError> {
-        if self.recipe_repository.is_recipe_in_meal_plan(id).await.map_err(RecipeError::DatabaseError)? {
-            return Err(RecipeError::DuplicateNameError);
+    #[instrument(name = "RecipeService::export_recipe_jsonld", skip_all, fields(id = id))]
+    async fn export_recipe_jsonld(&self, id: i64, caller: Option<&Caller>) -> Result<RecipeJsonLd, RecipeError> {
+        let recipe = self.get_recipe(id, caller).await?;
+
+        let catalogue: HashMap<i64, Ingredient> = self.ingredient_repository.get_all_ingredients().await
+            .map_err(RecipeError::DatabaseError)?
+            .into_iter()
+            .map(|ingredient| (ingredient.id_ingredient, ingredient))
+            .collect();
+
+        let mut ingredient_lines: Vec<String> = recipe.ingredients
+            .iter()
+            .filter_map(|ingredient_amount| {
+                let ingredient = catalogue.get(&ingredient_amount.id_ingredient)?;
+                Some(jsonld::ingredient_line(ingredient_amount.amount, &ingredient_amount.unit, &ingredient.name))
+            })
+            .collect();
+
+        for component in &recipe.components {
+            if let Some(component_recipe) = self.recipe_repository.get_by_id(component.id_recipe).await.map_err(RecipeError::DatabaseError)? {
+                ingredient_lines.push(jsonld::ingredient_line(component.servings, COMPONENT_UNIT, &component_recipe.name));
+            }
         }
 
-        match self.recipe_repository.delete_recipe(id).await {
-            Ok(_) => Ok(()),
-            Err(err) => Err(RecipeError::DatabaseError(err))
+        let mut nutrition = Nutrition::default();
+
+        for recipe_ingredient in self.recipeingredients_repository.get_expanded_recipe_ingredients(recipe.id_recipe).await.map_err(RecipeError::DatabaseError)? {
+            if let Some(ingredient) = catalogue.get(&recipe_ingredient.ingredients_id_ingredient) {
+                nutrition.add_ingredient(ingredient, recipe_ingredient.amount, &recipe_ingredient.unit);
+            }
         }
+
+        Ok(RecipeJsonLd::new(&recipe, ingredient_lines, &nutrition))
     }
 
-    async fn check_ingredients_existence(&self, ingredients: &[IngredientAmount]) -> Result<bool, RecipeError> {
-        for ingredient in ingredients {
-            if !self.ingredient_repository.ingredient_exist(ingredient.id_ingredient).await.map_err(RecipeError::DatabaseError)? {
-                return Ok(false);
+    #[instrument(name = "RecipeService::import_recipe_jsonld", skip_all)]
+    async fn import_recipe_jsonld(&self, document: Value, caller: &Caller) -> Result<RecipeImportReport, RecipeError> {
+        let imported = jsonld::parse_recipe(&document).ok_or(RecipeError::ValidationError)?;
+
+        let mut recipe_dto = RecipeDTO {
+            id_recipe: 0,
+            name: imported.name,
+            category: imported.category.unwrap_or_else(|| IMPORTED_RECIPE_CATEGORY.to_string()),
+            instructions: imported.instructions,
+            steps: imported.steps
+                .into_iter()
+                .map(|text| RecipeStepDTO { text, duration_minutes: None, ingredients: vec![] })
+                .collect(),
+            total_time_minutes: None,
+            servings: imported.servings.unwrap_or(1),
+            ingredients: vec![],
+            components: vec![],
+            tags: vec![],
+            allergens: vec![],
+            unlabelled_allergens: vec![],
+            diets: vec![],
+            unverified_diets: vec![],
+            visibility: Visibility::default(),
+            id_owner: None
+        };
+
+        let mut catalogue = self.ingredient_repository.get_all_ingredients().await.map_err(RecipeError::DatabaseError)?;
+        let mut created_ingredients = Vec::new();
+        // Ingredients that are created along with the recipe, and which of them
+        // each ingredient line uses.
+        let mut new_ingredients: Vec<Ingredient> = Vec::new();
+        let mut lines_using_new: Vec<(usize, usize)> = Vec::new();
+
+        for line in imported.ingredients {
+            let parsed = ingredient_parser::parse_line(&line);
+
+            let name: String = parsed.name.chars().take(MAX_INGREDIENT_NAME_LENGTH).collect();
+            let name = name.trim().to_string();
+
+            if name.is_empty() {
+                continue;
             }
+
+            let best_match = ingredient_parser::rank_matches(&name, &catalogue, |ingredient| &ingredient.name, 1)
+                .into_iter()
+                .find(|(_, score)| *score >= IMPORT_MATCH_SCORE)
+                .map(|(ingredient, _)| ingredient);
+
+            let id_ingredient = match best_match {
+                Some(ingredient) if ingredient.id_ingredient != 0 => ingredient.id_ingredient,
+                Some(ingredient) => {
+                    let position = new_ingredients.iter().position(|new_ingredient| new_ingredient.name == ingredient.name).unwrap_or_default();
+                    lines_using_new.push((recipe_dto.ingredients.len(), position));
+                    0
+                }
+                None => {
+                    let new_ingredient = Ingredient {
+                        id_ingredient: 0,
+                        name: name.clone(),
+                        proteins: 0.0,
+                        carbs: 0.0,
+                        fats: 0.0,
+                        source_id: None,
+                        allergens: None,
+                        diet_attributes: None
+                    };
+
+                    lines_using_new.push((recipe_dto.ingredients.len(), new_ingredients.len()));
+                    catalogue.push(new_ingredient.clone());
+                    new_ingredients.push(new_ingredient);
+                    created_ingredients.push(name);
+                    0
+                }
+            };
+
+            let (amount, unit) = match (parsed.amount, parsed.unit) {
+                (Some(amount), Some(unit)) => (amount, unit.symbol().to_string()),
+                (Some(amount), None) => (amount, Unit::Piece.symbol().to_string()),
+                (None, _) => (0.0, TO_TASTE_UNIT.to_string())
+            };
+
+            recipe_dto.ingredients.push(IngredientAmount { id_ingredient, amount, unit });
         }
-        Ok(true)
+
+        let mut transaction = self.db_pool.begin().await.map_err(RecipeError::DatabaseError)?;
+        let result = async {
+            let ids = self.ingredient_repository.add_ingredients(&mut transaction, &new_ingredients).await.map_err(RecipeError::DatabaseError)?;
+
+            for (line, position) in &lines_using_new {
+                recipe_dto.ingredients[*line].id_ingredient = ids[*position];
+            }
+
+            self.create_recipe(&mut transaction, &recipe_dto, caller).await
+        }
+        .await;
+
+        Self::finish_recipe_transaction(transaction, result).await?;
+
+        Ok(RecipeImportReport { name: recipe_dto.name, created_ingredients })
     }
 
     fn map_recipe(&self, recipe_dto: &RecipeDTO) -> Recipe {
@@ -145,7 +688,10 @@ impl RecipeServiceTrait for RecipeService  {
             id_recipe: 0,
             name: recipe_dto.name.clone(),
             category: recipe_dto.category.clone(),
-            instructions: recipe_dto.instructions.clone()
+            instructions: recipe_dto.instructions.clone(),
+            servings: recipe_dto.servings as i32,
+            id_owner: None,
+            visibility: recipe_dto.visibility.as_str().to_string()
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,106 @@
+use rocket::async_trait;
+use crate::business::dtos::tag_dto::{self, TagDTO};
+use crate::data_access::entities::tags::Tag;
+use crate::data_access::repository::tag_repository::TagRepository;
+use validator::Validate;
+use sqlx::{MySqlPool, Error};
+use tracing::instrument;
+
+#[derive(Debug)]
+pub enum TagError {
+    ValidationError,
+    DatabaseError(Error),
+    DuplicateNameError,
+    NotFound,
+    InUse
+}
+
+pub struct TagService {
+    pub tag_repository: TagRepository,
+}
+
+impl TagService {
+    pub fn new(db_pool: MySqlPool) -> Self {
+        let tag_repository = TagRepository::new(db_pool.clone());
+        Self { tag_repository }
+    }
+}
+
+#[async_trait]
+pub trait TagServiceTrait: Send + Sync {
+    async fn add_tag(&self, tag: TagDTO) -> Result<(), TagError>;
+    async fn get_all_tags(&self) -> Result<Vec<TagDTO>, TagError>;
+    async fn update_tag(&self, tag: TagDTO) -> Result<(), TagError>;
+    async fn delete_tag(&self, id: i64) -> Result<(), TagError>;
+}
+
+#[async_trait]
+impl TagServiceTrait for TagService {
+    #[instrument(name = "TagService::add_tag", skip_all)]
+    async fn add_tag(&self, tag_dto: TagDTO) -> Result<(), TagError> {
+        if tag_dto.validate().is_err() {
+            return Err(TagError::ValidationError);
+        }
+
+        let name = tag_dto::normalise_name(&tag_dto.name);
+
+        if name.is_empty() {
+            return Err(TagError::ValidationError);
+        }
+
+        if self.tag_repository.does_name_exist(&name).await.map_err(TagError::DatabaseError)? {
+            return Err(TagError::DuplicateNameError);
+        }
+
+        self.tag_repository.add_tag(&Tag { id_tag: 0, name }).await
+            .map(|_| ())
+            .map_err(TagError::DatabaseError)
+    }
+
+    #[instrument(name = "TagService::get_all_tags", skip_all)]
+    async fn get_all_tags(&self) -> Result<Vec<TagDTO>, TagError> {
+        let tags = self.tag_repository.get_all_tags().await.map_err(TagError::DatabaseError)?;
+
+        Ok(tags.into_iter().map(|tag| tag.into()).collect())
+    }
+
+    /// Renames a tag on every recipe that carries it, and in the category of
+    /// the recipes filed under it.
+    #[instrument(name = "TagService::update_tag", skip_all)]
+    async fn update_tag(&self, tag_dto: TagDTO) -> Result<(), TagError> {
+        if tag_dto.validate().is_err() {
+            return Err(TagError::ValidationError);
+        }
+
+        if !self.tag_repository.tag_exist(tag_dto.id_tag).await.map_err(TagError::DatabaseError)? {
+            return Err(TagError::NotFound);
+        }
+
+        let name = tag_dto::normalise_name(&tag_dto.name);
+
+        if name.is_empty() {
+            return Err(TagError::ValidationError);
+        }
+
+        if self.tag_repository.does_name_exist_and_id(tag_dto.id_tag, &name).await.map_err(TagError::DatabaseError)? {
+            return Err(TagError::DuplicateNameError);
+        }
+
+        self.tag_repository.update_tag(&Tag { id_tag: tag_dto.id_tag, name }).await
+            .map_err(TagError::DatabaseError)
+    }
+
+    /// Deletes a tag, unless it is the category of some recipe.
+    #[instrument(name = "TagService::delete_tag", skip_all, fields(id = id))]
+    async fn delete_tag(&self, id: i64) -> Result<(), TagError> {
+        if !self.tag_repository.tag_exist(id).await.map_err(TagError::DatabaseError)? {
+            return Err(TagError::NotFound);
+        }
+
+        if self.tag_repository.is_tag_a_category(id).await.map_err(TagError::DatabaseError)? {
+            return Err(TagError::InUse);
+        }
+
+        self.tag_repository.delete_tag(id).await.map_err(TagError::DatabaseError)
+    }
+}
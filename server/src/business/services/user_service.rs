@@ -0,0 +1,213 @@
+use std::sync::OnceLock;
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation};
+use rocket::async_trait;
+use serde::{Deserialize, Serialize};
+use sqlx::{Error, MySqlPool};
+use validator::Validate;
+
+use crate::business::access::Role;
+use crate::business::dtos::user_dto::{TokenDTO, UserCredentialsDTO, UserDTO};
+use crate::data_access::entities::users::User;
+use crate::data_access::repository::user_repository::UserRepository;
+
+const TOKEN_LIFETIME_SECONDS: i64 = 24 * 60 * 60;
+
+#[derive(Debug)]
+pub enum UserError {
+    ValidationError,
+    DatabaseError(Error),
+    DuplicateNameError,
+    InvalidCredentials,
+    InvalidToken,
+    HashError,
+    NotFound,
+    LastAdminError,
+    RegistrationClosed
+}
+
+/// Claims of the bearer tokens issued on login.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: String,
+    pub username: String,
+    pub iat: i64,
+    pub exp: i64
+}
+
+pub struct UserService {
+    pub user_repository: UserRepository,
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+    open_registration: bool
+}
+
+impl UserService {
+    /// `secret` signs the JWTs; it comes from Rocket's `secret_key`.
+    /// With `open_registration` off users can only be created by admins' tools.
+    pub fn new(db_pool: MySqlPool, secret: &[u8], open_registration: bool) -> Self {
+        let user_repository = UserRepository::new(db_pool.clone());
+        Self {
+            user_repository,
+            encoding_key: EncodingKey::from_secret(secret),
+            decoding_key: DecodingKey::from_secret(secret),
+            open_registration
+        }
+    }
+
+    async fn add_user(&self, credentials: UserCredentialsDTO, role: Role) -> Result<UserDTO, UserError> {
+        if credentials.validate().is_err() {
+            return Err(UserError::ValidationError);
+        }
+
+        if self.user_repository.does_username_exist(&credentials.username).await.map_err(UserError::DatabaseError)? {
+            return Err(UserError::DuplicateNameError);
+        }
+
+        let password = credentials.password;
+        let password_hash = tokio::task::spawn_blocking(move || {
+            let salt = SaltString::generate(&mut OsRng);
+            Argon2::default()
+                .hash_password(password.as_bytes(), &salt)
+                .map(|hash| hash.to_string())
+        })
+        .await
+        .map_err(|_| UserError::HashError)?
+        .map_err(|_| UserError::HashError)?;
+
+        let mut user = User {
+            id_user: 0,
+            username: credentials.username,
+            password_hash,
+            role: role.as_str().to_string()
+        };
+
+        // A concurrent registration of the same username hits the unique index.
+        user.id_user = self.user_repository.add_user(&user).await.map_err(|err| match err {
+            Error::Database(ref db_err) if db_err.is_unique_violation() => UserError::DuplicateNameError,
+            err => UserError::DatabaseError(err)
+        })?;
+
+        Ok(user.into())
+    }
+}
+
+/// Hash of a random password with the same parameters as real ones, computed
+/// once.
+fn dummy_password_hash() -> &'static str {
+    static DUMMY_PASSWORD_HASH: OnceLock<String> = OnceLock::new();
+
+    DUMMY_PASSWORD_HASH.get_or_init(|| {
+        let mut password = [0u8; 32];
+        OsRng.fill_bytes(&mut password);
+
+        Argon2::default()
+            .hash_password(&password, &SaltString::generate(&mut OsRng))
+            .map(|hash| hash.to_string())
+            .unwrap_or_default()
+    })
+}
+
+#[async_trait]
+pub trait UserServiceTrait: Send + Sync {
+    async fn register(&self, credentials: UserCredentialsDTO) -> Result<UserDTO, UserError>;
+    /// Creates an admin account; used by the `create-admin` command.
+    async fn create_admin(&self, credentials: UserCredentialsDTO) -> Result<UserDTO, UserError>;
+    async fn login(&self, credentials: UserCredentialsDTO) -> Result<TokenDTO, UserError>;
+    fn verify_token(&self, token: &str) -> Result<Claims, UserError>;
+    async fn get_user(&self, id: i64) -> Result<UserDTO, UserError>;
+    async fn get_all_users(&self) -> Result<Vec<UserDTO>, UserError>;
+    async fn set_role(&self, id: i64, role: Role) -> Result<(), UserError>;
+}
+
+#[async_trait]
+impl UserServiceTrait for UserService {
+    async fn register(&self, credentials: UserCredentialsDTO) -> Result<UserDTO, UserError> {
+        if !self.open_registration {
+            return Err(UserError::RegistrationClosed);
+        }
+
+        self.add_user(credentials, Role::default()).await
+    }
+
+    async fn create_admin(&self, credentials: UserCredentialsDTO) -> Result<UserDTO, UserError> {
+        self.add_user(credentials, Role::Admin).await
+    }
+
+    async fn login(&self, credentials: UserCredentialsDTO) -> Result<TokenDTO, UserError> {
+        let user = self.user_repository.get_by_username(&credentials.username).await
+            .map_err(UserError::DatabaseError)?;
+
+        // Unknown usernames are checked against a dummy hash so that they take
+        // as long to reject as wrong passwords.
+        let password = credentials.password;
+        let password_hash = user.as_ref().map(|user| user.password_hash.clone());
+        let verified = tokio::task::spawn_blocking(move || {
+            let password_hash = password_hash.as_deref().unwrap_or_else(|| dummy_password_hash());
+
+            PasswordHash::new(password_hash)
+                .map(|hash| Argon2::default().verify_password(password.as_bytes(), &hash).is_ok())
+        })
+        .await
+        .map_err(|_| UserError::HashError)?
+        .map_err(|_| UserError::HashError)?;
+
+        let user = match user {
+            Some(user) if verified => user,
+            _ => return Err(UserError::InvalidCredentials)
+        };
+
+        let now = chrono::Utc::now().timestamp();
+        let claims = Claims {
+            sub: user.id_user.to_string(),
+            username: user.username,
+            iat: now,
+            exp: now + TOKEN_LIFETIME_SECONDS
+        };
+
+        let token = jsonwebtoken::encode(&Header::default(), &claims, &self.encoding_key)
+            .map_err(|_| UserError::InvalidToken)?;
+
+        Ok(TokenDTO {
+            token,
+            token_type: "Bearer".to_string(),
+            expires_in: TOKEN_LIFETIME_SECONDS
+        })
+    }
+
+    fn verify_token(&self, token: &str) -> Result<Claims, UserError> {
+        jsonwebtoken::decode::<Claims>(token, &self.decoding_key, &Validation::default())
+            .map(|data| data.claims)
+            .map_err(|_| UserError::InvalidToken)
+    }
+
+    async fn get_user(&self, id: i64) -> Result<UserDTO, UserError> {
+        self.user_repository.get_by_id(id).await
+            .map_err(UserError::DatabaseError)?
+            .map(UserDTO::from)
+            .ok_or(UserError::NotFound)
+    }
+
+    async fn get_all_users(&self) -> Result<Vec<UserDTO>, UserError> {
+        let users = self.user_repository.get_all_users().await.map_err(UserError::DatabaseError)?;
+
+        Ok(users.into_iter().map(UserDTO::from).collect())
+    }
+
+    async fn set_role(&self, id: i64, role: Role) -> Result<(), UserError> {
+        let user = self.get_user(id).await?;
+
+        if user.role == Role::Admin && role != Role::Admin {
+            let admins = self.user_repository.count_users_with_role(Some(Role::Admin.as_str())).await.map_err(UserError::DatabaseError)?;
+
+            if admins <= 1 {
+                return Err(UserError::LastAdminError);
+            }
+        }
+
+        self.user_repository.update_role(id, role.as_str()).await.map_err(UserError::DatabaseError)
+    }
+}
@@ -0,0 +1,137 @@
+/// Units accepted in recipe ingredient amounts. Volumes are converted to grams
+/// assuming the density of water; pieces and pinches cannot be converted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    Gram,
+    Kilogram,
+    Milligram,
+    Ounce,
+    Pound,
+    Milliliter,
+    Centiliter,
+    Deciliter,
+    Liter,
+    Teaspoon,
+    Tablespoon,
+    Cup,
+    FluidOunce,
+    Pinch,
+    Piece
+}
+
+const UNIT_NAMES: [(Unit, &[&str]); 15] = [
+    (Unit::Gram, &["g", "gr", "grs", "gram", "grams", "gramo", "gramos"]),
+    (Unit::Kilogram, &["kg", "kgs", "kilo", "kilos", "kilogram", "kilograms", "kilogramo", "kilogramos"]),
+    (Unit::Milligram, &["mg", "milligram", "milligrams", "miligramo", "miligramos"]),
+    (Unit::Ounce, &["oz", "ounce", "ounces", "onza", "onzas"]),
+    (Unit::Pound, &["lb", "lbs", "pound", "pounds", "libra", "libras"]),
+    (Unit::Milliliter, &["ml", "milliliter", "milliliters", "millilitre", "millilitres", "mililitro", "mililitros", "cc"]),
+    (Unit::Centiliter, &["cl", "centiliter", "centiliters", "centilitro", "centilitros"]),
+    (Unit::Deciliter, &["dl", "deciliter", "deciliters", "decilitro", "decilitros"]),
+    (Unit::Liter, &["l", "lt", "liter", "liters", "litre", "litres", "litro", "litros"]),
+    (Unit::Teaspoon, &["tsp", "teaspoon", "teaspoons", "cdta", "cdita", "cucharadita", "cucharaditas"]),
+    (Unit::Tablespoon, &["tbsp", "tbs", "tablespoon", "tablespoons", "cda", "cucharada", "cucharadas"]),
+    (Unit::Cup, &["cup", "cups", "taza", "tazas"]),
+    (Unit::FluidOunce, &["fl oz", "fluid ounce", "fluid ounces"]),
+    (Unit::Pinch, &["pinch", "pinches", "pizca", "pizcas"]),
+    (Unit::Piece, &["unit", "units", "piece", "pieces", "unidad", "unidades", "u"])
+];
+
+impl Unit {
+    pub fn parse(unit: &str) -> Option<Unit> {
+        let unit = unit.trim().trim_end_matches('.').to_lowercase();
+
+        UNIT_NAMES
+            .iter()
+            .find(|(_, names)| names.contains(&unit.as_str()))
+            .map(|(unit, _)| *unit)
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        UNIT_NAMES
+            .iter()
+            .find(|(unit, _)| unit == self)
+            .map(|(_, names)| names[0])
+            .unwrap_or_default()
+    }
+
+    /// Grams in one of this unit, if it can be weighed.
+    pub fn grams(&self) -> Option<f64> {
+        match self {
+            Unit::Gram | Unit::Milliliter => Some(1.0),
+            Unit::Kilogram | Unit::Liter => Some(1000.0),
+            Unit::Milligram => Some(0.001),
+            Unit::Ounce => Some(28.3495),
+            Unit::Pound => Some(453.592),
+            Unit::Centiliter => Some(10.0),
+            Unit::Deciliter => Some(100.0),
+            Unit::Teaspoon => Some(5.0),
+            Unit::Tablespoon => Some(15.0),
+            Unit::Cup => Some(240.0),
+            Unit::FluidOunce => Some(29.5735),
+            Unit::Pinch | Unit::Piece => None
+        }
+    }
+
+    /// The unit amounts of this one are added up in: grams for weights and
+    /// millilitres for volumes, whose factor is then the same as `grams`.
+    pub fn base(&self) -> Option<Unit> {
+        match self {
+            Unit::Gram | Unit::Kilogram | Unit::Milligram | Unit::Ounce | Unit::Pound => Some(Unit::Gram),
+            Unit::Pinch | Unit::Piece => None,
+            _ => Some(Unit::Milliliter)
+        }
+    }
+
+    /// Smallest step worth measuring in this unit: whole grams and millilitres
+    /// (5 above 100, half below 10), quarters of spoons, cups and ounces, and
+    /// halves of pieces.
+    fn step(&self, amount: f64) -> f64 {
+        match self {
+            Unit::Gram | Unit::Milliliter if amount < 10.0 => 0.5,
+            Unit::Gram | Unit::Milliliter if amount < 100.0 => 1.0,
+            Unit::Gram | Unit::Milliliter => 5.0,
+            Unit::Milligram => 1.0,
+            Unit::Kilogram | Unit::Liter => 0.05,
+            Unit::Centiliter | Unit::Deciliter | Unit::Piece => 0.5,
+            Unit::Pinch => 1.0,
+            Unit::Ounce | Unit::Pound | Unit::Teaspoon | Unit::Tablespoon | Unit::Cup | Unit::FluidOunce => 0.25
+        }
+    }
+
+    /// Rounds to the unit's step, never down to nothing.
+    pub fn round(&self, amount: f64) -> f64 {
+        let step = self.step(amount);
+
+        if amount <= 0.0 {
+            return amount;
+        }
+
+        let rounded = ((amount / step).round() * step).max(step);
+
+        (rounded * 100.0).round() / 100.0
+    }
+}
+
+pub fn to_grams(amount: f64, unit: &str) -> Option<f64> {
+    Unit::parse(unit)?.grams().map(|grams| amount * grams)
+}
+
+/// Rounds `amount` of `unit` as the unit is measured. Amounts in unknown units
+/// are only rounded to two decimals.
+pub fn round(amount: f64, unit: &str) -> f64 {
+    match Unit::parse(unit) {
+        Some(unit) => unit.round(amount),
+        None => (amount * 100.0).round() / 100.0
+    }
+}
+
+/// Scales `amount` of `unit` by `factor`, e.g. to cook for more people.
+pub fn scale(amount: f64, unit: &str, factor: f64) -> f64 {
+    round(amount * factor, unit)
+}
+
+/// Formats an amount with at most two decimals and no trailing zeros.
+pub fn format_amount(amount: f64) -> String {
+    format!("{}", (amount * 100.0).round() / 100.0)
+}
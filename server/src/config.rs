@@ -0,0 +1,297 @@
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use rocket::figment::providers::Env;
+use rocket::figment::Figment;
+use rocket::http::Method;
+use serde::Deserialize;
+
+use crate::business::meal_slots::MealType;
+use crate::logging::LogFormat;
+use crate::telemetry::TraceExporter;
+
+/// Prefix of the environment variables overriding `Rocket.toml`, nested keys are
+/// separated by `__`, e.g. `APP_DATABASE__MAX_CONNECTIONS=10`.
+pub const ENV_PREFIX: &str = "APP_";
+
+/// Settings of the application, read from the same figment as Rocket's own so
+/// both live in `Rocket.toml` under the selected profile.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    pub database: DatabaseConfig,
+    pub tls: Option<TlsConfig>,
+    pub cors: CorsConfig,
+    pub logging: LoggingConfig,
+    pub features: FeaturesConfig,
+    pub meal_plans: MealPlanConfig,
+    pub rate_limit: RateLimitConfig,
+    pub shutdown: ShutdownConfig
+}
+
+/// Connection pool settings. Timeouts are in seconds, an idle timeout or
+/// lifetime of 0 keeps connections open indefinitely.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct DatabaseConfig {
+    pub url: String,
+    pub max_connections: u32,
+    pub min_connections: u32,
+    pub acquire_timeout: u64,
+    pub idle_timeout: u64,
+    pub max_lifetime: u64
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        DatabaseConfig {
+            url: String::new(),
+            max_connections: 5,
+            min_connections: 0,
+            acquire_timeout: 30,
+            idle_timeout: 600,
+            max_lifetime: 1800
+        }
+    }
+}
+
+impl DatabaseConfig {
+    pub fn acquire_timeout(&self) -> Duration {
+        Duration::from_secs(self.acquire_timeout)
+    }
+
+    pub fn idle_timeout(&self) -> Option<Duration> {
+        (self.idle_timeout > 0).then(|| Duration::from_secs(self.idle_timeout))
+    }
+
+    pub fn max_lifetime(&self) -> Option<Duration> {
+        (self.max_lifetime > 0).then(|| Duration::from_secs(self.max_lifetime))
+    }
+}
+
+/// PEM certificate chain and private key. Rocket reads the same `tls` section to
+/// serve HTTPS, here the files are only checked to exist.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TlsConfig {
+    pub certs: PathBuf,
+    pub key: PathBuf
+}
+
+/// Origins allowed to call the API from a browser, `*` allows any. No origins
+/// means cross-origin requests are not answered with CORS headers. `max_age` is
+/// how long, in seconds, browsers may cache a preflight.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct CorsConfig {
+    pub allowed_origins: Vec<String>,
+    pub allowed_methods: Vec<String>,
+    pub allowed_headers: Vec<String>,
+    pub allow_credentials: bool,
+    pub max_age: u64
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        CorsConfig {
+            allowed_origins: Vec::new(),
+            allowed_methods: ["GET", "POST", "PUT", "DELETE"].map(String::from).to_vec(),
+            allowed_headers: ["Content-Type", "Authorization", "X-Api-Key", "X-Request-Id", "traceparent"]
+                .map(String::from)
+                .to_vec(),
+            allow_credentials: false,
+            max_age: 3600
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    pub format: LogFormat,
+    pub traces: TraceExporter
+}
+
+/// Parts of the API that can be switched off.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct FeaturesConfig {
+    /// Open registration. Admins are created with the `create-admin` command.
+    pub registration: bool,
+    /// The `/metrics` endpoint. Off by default, since it is not authenticated.
+    pub metrics: bool
+}
+
+impl Default for FeaturesConfig {
+    fn default() -> Self {
+        FeaturesConfig {
+            registration: true,
+            metrics: false
+        }
+    }
+}
+
+/// Seconds readiness reports draining after `SIGTERM` before the server stops
+/// accepting connections. Shares the `shutdown` section with Rocket's `grace`
+/// and `mercy`.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct ShutdownConfig {
+    pub drain: u64
+}
+
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        ShutdownConfig { drain: 5 }
+    }
+}
+
+impl ShutdownConfig {
+    pub fn drain(&self) -> Duration {
+        Duration::from_secs(self.drain)
+    }
+}
+
+/// Meal types accepted in meal plan slots besides breakfast, lunch, snack and dinner.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct MealPlanConfig {
+    pub custom_meal_types: Vec<MealType>
+}
+
+/// Token bucket settings: `burst` requests at once, refilled at `per_minute`.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct Limit {
+    pub burst: u32,
+    pub per_minute: u32
+}
+
+/// Token buckets of the rate limiter, one per group of routes.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    pub enabled: bool,
+    /// Identify anonymous clients by Rocket's `ip_header` (`X-Real-IP`) instead
+    /// of the connection's address; only safe behind a proxy that sets it.
+    pub trust_ip_header: bool,
+    pub read: Limit,
+    pub write: Limit,
+    pub import: Limit,
+    pub auth: Limit
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        RateLimitConfig {
+            enabled: true,
+            trust_ip_header: false,
+            read: Limit { burst: 60, per_minute: 120 },
+            write: Limit { burst: 20, per_minute: 30 },
+            import: Limit { burst: 2, per_minute: 2 },
+            auth: Limit { burst: 5, per_minute: 10 }
+        }
+    }
+}
+
+impl AppConfig {
+    /// Rocket's figment (`Rocket.toml` and `ROCKET_` variables) extended with the
+    /// `APP_` variables and, for older deployments, `DATABASE_URL`.
+    pub fn figment() -> Figment {
+        rocket::Config::figment()
+            .merge(Env::raw().only(&["DATABASE_URL"]).map(|_| "database.url".into()).global())
+            .merge(Env::prefixed(ENV_PREFIX).split("__").global())
+    }
+
+    /// Extracts and validates the configuration, listing every problem found.
+    pub fn from_figment(figment: &Figment) -> Result<AppConfig, String> {
+        let config: AppConfig = figment.extract().map_err(|err| format!("invalid configuration: {}", err))?;
+
+        let errors = config.validate();
+        if !errors.is_empty() {
+            return Err(format!("invalid configuration:\n  - {}", errors.join("\n  - ")));
+        }
+
+        Ok(config)
+    }
+
+    fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        let database = &self.database;
+
+        if database.url.trim().is_empty() {
+            errors.push(format!("database.url: not set, use Rocket.toml or {}DATABASE__URL", ENV_PREFIX));
+        } else if !database.url.starts_with("mysql://") {
+            errors.push("database.url: must be a mysql:// URL".to_string());
+        }
+        if database.max_connections == 0 {
+            errors.push("database.max_connections: must be greater than 0".to_string());
+        }
+        if database.min_connections > database.max_connections {
+            errors.push(format!(
+                "database.min_connections: {} is more than max_connections ({})",
+                database.min_connections, database.max_connections
+            ));
+        }
+        if database.acquire_timeout == 0 {
+            errors.push("database.acquire_timeout: must be greater than 0".to_string());
+        }
+
+        if let Some(tls) = &self.tls {
+            for (key, path) in [("tls.certs", &tls.certs), ("tls.key", &tls.key)] {
+                if !path.is_file() {
+                    errors.push(format!("{}: {} does not exist", key, path.display()));
+                }
+            }
+        }
+
+        for origin in &self.cors.allowed_origins {
+            if origin == "*" {
+                if self.cors.allow_credentials {
+                    errors.push("cors.allowed_origins: * cannot be used with allow_credentials".to_string());
+                }
+            } else if !is_origin(origin) {
+                errors.push(format!("cors.allowed_origins: {} is not an origin like https://example.com", origin));
+            }
+        }
+
+        for method in &self.cors.allowed_methods {
+            if Method::from_str(method).is_err() {
+                errors.push(format!("cors.allowed_methods: {} is not an HTTP method", method));
+            }
+        }
+        for header in &self.cors.allowed_headers {
+            if header.is_empty() || !header.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+                errors.push(format!("cors.allowed_headers: {} is not a header name", header));
+            }
+        }
+
+        for (index, meal_type) in self.meal_plans.custom_meal_types.iter().enumerate() {
+            if !meal_type.is_custom() {
+                errors.push(format!("meal_plans.custom_meal_types: {} is a built-in meal type", meal_type));
+            } else if meal_type.as_str().chars().count() > 45 {
+                errors.push(format!("meal_plans.custom_meal_types: {} is longer than 45 characters", meal_type));
+            } else if self.meal_plans.custom_meal_types[..index].contains(meal_type) {
+                errors.push(format!("meal_plans.custom_meal_types: {} is listed twice", meal_type));
+            }
+        }
+
+        let limits = &self.rate_limit;
+        for (group, limit) in [("read", limits.read), ("write", limits.write), ("import", limits.import), ("auth", limits.auth)] {
+            if limit.burst == 0 || limit.per_minute == 0 {
+                errors.push(format!("rate_limit.{}: burst and per_minute must be greater than 0", group));
+            }
+        }
+
+        errors
+    }
+}
+
+/// Scheme and host with an optional port, without path or trailing slash.
+fn is_origin(origin: &str) -> bool {
+    let host = match origin.strip_prefix("https://").or_else(|| origin.strip_prefix("http://")) {
+        Some(host) => host,
+        None => return false
+    };
+
+    !host.is_empty() && !host.contains(['/', '?', '#', ' '])
+}
@@ -0,0 +1,22 @@
+use chrono::NaiveDateTime;
+use sqlx::FromRow;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize, FromRow)]
+pub struct ApiKey {
+    #[serde(rename = "idApiKey")]
+    pub id_api_key: i64,
+    pub name: String,
+    pub prefix: String,
+    #[serde(rename = "KeyHash")]
+    pub key_hash: String,
+    pub scope: String,
+    #[serde(rename = "Users_idUser")]
+    pub id_user: i64,
+    #[serde(rename = "CreatedAt")]
+    pub created_at: NaiveDateTime,
+    #[serde(rename = "LastUsedAt")]
+    pub last_used_at: Option<NaiveDateTime>,
+    #[serde(rename = "RevokedAt")]
+    pub revoked_at: Option<NaiveDateTime>
+}
@@ -1,12 +1,20 @@
 use sqlx::FromRow;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, Serialize, FromRow)]
+#[derive(Debug, Clone, Deserialize, Serialize, FromRow)]
 pub struct Ingredient {
     #[serde(rename = "idIngredient")]
     pub id_ingredient: i64,
     pub name: String,
     pub proteins: f64,
     pub carbs: f64,
-    pub fats: f64
+    pub fats: f64,
+    #[serde(rename = "SourceId")]
+    pub source_id: Option<String>,
+    /// Comma-separated stored names of the allergens it contains, `None`
+    /// until they are given.
+    pub allergens: Option<String>,
+    /// Comma-separated stored names of its diet attributes, `None` until they
+    /// are given.
+    pub diet_attributes: Option<String>
 }
\ No newline at end of file
@@ -1,3 +1,4 @@
+use chrono::NaiveDate;
 use sqlx::FromRow;
 use serde::{Deserialize, Serialize};
 
@@ -6,5 +7,12 @@ pub struct MealPlans {
     #[serde(rename = "idMealPlan")]
     pub id_mealplan: i64,
     pub name: String,
-    pub category: String
+    pub category: String,
+    #[serde(rename = "Users_idUser")]
+    pub id_owner: Option<i64>,
+    pub visibility: String,
+    pub start_date: Option<NaiveDate>,
+    pub length_days: i32,
+    /// Comma-separated stored names of the diets it follows.
+    pub diets: String
 }
\ No newline at end of file
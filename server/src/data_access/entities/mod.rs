@@ -2,4 +2,9 @@ pub mod ingredients;
 pub mod recipes;
 pub mod recipe_ingredients;
 pub mod recipe_meals;
-pub mod meal_plans;
\ No newline at end of file
+pub mod meal_plans;
+pub mod users;
+pub mod api_keys;
+pub mod tags;
+pub mod recipe_steps;
+pub mod recipe_components;
\ No newline at end of file
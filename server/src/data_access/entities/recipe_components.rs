@@ -0,0 +1,13 @@
+use sqlx::FromRow;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize, FromRow)]
+pub struct RecipeComponent {
+    #[serde(rename = "idRecipeComponent")]
+    pub id_recipe_component: i64,
+    pub servings: f64,
+    #[serde(rename = "Recipes_idRecipe")]
+    pub recipes_id: i64,
+    #[serde(rename = "Components_idRecipe")]
+    pub components_id: i64
+}
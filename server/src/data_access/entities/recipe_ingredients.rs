@@ -1,7 +1,7 @@
 use sqlx::FromRow;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, Serialize, FromRow)]
+#[derive(Debug, Clone, Deserialize, Serialize, FromRow)]
 pub struct RecipeIngredients {
     #[serde(rename = "idRecipeIngredient")]
     pub id_recipe_ingredients: i64,
@@ -1,3 +1,4 @@
+use chrono::NaiveDate;
 use sqlx::FromRow;
 use serde::{Deserialize, Serialize};
 
@@ -7,8 +8,21 @@ pub struct RecipeMeals {
     pub id_recipe_meal: i64,
     pub day: String,
     pub meal_type: String,
+    pub date: Option<NaiveDate>,
+    pub servings: Option<i32>,
     #[serde(rename = "Recipes_idRecipe")]
     pub recipes_id: i64,
     #[serde(rename = "MealPlans_idMealPlan")]
     pub mealplan_id: i64
+}
+
+/// A slot of a dated meal plan with the names of its plan and recipe.
+#[derive(Debug, Deserialize, Serialize, FromRow)]
+pub struct PlannedMeal {
+    pub id_recipe_meal: i64,
+    pub meal_type: String,
+    pub mealplan_id: i64,
+    pub mealplan_name: String,
+    pub recipes_id: i64,
+    pub recipe_name: String
 }
\ No newline at end of file
@@ -0,0 +1,23 @@
+use sqlx::FromRow;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize, FromRow)]
+pub struct RecipeStep {
+    #[serde(rename = "idRecipeStep")]
+    pub id_recipe_step: i64,
+    pub position: i32,
+    pub text: String,
+    #[serde(rename = "DurationMinutes")]
+    pub duration_minutes: Option<i32>,
+    #[serde(rename = "Recipes_idRecipe")]
+    pub recipes_id: i64
+}
+
+/// An ingredient used in a step.
+#[derive(Debug, Deserialize, Serialize, FromRow)]
+pub struct RecipeStepIngredient {
+    #[serde(rename = "RecipeSteps_idRecipeStep")]
+    pub steps_id: i64,
+    #[serde(rename = "Ingredients_idIngredient")]
+    pub ingredients_id: i64
+}
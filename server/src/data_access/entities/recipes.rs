@@ -7,5 +7,9 @@ pub struct Recipe {
     pub id_recipe: i64,
     pub name: String,
     pub category: String,
-    pub instructions: String
+    pub instructions: String,
+    pub servings: i32,
+    #[serde(rename = "Users_idUser")]
+    pub id_owner: Option<i64>,
+    pub visibility: String
 }
\ No newline at end of file
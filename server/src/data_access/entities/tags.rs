@@ -0,0 +1,17 @@
+use sqlx::FromRow;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize, FromRow)]
+pub struct Tag {
+    #[serde(rename = "idTag")]
+    pub id_tag: i64,
+    pub name: String
+}
+
+/// A tag carried by a recipe.
+#[derive(Debug, Deserialize, Serialize, FromRow)]
+pub struct RecipeTag {
+    #[serde(rename = "Recipes_idRecipe")]
+    pub recipes_id: i64,
+    pub name: String
+}
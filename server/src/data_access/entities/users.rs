@@ -0,0 +1,12 @@
+use sqlx::FromRow;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize, FromRow)]
+pub struct User {
+    #[serde(rename = "idUser")]
+    pub id_user: i64,
+    pub username: String,
+    #[serde(rename = "PasswordHash")]
+    pub password_hash: String,
+    pub role: String
+}
@@ -1,2 +1,7 @@
+use sqlx::migrate::Migrator;
+
 pub mod entities;
-pub mod repository;
\ No newline at end of file
+pub mod repository;
+
+/// Schema migrations from the `migrations` directory, embedded at build time.
+pub static MIGRATOR: Migrator = sqlx::migrate!();
@@ -0,0 +1,126 @@
+use sqlx::{MySql, MySqlPool, Error};
+use tracing::instrument;
+
+use crate::data_access::entities::api_keys::ApiKey;
+use crate::metrics::QueryTimer;
+
+const SELECT_API_KEYS: &str = "SELECT idApiKey as id_api_key, name, prefix, KeyHash as key_hash, scope, Users_idUser as id_user, CreatedAt as created_at, LastUsedAt as last_used_at, RevokedAt as revoked_at FROM ApiKeys";
+
+pub struct ApiKeyRepository {
+    pub db_pool: MySqlPool,
+}
+
+impl ApiKeyRepository {
+    pub fn new(db_pool: MySqlPool) -> Self {
+        Self { db_pool }
+    }
+
+    #[instrument(name = "ApiKeyRepository::add_api_key", skip_all)]
+    pub async fn add_api_key(&self, api_key: &ApiKey) -> Result<i64, Error> {
+        let _timer = QueryTimer::new("api_key", "add_api_key");
+        let query = r#"
+            INSERT INTO ApiKeys (Name, Prefix, KeyHash, Scope, Users_idUser)
+            VALUES (?, ?, ?, ?, ?)
+        "#;
+
+        match sqlx::query(query)
+            .bind(&api_key.name)
+            .bind(&api_key.prefix)
+            .bind(&api_key.key_hash)
+            .bind(&api_key.scope)
+            .bind(api_key.id_user)
+            .execute(&self.db_pool)
+            .await
+        {
+            Ok(result) => Ok(result.last_insert_id() as i64),
+            Err(err) => {
+                log::error!("Error adding API key to the database: {}", err);
+                Err(err)
+            }
+        }
+    }
+
+    #[instrument(name = "ApiKeyRepository::get_all_api_keys", skip_all)]
+    pub async fn get_all_api_keys(&self) -> Result<Vec<ApiKey>, Error> {
+        let _timer = QueryTimer::new("api_key", "get_all_api_keys");
+        match sqlx::query_as::<MySql, ApiKey>(&format!("{} ORDER BY idApiKey", SELECT_API_KEYS))
+            .fetch_all(&self.db_pool)
+            .await
+        {
+            Ok(api_keys) => Ok(api_keys),
+            Err(err) => {
+                log::error!("Error retrieving API keys from the database: {}", err);
+                Err(err)
+            }
+        }
+    }
+
+    #[instrument(name = "ApiKeyRepository::get_by_id", skip_all)]
+    pub async fn get_by_id(&self, id: i64) -> Result<Option<ApiKey>, Error> {
+        let _timer = QueryTimer::new("api_key", "get_by_id");
+        match sqlx::query_as::<MySql, ApiKey>(&format!("{} WHERE idApiKey = ?", SELECT_API_KEYS))
+            .bind(id)
+            .fetch_optional(&self.db_pool)
+            .await
+        {
+            Ok(api_key) => Ok(api_key),
+            Err(err) => {
+                log::error!("Error retrieving API key from the database: {}", err);
+                Err(err)
+            }
+        }
+    }
+
+    /// Only keys that have not been revoked are returned.
+    #[instrument(name = "ApiKeyRepository::get_active_by_hash", skip_all)]
+    pub async fn get_active_by_hash(&self, key_hash: &str) -> Result<Option<ApiKey>, Error> {
+        let _timer = QueryTimer::new("api_key", "get_active_by_hash");
+        match sqlx::query_as::<MySql, ApiKey>(&format!("{} WHERE KeyHash = ? AND RevokedAt IS NULL", SELECT_API_KEYS))
+            .bind(key_hash)
+            .fetch_optional(&self.db_pool)
+            .await
+        {
+            Ok(api_key) => Ok(api_key),
+            Err(err) => {
+                log::error!("Error retrieving API key from the database: {}", err);
+                Err(err)
+            }
+        }
+    }
+
+    #[instrument(name = "ApiKeyRepository::touch_api_key", skip_all)]
+    pub async fn touch_api_key(&self, id: i64) -> Result<(), Error> {
+        let _timer = QueryTimer::new("api_key", "touch_api_key");
+        let query = "UPDATE ApiKeys SET LastUsedAt = CURRENT_TIMESTAMP WHERE idApiKey = ?";
+
+        match sqlx::query(query)
+            .bind(id)
+            .execute(&self.db_pool)
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                log::error!("Error updating API key in the database: {}", err);
+                Err(err)
+            }
+        }
+    }
+
+    #[instrument(name = "ApiKeyRepository::revoke_api_key", skip_all)]
+    pub async fn revoke_api_key(&self, id: i64) -> Result<(), Error> {
+        let _timer = QueryTimer::new("api_key", "revoke_api_key");
+        let query = "UPDATE ApiKeys SET RevokedAt = CURRENT_TIMESTAMP WHERE idApiKey = ? AND RevokedAt IS NULL";
+
+        match sqlx::query(query)
+            .bind(id)
+            .execute(&self.db_pool)
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                log::error!("Error revoking API key in the database: {}", err);
+                Err(err)
+            }
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use sqlx::{MySqlPool, Error};
+
+pub struct HealthRepository {
+    pub db_pool: MySqlPool,
+}
+
+impl HealthRepository {
+    pub fn new(db_pool: MySqlPool) -> Self {
+        Self { db_pool }
+    }
+
+    pub async fn ping(&self) -> Result<(), Error> {
+        match sqlx::query("SELECT 1").execute(&self.db_pool).await {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                log::error!("Error pinging the database: {}", err);
+                Err(err)
+            }
+        }
+    }
+
+    /// Versions of the migrations recorded by sqlx that completed successfully.
+    pub async fn get_applied_migrations(&self) -> Result<Vec<i64>, Error> {
+        match sqlx::query_scalar("SELECT version FROM _sqlx_migrations WHERE success = TRUE")
+            .fetch_all(&self.db_pool)
+            .await
+        {
+            Ok(versions) => Ok(versions),
+            Err(err) => {
+                log::error!("Error retrieving applied migrations from the database: {}", err);
+                Err(err)
+            }
+        }
+    }
+
+    /// Open connections, idle connections and the configured maximum.
+    pub fn pool_stats(&self) -> (u32, u32, u32) {
+        (self.db_pool.size(), self.db_pool.num_idle() as u32, self.db_pool.options().get_max_connections())
+    }
+}
@@ -1,5 +1,9 @@
-use sqlx::{MySql, MySqlPool, Error};
+use sqlx::{MySql, MySqlPool, Error, Transaction};
+use tracing::instrument;
+
+use crate::metrics::QueryTimer;
 use crate::data_access::entities::ingredients::Ingredient; 
+use crate::data_access::repository::placeholders;
 
 pub struct IngredientRepository {
     pub db_pool: MySqlPool,
@@ -10,10 +14,12 @@ impl IngredientRepository {
         Self { db_pool }
     }
 
-    pub async fn add_ingredient(&self, ingredient: &Ingredient) -> Result<(), Error> {
+    #[instrument(name = "IngredientRepository::add_ingredient", skip_all)]
+    pub async fn add_ingredient(&self, ingredient: &Ingredient) -> Result<i64, Error> {
+        let _timer = QueryTimer::new("ingredient", "add_ingredient");
         let query = r#"
-            INSERT INTO ingredients (idIngredient, Name, Proteins, Carbs, Fats)
-            VALUES (?, ?, ?, ?, ?)
+            INSERT INTO ingredients (idIngredient, Name, Proteins, Carbs, Fats, Allergens, DietAttributes)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
         "#;
     
         match sqlx::query(query)
@@ -22,10 +28,12 @@ impl IngredientRepository {
             .bind(ingredient.proteins)
             .bind(ingredient.carbs)
             .bind(ingredient.fats)
+            .bind(ingredient.allergens.as_deref())
+            .bind(ingredient.diet_attributes.as_deref())
             .execute(&self.db_pool)
             .await
         {
-            Ok(_) => Ok(()),
+            Ok(result) => Ok(result.last_insert_id() as i64),
             Err(err) => {
                 log::error!("Error adding ingredient to the database: {}", err);
                 Err(err)
@@ -33,8 +41,107 @@ impl IngredientRepository {
         }
     }
 
+    /// Adds ingredients as part of a larger write, returning their ids.
+    #[instrument(name = "IngredientRepository::add_ingredients", skip_all)]
+    pub async fn add_ingredients(&self, transaction: &mut Transaction<'_, MySql>, ingredients: &[Ingredient]) -> Result<Vec<i64>, Error> {
+        let _timer = QueryTimer::new("ingredient", "add_ingredients");
+        let mut ids = Vec::with_capacity(ingredients.len());
+
+        for ingredient in ingredients {
+            match sqlx::query("INSERT INTO ingredients (Name, Proteins, Carbs, Fats, Allergens, DietAttributes) VALUES (?, ?, ?, ?, ?, ?)")
+                .bind(ingredient.name.as_str())
+                .bind(ingredient.proteins)
+                .bind(ingredient.carbs)
+                .bind(ingredient.fats)
+                .bind(ingredient.allergens.as_deref())
+                .bind(ingredient.diet_attributes.as_deref())
+                .execute(&mut **transaction)
+                .await
+            {
+                Ok(result) => ids.push(result.last_insert_id() as i64),
+                Err(err) => {
+                    log::error!("Error adding ingredient to the database: {}", err);
+                    return Err(err);
+                }
+            }
+        }
+
+        Ok(ids)
+    }
+
+    #[instrument(name = "IngredientRepository::import_ingredients", skip_all)]
+    pub async fn import_ingredients(&self, ingredients: &[Ingredient]) -> Result<(), Error> {
+        let _timer = QueryTimer::new("ingredient", "import_ingredients");
+        let mut transaction = self.db_pool.begin().await?;
+
+        for ingredient in ingredients {
+            let query = if ingredient.id_ingredient == 0 {
+                sqlx::query("INSERT INTO ingredients (Name, Proteins, Carbs, Fats, Allergens, DietAttributes) VALUES (?, ?, ?, ?, ?, ?)")
+                    .bind(ingredient.name.as_str())
+                    .bind(ingredient.proteins)
+                    .bind(ingredient.carbs)
+                    .bind(ingredient.fats)
+                    .bind(ingredient.allergens.as_deref())
+                    .bind(ingredient.diet_attributes.as_deref())
+            } else {
+                sqlx::query("UPDATE ingredients SET Name = ?, Proteins = ?, Carbs = ?, Fats = ?, Allergens = ?, DietAttributes = ? WHERE idIngredient = ?")
+                    .bind(ingredient.name.as_str())
+                    .bind(ingredient.proteins)
+                    .bind(ingredient.carbs)
+                    .bind(ingredient.fats)
+                    .bind(ingredient.allergens.as_deref())
+                    .bind(ingredient.diet_attributes.as_deref())
+                    .bind(ingredient.id_ingredient)
+            };
+
+            if let Err(err) = query.execute(&mut *transaction).await {
+                transaction.rollback().await?;
+                log::error!("Error importing ingredient into the database: {}", err);
+                return Err(err);
+            }
+        }
+
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    #[instrument(name = "IngredientRepository::save_sourced_ingredients", skip_all)]
+    pub async fn save_sourced_ingredients(&self, ingredients: &[Ingredient]) -> Result<(), Error> {
+        let _timer = QueryTimer::new("ingredient", "save_sourced_ingredients");
+        let mut transaction = self.db_pool.begin().await?;
+
+        for ingredient in ingredients {
+            let query = if ingredient.id_ingredient == 0 {
+                sqlx::query("INSERT INTO ingredients (Name, Proteins, Carbs, Fats, SourceId) VALUES (?, ?, ?, ?, ?)")
+                    .bind(ingredient.name.as_str())
+                    .bind(ingredient.proteins)
+                    .bind(ingredient.carbs)
+                    .bind(ingredient.fats)
+                    .bind(ingredient.source_id.as_deref())
+            } else {
+                sqlx::query("UPDATE ingredients SET Proteins = ?, Carbs = ?, Fats = ?, SourceId = ? WHERE idIngredient = ?")
+                    .bind(ingredient.proteins)
+                    .bind(ingredient.carbs)
+                    .bind(ingredient.fats)
+                    .bind(ingredient.source_id.as_deref())
+                    .bind(ingredient.id_ingredient)
+            };
+
+            if let Err(err) = query.execute(&mut *transaction).await {
+                transaction.rollback().await?;
+                log::error!("Error saving imported ingredient in the database: {}", err);
+                return Err(err);
+            }
+        }
+
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    #[instrument(name = "IngredientRepository::get_all_ingredients", skip_all)]
     pub async fn get_all_ingredients(&self) -> Result<Vec<Ingredient>, Error> {
-        match sqlx::query_as::<MySql, Ingredient>("SELECT IdIngredient as id_ingredient, name, proteins, carbs, fats FROM ingredients")
+        let _timer = QueryTimer::new("ingredient", "get_all_ingredients");
+        match sqlx::query_as::<MySql, Ingredient>("SELECT IdIngredient as id_ingredient, name, proteins, carbs, fats, SourceId as source_id, Allergens as allergens, DietAttributes as diet_attributes FROM ingredients")
             .fetch_all(&self.db_pool)
             .await
         {
@@ -46,8 +153,10 @@ impl IngredientRepository {
         }
     }
 
+    #[instrument(name = "IngredientRepository::get_by_id", skip_all)]
     pub async fn get_by_id(&self, id: &i64) -> Result<Option<Ingredient>, Error> {
-        let query = "SELECT IdIngredient as id_ingredient, name, proteins, carbs, fats FROM ingredients WHERE idIngredient = ?";
+        let _timer = QueryTimer::new("ingredient", "get_by_id");
+        let query = "SELECT IdIngredient as id_ingredient, name, proteins, carbs, fats, SourceId as source_id, Allergens as allergens, DietAttributes as diet_attributes FROM ingredients WHERE idIngredient = ?";
         
         match sqlx::query_as::<MySql, Ingredient>(query)
             .bind(id)
@@ -62,10 +171,37 @@ impl IngredientRepository {
         }
     }
 
+    #[instrument(name = "IngredientRepository::get_by_ids", skip_all)]
+    pub async fn get_by_ids(&self, ids: &[i64]) -> Result<Vec<Ingredient>, Error> {
+        let _timer = QueryTimer::new("ingredient", "get_by_ids");
+
+        if ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let query = format!("SELECT IdIngredient as id_ingredient, name, proteins, carbs, fats, SourceId as source_id, Allergens as allergens, DietAttributes as diet_attributes FROM ingredients WHERE idIngredient IN ({})", placeholders(ids.len()));
+        let mut select = sqlx::query_as::<MySql, Ingredient>(&query);
+
+        for id in ids {
+            select = select.bind(id);
+        }
+
+        match select.fetch_all(&self.db_pool).await
+        {
+            Ok(ingredients) => Ok(ingredients),
+            Err(err) => {
+                log::error!("Error retrieving ingredients from the database: {}", err);
+                Err(err)
+            }
+        }
+    }
+
+    #[instrument(name = "IngredientRepository::update_ingredient", skip_all)]
     pub async fn update_ingredient(&self, ingredient: &Ingredient) -> Result<(), Error> {
+        let _timer = QueryTimer::new("ingredient", "update_ingredient");
         let query = r#"
             UPDATE ingredients
-            SET Name = ?, Proteins = ?, Carbs = ?, Fats = ?
+            SET Name = ?, Proteins = ?, Carbs = ?, Fats = ?, Allergens = ?, DietAttributes = ?
             WHERE idIngredient = ?
         "#;
     
@@ -74,6 +210,8 @@ impl IngredientRepository {
             .bind(ingredient.proteins)
             .bind(ingredient.carbs)
             .bind(ingredient.fats)
+            .bind(ingredient.allergens.as_deref())
+            .bind(ingredient.diet_attributes.as_deref())
             .bind(ingredient.id_ingredient)
             .execute(&self.db_pool)
             .await
@@ -86,7 +224,9 @@ impl IngredientRepository {
         }
     }
 
+    #[instrument(name = "IngredientRepository::delete_ingredient", skip_all)]
     pub async fn delete_ingredient(&self, ingredient_id: i64) -> Result<(), Error> {
+        let _timer = QueryTimer::new("ingredient", "delete_ingredient");
         let query = "DELETE FROM Ingredients WHERE idIngredient = ?";
     
         match sqlx::query(query)
@@ -102,7 +242,9 @@ impl IngredientRepository {
         }
     }
 
+    #[instrument(name = "IngredientRepository::does_name_exist", skip_all)]
     pub async fn does_name_exist(&self, name: &str) -> Result<bool, Error> {
+        let _timer = QueryTimer::new("ingredient", "does_name_exist");
         let query = "SELECT COUNT(*) FROM ingredients WHERE Name = ?";
         let count: i64 = sqlx::query_scalar(query)
             .bind(name)
@@ -112,7 +254,9 @@ impl IngredientRepository {
         Ok(count > 0)
     }
 
+    #[instrument(name = "IngredientRepository::does_name_exist_and_id", skip_all)]
     pub async fn does_name_exist_and_id(&self, id_ingredient: &i64, name: &str) -> Result<bool, Error> {
+        let _timer = QueryTimer::new("ingredient", "does_name_exist_and_id");
         let query = "SELECT COUNT(*) FROM ingredients WHERE Name = ? AND idIngredient != ?";
         let count: i64 = sqlx::query_scalar(query)
             .bind(name)
@@ -123,7 +267,9 @@ impl IngredientRepository {
         Ok(count > 0)
     }
 
+    #[instrument(name = "IngredientRepository::is_ingredient_in_recipe_ingredient", skip_all)]
     pub async fn is_ingredient_in_recipe_ingredient(&self, ingredient_id: i64,) -> Result<bool, Error> {
+        let _timer = QueryTimer::new("ingredient", "is_ingredient_in_recipe_ingredient");
         let query = "SELECT COUNT(*) FROM recipeIngredients WHERE Ingredients_idIngredient = ?";
     
         let count: i64 = sqlx::query_scalar(query)
@@ -134,11 +280,13 @@ impl IngredientRepository {
         Ok(count > 0)
     }
 
-    pub async fn ingredient_exist(&self, id: i64) -> Result<bool, Error> {
+    #[instrument(name = "IngredientRepository::ingredient_exist", skip_all)]
+    pub async fn ingredient_exist(&self, transaction: &mut Transaction<'_, MySql>, id: i64) -> Result<bool, Error> {
+        let _timer = QueryTimer::new("ingredient", "ingredient_exist");
         let query = "SELECT COUNT(*) FROM ingredients WHERE idIngredient = ?";
         let count: i64 = sqlx::query_scalar(query)
             .bind(id)
-            .fetch_one(&self.db_pool)
+            .fetch_one(&mut **transaction)
             .await?;
     
         Ok(count > 0)
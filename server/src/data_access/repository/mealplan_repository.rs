@@ -1,6 +1,8 @@
-use sqlx::{MySql, MySqlPool, Error};
+use sqlx::{MySql, MySqlPool, Error, Transaction};
+use tracing::instrument;
 
 use crate::data_access::entities::meal_plans::MealPlans;
+use crate::metrics::QueryTimer;
 pub struct MealPlanRepository {
     pub db_pool: MySqlPool,
 }
@@ -10,32 +12,42 @@ impl MealPlanRepository {
         Self { db_pool }
     }
 
-    pub async fn add_mealplan(&self, meal_plan: &MealPlans) -> Result<i64, Error> {
-        let mut transaction = self.db_pool.begin().await?;
-        
+    #[instrument(name = "MealPlanRepository::add_mealplan", skip_all)]
+    pub async fn add_mealplan(&self, transaction: &mut Transaction<'_, MySql>, meal_plan: &MealPlans) -> Result<i64, Error> {
+        let _timer = QueryTimer::new("mealplan", "add_mealplan");
         let meal_plan_id: i64 = match sqlx::query(r#"
-            INSERT INTO MealPlans (Name, Category)
-            VALUES (?, ?)
+            INSERT INTO MealPlans (Name, Category, Users_idUser, Visibility, StartDate, LengthDays, Diets)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
         "#)
         .bind(&meal_plan.name)
         .bind(&meal_plan.category)
-        .execute(&mut *transaction)
+        .bind(meal_plan.id_owner)
+        .bind(&meal_plan.visibility)
+        .bind(meal_plan.start_date)
+        .bind(meal_plan.length_days)
+        .bind(&meal_plan.diets)
+        .execute(&mut **transaction)
         .await
         {
             Ok(result) => result.last_insert_id() as i64,
             Err(err) => {
-                transaction.rollback().await?;
                 log::error!("Error adding meal plan to the database: {}", err);
                 return Err(err);
             }
         };
     
-        transaction.commit().await?;
         Ok(meal_plan_id)
     }
 
-    pub async fn get_all_mealplans(&self) -> Result<Vec<MealPlans>, Error> {
-        match sqlx::query_as::<MySql, MealPlans>("SELECT IdMealPlan as id_mealplan, name, category FROM mealplans")
+    /// Meal plans the user can see, with the same rules as recipes.
+    #[instrument(name = "MealPlanRepository::get_visible_mealplans", skip_all)]
+    pub async fn get_visible_mealplans(&self, id_user: Option<i64>) -> Result<Vec<MealPlans>, Error> {
+        let _timer = QueryTimer::new("mealplan", "get_visible_mealplans");
+        let query = "SELECT IdMealPlan as id_mealplan, name, category, Users_idUser as id_owner, visibility, StartDate as start_date, LengthDays as length_days, Diets as diets FROM mealplans WHERE Visibility = 'public' OR (? IS NOT NULL AND (Visibility = 'household' OR Users_idUser IS NULL OR Users_idUser = ?))";
+
+        match sqlx::query_as::<MySql, MealPlans>(query)
+            .bind(id_user)
+            .bind(id_user)
             .fetch_all(&self.db_pool)
             .await
         {
@@ -47,42 +59,88 @@ impl MealPlanRepository {
         }
     }
 
-    pub async fn delete_mealplan(&self, id: i64) -> Result<(), Error> {
-        let mut transaction = self.db_pool.begin().await?;
+    #[instrument(name = "MealPlanRepository::get_by_id", skip_all)]
+    pub async fn get_by_id(&self, id: i64) -> Result<Option<MealPlans>, Error> {
+        let _timer = QueryTimer::new("mealplan", "get_by_id");
+        let query = "SELECT IdMealPlan as id_mealplan, name, category, Users_idUser as id_owner, visibility, StartDate as start_date, LengthDays as length_days, Diets as diets FROM mealplans WHERE idMealPlan = ?";
+
+        match sqlx::query_as::<MySql, MealPlans>(query)
+            .bind(id)
+            .fetch_optional(&self.db_pool)
+            .await
+        {
+            Ok(meal_plan) => Ok(meal_plan),
+            Err(err) => {
+                log::error!("Error retrieving meal plan from the database: {}", err);
+                Err(err)
+            }
+        }
+    }
+
+    #[instrument(name = "MealPlanRepository::update_mealplan", skip_all)]
+    pub async fn update_mealplan(&self, transaction: &mut Transaction<'_, MySql>, meal_plan: &MealPlans) -> Result<(), Error> {
+        let _timer = QueryTimer::new("mealplan", "update_mealplan");
+        let query = "UPDATE MealPlans SET Name = ?, Category = ?, Visibility = ?, StartDate = ?, LengthDays = ?, Diets = ? WHERE idMealPlan = ?";
 
+        match sqlx::query(query)
+            .bind(&meal_plan.name)
+            .bind(&meal_plan.category)
+            .bind(&meal_plan.visibility)
+            .bind(meal_plan.start_date)
+            .bind(meal_plan.length_days)
+            .bind(&meal_plan.diets)
+            .bind(meal_plan.id_mealplan)
+            .execute(&mut **transaction)
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                log::error!("Error updating meal plan in the database: {}", err);
+                Err(err)
+            }
+        }
+    }
+
+    #[instrument(name = "MealPlanRepository::delete_mealplan", skip_all)]
+    pub async fn delete_mealplan(&self, transaction: &mut Transaction<'_, MySql>, id: i64) -> Result<(), Error> {
+        let _timer = QueryTimer::new("mealplan", "delete_mealplan");
         let query = "DELETE FROM MealPlans WHERE idMealPlan = ?";
     
         match sqlx::query(query)
             .bind(id)
-            .execute(&mut *transaction)
+            .execute(&mut **transaction)
             .await
         {
-            Ok(_) => {
-                transaction.commit().await?;
-                Ok(())
-            }
+            Ok(_) => Ok(()),
             Err(err) => {
-                transaction.rollback().await?;
                 log::error!("Error deleting meal plan in the database: {}", err);
                 Err(err)
             }
         }
     }
 
-    pub async fn does_name_exist(&self, name: &str) -> Result<bool, Error> {
-        let query = "SELECT COUNT(*) FROM mealplans WHERE name = ?";
+    /// Whether `id_owner` already has a meal plan named `name`; ownerless
+    /// plans are checked against each other.
+    #[instrument(name = "MealPlanRepository::does_name_exist", skip_all)]
+    pub async fn does_name_exist(&self, name: &str, id_owner: Option<i64>) -> Result<bool, Error> {
+        let _timer = QueryTimer::new("mealplan", "does_name_exist");
+        let query = "SELECT COUNT(*) FROM MealPlans WHERE Name = ? AND Users_idUser <=> ?";
         let count: i64 = sqlx::query_scalar(query)
             .bind(name)
+            .bind(id_owner)
             .fetch_one(&self.db_pool)
             .await?;
 
         Ok(count > 0)
     }
 
-    pub async fn does_name_exist_and_id(&self, id_meal_plan: &i32, name: &str) -> Result<bool, Error> {
-        let query = "SELECT COUNT(*) FROM MealPlans WHERE Name = ? AND idMealPlan != ?";
+    #[instrument(name = "MealPlanRepository::does_name_exist_and_id", skip_all)]
+    pub async fn does_name_exist_and_id(&self, id_meal_plan: i64, name: &str, id_owner: Option<i64>) -> Result<bool, Error> {
+        let _timer = QueryTimer::new("mealplan", "does_name_exist_and_id");
+        let query = "SELECT COUNT(*) FROM MealPlans WHERE Name = ? AND Users_idUser <=> ? AND idMealPlan != ?";
         let count: i64 = sqlx::query_scalar(query)
             .bind(name)
+            .bind(id_owner)
             .bind(id_meal_plan)
             .fetch_one(&self.db_pool)
             .await?;
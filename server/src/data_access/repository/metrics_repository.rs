@@ -0,0 +1,38 @@
+use sqlx::{MySqlPool, Error};
+use tracing::instrument;
+
+use crate::metrics::QueryTimer;
+
+pub struct MetricsRepository {
+    pub db_pool: MySqlPool,
+}
+
+impl MetricsRepository {
+    pub fn new(db_pool: MySqlPool) -> Self {
+        Self { db_pool }
+    }
+
+    /// Number of ingredients, recipes, meal plans and users.
+    #[instrument(name = "MetricsRepository::count_items", skip_all)]
+    pub async fn count_items(&self) -> Result<(i64, i64, i64, i64), Error> {
+        let _timer = QueryTimer::new("metrics", "count_items");
+        let query = r#"
+            SELECT
+                (SELECT COUNT(*) FROM Ingredients),
+                (SELECT COUNT(*) FROM Recipes),
+                (SELECT COUNT(*) FROM MealPlans),
+                (SELECT COUNT(*) FROM Users)
+        "#;
+
+        match sqlx::query_as::<_, (i64, i64, i64, i64)>(query)
+            .fetch_one(&self.db_pool)
+            .await
+        {
+            Ok(counts) => Ok(counts),
+            Err(err) => {
+                log::error!("Error counting items in the database: {}", err);
+                Err(err)
+            }
+        }
+    }
+}
@@ -1,5 +1,35 @@
+use sqlx::{Error, MySql, Transaction};
+
 pub mod ingredient_repository;
 pub mod recipe_repository;
 pub mod mealplan_repository;
 pub mod recipeingredients_repository;
-pub mod recipemeal_repository;
\ No newline at end of file
+pub mod recipemeal_repository;
+pub mod user_repository;
+pub mod api_key_repository;
+pub mod health_repository;
+pub mod metrics_repository;
+pub mod tag_repository;
+pub mod recipestep_repository;
+pub mod recipecomponent_repository;
+
+/// `?, ?, ?` for an `IN` list of `count` values. Callers skip the query for
+/// empty lists, which `IN ()` does not allow.
+pub fn placeholders(count: usize) -> String {
+    vec!["?"; count].join(", ")
+}
+
+/// Commits `transaction` if `result` is ok and rolls it back otherwise, so that
+/// writes spread over several repositories land together or not at all.
+pub async fn finish_transaction<T>(transaction: Transaction<'_, MySql>, result: Result<T, Error>) -> Result<T, Error> {
+    match result {
+        Ok(value) => {
+            transaction.commit().await?;
+            Ok(value)
+        }
+        Err(err) => {
+            transaction.rollback().await?;
+            Err(err)
+        }
+    }
+}
@@ -1,6 +1,9 @@
-use sqlx::{MySql, MySqlPool, Error};
+use sqlx::{MySql, MySqlPool, Error, Transaction};
+use tracing::instrument;
 
 use crate::data_access::entities::recipes::Recipe;
+use crate::data_access::repository::placeholders;
+use crate::metrics::QueryTimer;
 
 pub struct RecipeRepository {
     pub db_pool: MySqlPool,
@@ -11,11 +14,12 @@ impl RecipeRepository {
         Self { db_pool }
     }
 
-    pub async fn add_recipe(&self, recipe: &Recipe) -> Result<i64, Error> {
-        let mut transaction = self.db_pool.begin().await?;
+    #[instrument(name = "RecipeRepository::add_recipe", skip_all)]
+    pub async fn add_recipe(&self, transaction: &mut Transaction<'_, MySql>, recipe: &Recipe) -> Result<i64, Error> {
+        let _timer = QueryTimer::new("recipe", "add_recipe");
         let query = r#"
-            INSERT INTO Recipes (idRecipe, Name, Category, Instructions)
-            VALUES (?, ?, ?, ?)
+            INSERT INTO Recipes (idRecipe, Name, Category, Instructions, Servings, Users_idUser, Visibility)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
         "#;
     
         match sqlx::query(query)
@@ -23,26 +27,56 @@ impl RecipeRepository {
             .bind(&recipe.name)
             .bind(&recipe.category)
             .bind(&recipe.instructions)
-            .execute(&mut *transaction)
+            .bind(recipe.servings)
+            .bind(recipe.id_owner)
+            .bind(&recipe.visibility)
+            .execute(&mut **transaction)
             .await
         {
-            Ok(result) => {
-                let id: i64 = result.last_insert_id() as i64;
-                transaction.commit().await?;
-                Ok(id)
-            }
+            Ok(result) => Ok(result.last_insert_id() as i64),
             Err(err) => {
-                transaction.rollback().await?;
-                log::error!("Error adding ingredient to the database: {}", err);
+                log::error!("Error adding recipe to the database: {}", err);
                 Err(err)
             }
         }
     }
 
-    pub async fn get_all_recipes(&self) -> Result<Vec<Recipe>, Error> {
-        match sqlx::query_as::<MySql, Recipe>("SELECT IdRecipe as id_recipe, name, category, instructions FROM recipes")
-            .fetch_all(&self.db_pool)
-            .await
+    /// Recipes the user can see: public ones, and for signed-in users also the
+    /// household ones, the legacy ones without owner and their own. With `tags`,
+    /// only those carrying any of them, or all of them with `all_tags`.
+    #[instrument(name = "RecipeRepository::get_visible_recipes", skip_all)]
+    pub async fn get_visible_recipes(&self, id_user: Option<i64>, tags: &[String], all_tags: bool) -> Result<Vec<Recipe>, Error> {
+        let _timer = QueryTimer::new("recipe", "get_visible_recipes");
+        let mut query = "SELECT IdRecipe as id_recipe, name, category, instructions, servings, Users_idUser as id_owner, visibility FROM recipes WHERE (Visibility = 'public' OR (? IS NOT NULL AND (Visibility = 'household' OR Users_idUser IS NULL OR Users_idUser = ?)))".to_string();
+
+        if !tags.is_empty() {
+            query.push_str(&format!(r#"
+                AND idRecipe IN (
+                    SELECT rt.Recipes_idRecipe
+                    FROM RecipeTags rt
+                    JOIN Tags t ON t.idTag = rt.Tags_idTag
+                    WHERE t.Name IN ({})
+                    GROUP BY rt.Recipes_idRecipe
+                    HAVING COUNT(*) >= ?
+                )
+            "#, placeholders(tags.len())));
+        }
+
+        query.push_str(" ORDER BY idRecipe");
+
+        let mut select = sqlx::query_as::<MySql, Recipe>(&query)
+            .bind(id_user)
+            .bind(id_user);
+
+        if !tags.is_empty() {
+            for tag in tags {
+                select = select.bind(tag);
+            }
+
+            select = select.bind(if all_tags { tags.len() as i64 } else { 1 });
+        }
+
+        match select.fetch_all(&self.db_pool).await
         {
             Ok(recipes) => Ok(recipes),
             Err(err) => {
@@ -52,23 +86,70 @@ impl RecipeRepository {
         }
     }
 
-    pub async fn is_recipe_in_meal_plan(&self, recipe_id: i64,) -> Result<bool, Error> {
-        let query = "SELECT COUNT(*) FROM recipeMeals WHERE Recipes_idRecipe = ?";
+    #[instrument(name = "RecipeRepository::get_by_id", skip_all)]
+    pub async fn get_by_id(&self, id: i64) -> Result<Option<Recipe>, Error> {
+        let _timer = QueryTimer::new("recipe", "get_by_id");
+        let query = "SELECT IdRecipe as id_recipe, name, category, instructions, servings, Users_idUser as id_owner, visibility FROM recipes WHERE idRecipe = ?";
+
+        match sqlx::query_as::<MySql, Recipe>(query)
+            .bind(id)
+            .fetch_optional(&self.db_pool)
+            .await
+        {
+            Ok(recipe) => Ok(recipe),
+            Err(err) => {
+                log::error!("Error retrieving recipe from the database: {}", err);
+                Err(err)
+            }
+        }
+    }
+
+    #[instrument(name = "RecipeRepository::update_recipe", skip_all)]
+    pub async fn update_recipe(&self, transaction: &mut Transaction<'_, MySql>, recipe: &Recipe) -> Result<(), Error> {
+        let _timer = QueryTimer::new("recipe", "update_recipe");
+        let query = "UPDATE Recipes SET Name = ?, Category = ?, Instructions = ?, Servings = ?, Visibility = ? WHERE idRecipe = ?";
+
+        match sqlx::query(query)
+            .bind(&recipe.name)
+            .bind(&recipe.category)
+            .bind(&recipe.instructions)
+            .bind(recipe.servings)
+            .bind(&recipe.visibility)
+            .bind(recipe.id_recipe)
+            .execute(&mut **transaction)
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                log::error!("Error updating recipe in the database: {}", err);
+                Err(err)
+            }
+        }
+    }
+
+    /// Locks the slots using the recipe, and the gap for new ones, until
+    /// `transaction` ends.
+    #[instrument(name = "RecipeRepository::is_recipe_in_meal_plan", skip_all)]
+    pub async fn is_recipe_in_meal_plan(&self, transaction: &mut Transaction<'_, MySql>, recipe_id: i64) -> Result<bool, Error> {
+        let _timer = QueryTimer::new("recipe", "is_recipe_in_meal_plan");
+        let query = "SELECT COUNT(*) FROM RecipeMeals WHERE Recipes_idRecipe = ? FOR UPDATE";
     
         let count: i64 = sqlx::query_scalar(query)
             .bind(recipe_id)
-            .fetch_one(&self.db_pool)
+            .fetch_one(&mut **transaction)
             .await?;
     
         Ok(count > 0)
     }
 
-    pub async fn delete_recipe(&self, recipe_id: i64) -> Result<(), Error> {
+    #[instrument(name = "RecipeRepository::delete_recipe", skip_all)]
+    pub async fn delete_recipe(&self, transaction: &mut Transaction<'_, MySql>, recipe_id: i64) -> Result<(), Error> {
+        let _timer = QueryTimer::new("recipe", "delete_recipe");
         let query = "DELETE FROM Recipes WHERE idRecipe = ?";
     
         match sqlx::query(query)
             .bind(recipe_id)
-            .execute(&self.db_pool)
+            .execute(&mut **transaction)
             .await
         {
             Ok(_) => Ok(()),
@@ -79,20 +160,28 @@ impl RecipeRepository {
         }
     }
 
-    pub async fn does_name_exist(&self, name: &str) -> Result<bool, Error> {
-        let query = "SELECT COUNT(*) FROM Recipes WHERE Name = ?";
+    /// Whether `id_owner` already has a recipe named `name`; ownerless recipes
+    /// are checked against each other.
+    #[instrument(name = "RecipeRepository::does_name_exist", skip_all)]
+    pub async fn does_name_exist(&self, name: &str, id_owner: Option<i64>) -> Result<bool, Error> {
+        let _timer = QueryTimer::new("recipe", "does_name_exist");
+        let query = "SELECT COUNT(*) FROM Recipes WHERE Name = ? AND Users_idUser <=> ?";
         let count: i64 = sqlx::query_scalar(query)
             .bind(name)
+            .bind(id_owner)
             .fetch_one(&self.db_pool)
             .await?;
 
         Ok(count > 0)
     }
 
-    pub async fn does_name_exist_and_id(&self, id_recipe: &i32, name: &str) -> Result<bool, Error> {
-        let query = "SELECT COUNT(*) FROM Recipes WHERE Name = ? AND idRecipe != ?";
+    #[instrument(name = "RecipeRepository::does_name_exist_and_id", skip_all)]
+    pub async fn does_name_exist_and_id(&self, id_recipe: i64, name: &str, id_owner: Option<i64>) -> Result<bool, Error> {
+        let _timer = QueryTimer::new("recipe", "does_name_exist_and_id");
+        let query = "SELECT COUNT(*) FROM Recipes WHERE Name = ? AND Users_idUser <=> ? AND idRecipe != ?";
         let count: i64 = sqlx::query_scalar(query)
             .bind(name)
+            .bind(id_owner)
             .bind(id_recipe)
             .fetch_one(&self.db_pool)
             .await?;
@@ -100,7 +189,9 @@ impl RecipeRepository {
         Ok(count > 0)
     }
 
+    #[instrument(name = "RecipeRepository::recipe_exist", skip_all)]
     pub async fn recipe_exist(&self, id: i64) -> Result<bool, Error> {
+        let _timer = QueryTimer::new("recipe", "recipe_exist");
         let query = "SELECT COUNT(*) FROM recipes WHERE idRecipe = ?";
         let count: i64 = sqlx::query_scalar(query)
             .bind(id)
@@ -0,0 +1,118 @@
+use sqlx::{MySql, MySqlPool, Error, Transaction};
+use tracing::instrument;
+
+use crate::data_access::entities::recipe_components::RecipeComponent;
+use crate::data_access::repository::placeholders;
+use crate::metrics::QueryTimer;
+
+pub struct RecipeComponentRepository {
+    pub db_pool: MySqlPool,
+}
+
+impl RecipeComponentRepository {
+    pub fn new(db_pool: MySqlPool) -> Self {
+        Self { db_pool }
+    }
+
+    #[instrument(name = "RecipeComponentRepository::add_recipe_components", skip_all)]
+    pub async fn add_recipe_components(&self, transaction: &mut Transaction<'_, MySql>, components: &[RecipeComponent]) -> Result<(), Error> {
+        let _timer = QueryTimer::new("recipecomponent", "add_recipe_components");
+        for component in components {
+            match sqlx::query(r#"
+                INSERT INTO RecipeComponents (Servings, Recipes_idRecipe, Components_idRecipe)
+                VALUES (?, ?, ?)
+            "#)
+            .bind(component.servings)
+            .bind(component.recipes_id)
+            .bind(component.components_id)
+            .execute(&mut **transaction)
+            .await
+            {
+                Ok(_) => {}
+                Err(err) => {
+                    log::error!("Error adding recipe component to the database: {}", err);
+                    return Err(err);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The components of each of `id_recipes`.
+    #[instrument(name = "RecipeComponentRepository::get_recipe_components", skip_all)]
+    pub async fn get_recipe_components(&self, id_recipes: &[i64]) -> Result<Vec<RecipeComponent>, Error> {
+        let _timer = QueryTimer::new("recipecomponent", "get_recipe_components");
+
+        if id_recipes.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let query = format!("SELECT idRecipeComponent as id_recipe_component, servings, Recipes_idRecipe as recipes_id, Components_idRecipe as components_id FROM RecipeComponents WHERE Recipes_idRecipe IN ({}) ORDER BY idRecipeComponent", placeholders(id_recipes.len()));
+        let mut select = sqlx::query_as::<MySql, RecipeComponent>(&query);
+
+        for id_recipe in id_recipes {
+            select = select.bind(id_recipe);
+        }
+
+        match select.fetch_all(&self.db_pool).await
+        {
+            Ok(components) => Ok(components),
+            Err(err) => {
+                log::error!("Error retrieving recipe components from the database: {}", err);
+                Err(err)
+            }
+        }
+    }
+
+    /// Every component link, locked until `transaction` ends so that no other
+    /// write can add links while the caller checks them for cycles.
+    #[instrument(name = "RecipeComponentRepository::get_all_recipe_components", skip_all)]
+    pub async fn get_all_recipe_components(&self, transaction: &mut Transaction<'_, MySql>) -> Result<Vec<RecipeComponent>, Error> {
+        let _timer = QueryTimer::new("recipecomponent", "get_all_recipe_components");
+        let query = "SELECT idRecipeComponent as id_recipe_component, servings, Recipes_idRecipe as recipes_id, Components_idRecipe as components_id FROM RecipeComponents FOR UPDATE";
+
+        match sqlx::query_as::<MySql, RecipeComponent>(query)
+            .fetch_all(&mut **transaction)
+            .await
+        {
+            Ok(components) => Ok(components),
+            Err(err) => {
+                log::error!("Error retrieving recipe components from the database: {}", err);
+                Err(err)
+            }
+        }
+    }
+
+    #[instrument(name = "RecipeComponentRepository::delete_recipe_components", skip_all)]
+    pub async fn delete_recipe_components(&self, transaction: &mut Transaction<'_, MySql>, id_recipe: i64) -> Result<(), Error> {
+        let _timer = QueryTimer::new("recipecomponent", "delete_recipe_components");
+        let query = "DELETE FROM RecipeComponents WHERE Recipes_idRecipe = ?";
+
+        match sqlx::query(query)
+            .bind(id_recipe)
+            .execute(&mut **transaction)
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                log::error!("Error deleting recipe components in the database: {}", err);
+                Err(err)
+            }
+        }
+    }
+
+    /// Locks the links to the recipe, and the gap for new ones, until
+    /// `transaction` ends.
+    #[instrument(name = "RecipeComponentRepository::is_recipe_a_component", skip_all)]
+    pub async fn is_recipe_a_component(&self, transaction: &mut Transaction<'_, MySql>, id_recipe: i64) -> Result<bool, Error> {
+        let _timer = QueryTimer::new("recipecomponent", "is_recipe_a_component");
+        let query = "SELECT COUNT(*) FROM RecipeComponents WHERE Components_idRecipe = ? FOR UPDATE";
+        let count: i64 = sqlx::query_scalar(query)
+            .bind(id_recipe)
+            .fetch_one(&mut **transaction)
+            .await?;
+
+        Ok(count > 0)
+    }
+}
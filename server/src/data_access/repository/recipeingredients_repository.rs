@@ -1,6 +1,42 @@
-use sqlx::{MySqlPool, MySql, Error};
+use sqlx::{MySqlPool, MySql, Error, Transaction};
+use tracing::instrument;
 
 use crate::data_access::entities::recipe_ingredients::RecipeIngredients;
+use crate::data_access::repository::placeholders;
+use crate::metrics::QueryTimer;
+
+/// How deep components of components are followed.
+pub const MAX_COMPONENT_DEPTH: i32 = 10;
+
+/// A recipe and, recursively, its component recipes, with the factor their
+/// amounts are scaled by. Binds the recipe id and `MAX_COMPONENT_DEPTH`.
+const EXPANDED_RECIPES: &str = r#"
+    WITH RECURSIVE Expanded (idRecipe, Factor, Depth) AS (
+        SELECT ?, CAST(1 AS DOUBLE), 0
+        UNION ALL
+        SELECT rc.Components_idRecipe, e.Factor * rc.Servings / GREATEST(c.Servings, 1), e.Depth + 1
+        FROM Expanded e
+        JOIN RecipeComponents rc ON rc.Recipes_idRecipe = e.idRecipe
+        JOIN Recipes c ON c.idRecipe = rc.Components_idRecipe
+        WHERE e.Depth < ?
+    )
+"#;
+
+/// Like `EXPANDED_RECIPES` for `count` recipes at once, keeping in `Root` the
+/// recipe each row counts for. Binds the recipe ids and `MAX_COMPONENT_DEPTH`.
+fn expanded_recipe_list(count: usize) -> String {
+    format!(r#"
+        WITH RECURSIVE Expanded (Root, idRecipe, Factor, Depth) AS (
+            SELECT idRecipe, idRecipe, CAST(1 AS DOUBLE), 0 FROM Recipes WHERE idRecipe IN ({})
+            UNION ALL
+            SELECT e.Root, rc.Components_idRecipe, e.Factor * rc.Servings / GREATEST(c.Servings, 1), e.Depth + 1
+            FROM Expanded e
+            JOIN RecipeComponents rc ON rc.Recipes_idRecipe = e.idRecipe
+            JOIN Recipes c ON c.idRecipe = rc.Components_idRecipe
+            WHERE e.Depth < ?
+        )
+    "#, placeholders(count))
+}
 
 pub struct RecipeIngredientsRepository {
     pub db_pool: MySqlPool,
@@ -11,9 +47,9 @@ impl RecipeIngredientsRepository {
         Self { db_pool }
     }
 
-    pub async fn add_ingredient_recipe(&self, recipe_ingredients: &[RecipeIngredients]) -> Result<(), sqlx::Error> {
-        let mut transaction = self.db_pool.begin().await?;
-
+    #[instrument(name = "RecipeIngredientsRepository::add_ingredient_recipe", skip_all)]
+    pub async fn add_ingredient_recipe(&self, transaction: &mut Transaction<'_, MySql>, recipe_ingredients: &[RecipeIngredients]) -> Result<(), sqlx::Error> {
+        let _timer = QueryTimer::new("recipeingredients", "add_ingredient_recipe");
         let query_ingredients = r#"
             INSERT INTO recipeIngredients (idRecipeIngredient, Amount, Unit, Ingredients_idIngredient, Recipes_idRecipe)
             VALUES (?, ?, ?, ?, ?)
@@ -26,33 +62,149 @@ impl RecipeIngredientsRepository {
                 .bind(recipe_ingredient.unit.clone())
                 .bind(recipe_ingredient.ingredients_id_ingredient)
                 .bind(recipe_ingredient.recipes_id_recipe)
-                .execute(&mut *transaction)
+                .execute(&mut **transaction)
                 .await
             {
                 Ok(_) => {}
                 Err(err) => {
-                    transaction.rollback().await?;
                     log::error!("Error adding recipe ingredient to the database: {}", err);
                     return Err(err);
                 }
             }
         }
         
-        transaction.commit().await?;
         Ok(())
     }
 
-    pub async fn get_all_recipe_ingredients(&self, id_recipe: i64) -> Result<Vec<RecipeIngredients>, Error> {
-        let query = "SELECT idRecipeIngredient as id_recipe_ingredients, amount, unit, Ingredients_idIngredient as ingredients_id_ingredient, Recipes_idRecipe as recipes_id_recipe FROM recipeingredients WHERE Recipes_idRecipe = ?";
-        
-        match sqlx::query_as::<MySql, RecipeIngredients>(query)
+    /// The ingredients of each of `id_recipes`.
+    #[instrument(name = "RecipeIngredientsRepository::get_all_recipe_ingredients", skip_all)]
+    pub async fn get_all_recipe_ingredients(&self, id_recipes: &[i64]) -> Result<Vec<RecipeIngredients>, Error> {
+        let _timer = QueryTimer::new("recipeingredients", "get_all_recipe_ingredients");
+
+        if id_recipes.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let query = format!("SELECT idRecipeIngredient as id_recipe_ingredients, amount, unit, Ingredients_idIngredient as ingredients_id_ingredient, Recipes_idRecipe as recipes_id_recipe FROM recipeingredients WHERE Recipes_idRecipe IN ({}) ORDER BY idRecipeIngredient", placeholders(id_recipes.len()));
+        let mut select = sqlx::query_as::<MySql, RecipeIngredients>(&query);
+
+        for id_recipe in id_recipes {
+            select = select.bind(id_recipe);
+        }
+
+        match select.fetch_all(&self.db_pool).await
+        {
+            Ok(recipe_ingredients) => Ok(recipe_ingredients),
+            Err(err) => {
+                log::error!("Error retrieving recipe ingredients from the database: {}", err);
+                Err(err)
+            }
+        }
+    }
+
+    /// The ingredients of a recipe and, recursively, of its component recipes,
+    /// with the amounts of the components scaled to the servings used.
+    #[instrument(name = "RecipeIngredientsRepository::get_expanded_recipe_ingredients", skip_all)]
+    pub async fn get_expanded_recipe_ingredients(&self, id_recipe: i64) -> Result<Vec<RecipeIngredients>, Error> {
+        let _timer = QueryTimer::new("recipeingredients", "get_expanded_recipe_ingredients");
+        let query = format!(r#"
+            {}
+            SELECT ri.idRecipeIngredient as id_recipe_ingredients, ri.Amount * e.Factor as amount, ri.Unit as unit,
+                   ri.Ingredients_idIngredient as ingredients_id_ingredient, ri.Recipes_idRecipe as recipes_id_recipe
+            FROM Expanded e
+            JOIN RecipeIngredients ri ON ri.Recipes_idRecipe = e.idRecipe
+        "#, EXPANDED_RECIPES);
+
+        match sqlx::query_as::<MySql, RecipeIngredients>(&query)
             .bind(id_recipe)
+            .bind(MAX_COMPONENT_DEPTH)
             .fetch_all(&self.db_pool)
             .await
         {
             Ok(recipe_ingredients) => Ok(recipe_ingredients),
             Err(err) => {
-                log::error!("Error retrieving recipe ingredients from the database: {}", err);
+                log::error!("Error retrieving expanded recipe ingredients from the database: {}", err);
+                Err(err)
+            }
+        }
+    }
+
+    /// `get_expanded_recipe_ingredients` for each of `id_recipes` at once, with
+    /// `recipes_id_recipe` set to the recipe of `id_recipes` each row counts for.
+    #[instrument(name = "RecipeIngredientsRepository::get_expanded_recipes_ingredients", skip_all)]
+    pub async fn get_expanded_recipes_ingredients(&self, id_recipes: &[i64]) -> Result<Vec<RecipeIngredients>, Error> {
+        let _timer = QueryTimer::new("recipeingredients", "get_expanded_recipes_ingredients");
+
+        if id_recipes.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let query = format!(r#"
+            {}
+            SELECT ri.idRecipeIngredient as id_recipe_ingredients, ri.Amount * e.Factor as amount, ri.Unit as unit,
+                   ri.Ingredients_idIngredient as ingredients_id_ingredient, e.Root as recipes_id_recipe
+            FROM Expanded e
+            JOIN RecipeIngredients ri ON ri.Recipes_idRecipe = e.idRecipe
+        "#, expanded_recipe_list(id_recipes.len()));
+        let mut select = sqlx::query_as::<MySql, RecipeIngredients>(&query);
+
+        for id_recipe in id_recipes {
+            select = select.bind(id_recipe);
+        }
+
+        match select
+            .bind(MAX_COMPONENT_DEPTH)
+            .fetch_all(&self.db_pool)
+            .await
+        {
+            Ok(recipe_ingredients) => Ok(recipe_ingredients),
+            Err(err) => {
+                log::error!("Error retrieving expanded recipe ingredients from the database: {}", err);
+                Err(err)
+            }
+        }
+    }
+
+    /// The allergen lists of the ingredients of a recipe and of its component
+    /// recipes, one per distinct list; `None` for ingredients never labelled.
+    #[instrument(name = "RecipeIngredientsRepository::get_recipe_allergens", skip_all)]
+    pub async fn get_recipe_allergens(&self, id_recipe: i64) -> Result<Vec<Option<String>>, Error> {
+        let _timer = QueryTimer::new("recipeingredients", "get_recipe_allergens");
+        let query = format!(r#"
+            {}
+            SELECT DISTINCT i.Allergens
+            FROM Expanded e
+            JOIN RecipeIngredients ri ON ri.Recipes_idRecipe = e.idRecipe
+            JOIN Ingredients i ON i.idIngredient = ri.Ingredients_idIngredient
+        "#, EXPANDED_RECIPES);
+
+        match sqlx::query_scalar::<MySql, Option<String>>(&query)
+            .bind(id_recipe)
+            .bind(MAX_COMPONENT_DEPTH)
+            .fetch_all(&self.db_pool)
+            .await
+        {
+            Ok(allergens) => Ok(allergens),
+            Err(err) => {
+                log::error!("Error retrieving recipe allergens from the database: {}", err);
+                Err(err)
+            }
+        }
+    }
+
+    #[instrument(name = "RecipeIngredientsRepository::delete_recipe_ingredients", skip_all)]
+    pub async fn delete_recipe_ingredients(&self, transaction: &mut Transaction<'_, MySql>, id_recipe: i64) -> Result<(), Error> {
+        let _timer = QueryTimer::new("recipeingredients", "delete_recipe_ingredients");
+        let query = "DELETE FROM RecipeIngredients WHERE Recipes_idRecipe = ?";
+
+        match sqlx::query(query)
+            .bind(id_recipe)
+            .execute(&mut **transaction)
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                log::error!("Error deleting recipe ingredients in the database: {}", err);
                 Err(err)
             }
         }
@@ -1,6 +1,9 @@
-use sqlx::{MySql, MySqlPool, Error};
+use chrono::NaiveDate;
+use sqlx::{MySql, MySqlPool, Error, Transaction};
+use tracing::instrument;
 
-use crate::data_access::entities::recipe_meals::RecipeMeals;
+use crate::data_access::entities::recipe_meals::{PlannedMeal, RecipeMeals};
+use crate::metrics::QueryTimer;
 
 pub struct MealRecipeRepository {
     pub db_pool: MySqlPool,
@@ -11,36 +14,38 @@ impl MealRecipeRepository {
         Self { db_pool }
     }
 
-    pub async fn add_recipe_meal(&self, recipe_meals: &[RecipeMeals]) -> Result<(), sqlx::Error> {
-        let mut transaction = self.db_pool.begin().await?;
-        
+    #[instrument(name = "MealRecipeRepository::add_recipe_meal", skip_all)]
+    pub async fn add_recipe_meal(&self, transaction: &mut Transaction<'_, MySql>, recipe_meals: &[RecipeMeals]) -> Result<(), sqlx::Error> {
+        let _timer = QueryTimer::new("recipemeal", "add_recipe_meal");
         for meal in recipe_meals {
             match sqlx::query(r#"
-                INSERT INTO RecipeMeals (Day, MealType, Recipes_idRecipe, MealPlans_idMealPlan)
-                VALUES (?, ?, ?, ?)
+                INSERT INTO RecipeMeals (Day, MealType, Date, Servings, Recipes_idRecipe, MealPlans_idMealPlan)
+                VALUES (?, ?, ?, ?, ?, ?)
             "#)
             .bind(&meal.day)
             .bind(&meal.meal_type)
-            .bind(&meal.recipes_id)
-            .bind(&meal.mealplan_id)
-            .execute(&mut *transaction)
+            .bind(meal.date)
+            .bind(meal.servings)
+            .bind(meal.recipes_id)
+            .bind(meal.mealplan_id)
+            .execute(&mut **transaction)
             .await
             {
                 Ok(_) => {}
                 Err(err) => {
-                    transaction.rollback().await?;
                     log::error!("Error adding recipe meal to the database: {}", err);
                     return Err(err);
                 }
             }
         }
     
-        transaction.commit().await?;
         Ok(())
     }
 
+    #[instrument(name = "MealRecipeRepository::get_all_recipe_mealplan", skip_all)]
     pub async fn get_all_recipe_mealplan(&self, id_mealplan: i64) -> Result<Vec<RecipeMeals>, Error> {
-        let query = "SELECT idRecipeMeals as id_recipe_meal, day, mealtype as meal_type, Recipes_idRecipe as recipes_id, MealPlans_idMealPlan as mealplan_id FROM recipemeals WHERE MealPlans_idMealPlan = ?";
+        let _timer = QueryTimer::new("recipemeal", "get_all_recipe_mealplan");
+        let query = "SELECT idRecipeMeals as id_recipe_meal, day, mealtype as meal_type, Date as date, Servings as servings, Recipes_idRecipe as recipes_id, MealPlans_idMealPlan as mealplan_id FROM recipemeals WHERE MealPlans_idMealPlan = ? ORDER BY Date, idRecipeMeals";
         
         match sqlx::query_as::<MySql, RecipeMeals>(query)
             .bind(id_mealplan)
@@ -55,22 +60,51 @@ impl MealRecipeRepository {
         }
     }
 
-    pub async fn delete_recipemeal(&self, id: i64) -> Result<(), Error> {
-        let mut transaction = self.db_pool.begin().await?;
+    /// Slots on `date` of the user's own dated meal plans running that day.
+    #[instrument(name = "MealRecipeRepository::get_planned_meals_on", skip_all)]
+    pub async fn get_planned_meals_on(&self, date: NaiveDate, id_user: i64) -> Result<Vec<PlannedMeal>, Error> {
+        let _timer = QueryTimer::new("recipemeal", "get_planned_meals_on");
+        let query = r#"
+            SELECT rm.idRecipeMeals as id_recipe_meal, rm.MealType as meal_type,
+                   mp.idMealPlan as mealplan_id, mp.Name as mealplan_name,
+                   r.idRecipe as recipes_id, r.Name as recipe_name
+            FROM RecipeMeals rm
+            JOIN MealPlans mp ON mp.idMealPlan = rm.MealPlans_idMealPlan
+            JOIN Recipes r ON r.idRecipe = rm.Recipes_idRecipe
+            WHERE rm.Date = ?
+              AND mp.StartDate <= ? AND ? < DATE_ADD(mp.StartDate, INTERVAL mp.LengthDays DAY)
+              AND mp.Users_idUser = ?
+            ORDER BY mp.idMealPlan, rm.idRecipeMeals
+        "#;
 
+        match sqlx::query_as::<MySql, PlannedMeal>(query)
+            .bind(date)
+            .bind(date)
+            .bind(date)
+            .bind(id_user)
+            .fetch_all(&self.db_pool)
+            .await
+        {
+            Ok(planned_meals) => Ok(planned_meals),
+            Err(err) => {
+                log::error!("Error retrieving planned meals from the database: {}", err);
+                Err(err)
+            }
+        }
+    }
+
+    #[instrument(name = "MealRecipeRepository::delete_recipemeal", skip_all)]
+    pub async fn delete_recipemeal(&self, transaction: &mut Transaction<'_, MySql>, id: i64) -> Result<(), Error> {
+        let _timer = QueryTimer::new("recipemeal", "delete_recipemeal");
         let query = "DELETE FROM RecipeMeals WHERE MealPlans_idMealPlan = ?";
     
         match sqlx::query(query)
             .bind(id)
-            .execute(&mut *transaction)
+            .execute(&mut **transaction)
             .await
         {
-            Ok(_) => {
-                transaction.commit().await?;
-                Ok(())
-            }
+            Ok(_) => Ok(()),
             Err(err) => {
-                transaction.rollback().await?;
                 log::error!("Error deleting recipe meal in the database: {}", err);
                 Err(err)
             }
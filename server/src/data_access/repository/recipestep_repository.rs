@@ -0,0 +1,131 @@
+use sqlx::{MySql, MySqlPool, Error, Transaction};
+use tracing::instrument;
+
+use crate::data_access::entities::recipe_steps::{RecipeStep, RecipeStepIngredient};
+use crate::data_access::repository::placeholders;
+use crate::metrics::QueryTimer;
+
+pub struct RecipeStepRepository {
+    pub db_pool: MySqlPool,
+}
+
+impl RecipeStepRepository {
+    pub fn new(db_pool: MySqlPool) -> Self {
+        Self { db_pool }
+    }
+
+    /// Adds the steps of a recipe, each with the ids of the ingredients it uses.
+    #[instrument(name = "RecipeStepRepository::add_recipe_steps", skip_all)]
+    pub async fn add_recipe_steps(&self, transaction: &mut Transaction<'_, MySql>, steps: &[(RecipeStep, Vec<i64>)]) -> Result<(), Error> {
+        let _timer = QueryTimer::new("recipestep", "add_recipe_steps");
+        for (step, ingredients) in steps {
+            let result = sqlx::query(r#"
+                INSERT INTO RecipeSteps (Position, Text, DurationMinutes, Recipes_idRecipe)
+                VALUES (?, ?, ?, ?)
+            "#)
+            .bind(step.position)
+            .bind(&step.text)
+            .bind(step.duration_minutes)
+            .bind(step.recipes_id)
+            .execute(&mut **transaction)
+            .await;
+
+            let id_recipe_step = match result {
+                Ok(result) => result.last_insert_id() as i64,
+                Err(err) => {
+                    log::error!("Error adding recipe step to the database: {}", err);
+                    return Err(err);
+                }
+            };
+
+            for id_ingredient in ingredients {
+                if let Err(err) = sqlx::query("INSERT INTO RecipeStepIngredients (RecipeSteps_idRecipeStep, Ingredients_idIngredient) VALUES (?, ?)")
+                    .bind(id_recipe_step)
+                    .bind(id_ingredient)
+                    .execute(&mut **transaction)
+                    .await
+                {
+                    log::error!("Error adding recipe step ingredient to the database: {}", err);
+                    return Err(err);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The steps of each of `id_recipes`, in order.
+    #[instrument(name = "RecipeStepRepository::get_recipe_steps", skip_all)]
+    pub async fn get_recipe_steps(&self, id_recipes: &[i64]) -> Result<Vec<RecipeStep>, Error> {
+        let _timer = QueryTimer::new("recipestep", "get_recipe_steps");
+
+        if id_recipes.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let query = format!("SELECT idRecipeStep as id_recipe_step, position, text, DurationMinutes as duration_minutes, Recipes_idRecipe as recipes_id FROM RecipeSteps WHERE Recipes_idRecipe IN ({}) ORDER BY Position", placeholders(id_recipes.len()));
+        let mut select = sqlx::query_as::<MySql, RecipeStep>(&query);
+
+        for id_recipe in id_recipes {
+            select = select.bind(id_recipe);
+        }
+
+        match select.fetch_all(&self.db_pool).await
+        {
+            Ok(steps) => Ok(steps),
+            Err(err) => {
+                log::error!("Error retrieving recipe steps from the database: {}", err);
+                Err(err)
+            }
+        }
+    }
+
+    /// The ingredients used in each step of each of `id_recipes`.
+    #[instrument(name = "RecipeStepRepository::get_recipe_step_ingredients", skip_all)]
+    pub async fn get_recipe_step_ingredients(&self, id_recipes: &[i64]) -> Result<Vec<RecipeStepIngredient>, Error> {
+        let _timer = QueryTimer::new("recipestep", "get_recipe_step_ingredients");
+
+        if id_recipes.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let query = format!(r#"
+            SELECT rsi.RecipeSteps_idRecipeStep as steps_id, rsi.Ingredients_idIngredient as ingredients_id
+            FROM RecipeStepIngredients rsi
+            JOIN RecipeSteps rs ON rs.idRecipeStep = rsi.RecipeSteps_idRecipeStep
+            WHERE rs.Recipes_idRecipe IN ({})
+        "#, placeholders(id_recipes.len()));
+        let mut select = sqlx::query_as::<MySql, RecipeStepIngredient>(&query);
+
+        for id_recipe in id_recipes {
+            select = select.bind(id_recipe);
+        }
+
+        match select.fetch_all(&self.db_pool).await
+        {
+            Ok(step_ingredients) => Ok(step_ingredients),
+            Err(err) => {
+                log::error!("Error retrieving recipe step ingredients from the database: {}", err);
+                Err(err)
+            }
+        }
+    }
+
+    #[instrument(name = "RecipeStepRepository::delete_recipe_steps", skip_all)]
+    pub async fn delete_recipe_steps(&self, transaction: &mut Transaction<'_, MySql>, id_recipe: i64) -> Result<(), Error> {
+        let _timer = QueryTimer::new("recipestep", "delete_recipe_steps");
+        let queries = [
+            "DELETE rsi FROM RecipeStepIngredients rsi JOIN RecipeSteps rs ON rs.idRecipeStep = rsi.RecipeSteps_idRecipeStep WHERE rs.Recipes_idRecipe = ?",
+            "DELETE FROM RecipeSteps WHERE Recipes_idRecipe = ?"
+        ];
+
+        for query in queries {
+            if let Err(err) = sqlx::query(query).bind(id_recipe).execute(&mut **transaction).await {
+                log::error!("Error deleting recipe steps in the database: {}", err);
+                return Err(err);
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,230 @@
+use sqlx::{MySql, MySqlPool, Error, Transaction};
+use tracing::instrument;
+
+use crate::data_access::entities::tags::{RecipeTag, Tag};
+use crate::data_access::repository::placeholders;
+use crate::metrics::QueryTimer;
+
+pub struct TagRepository {
+    pub db_pool: MySqlPool,
+}
+
+impl TagRepository {
+    pub fn new(db_pool: MySqlPool) -> Self {
+        Self { db_pool }
+    }
+
+    #[instrument(name = "TagRepository::add_tag", skip_all)]
+    pub async fn add_tag(&self, tag: &Tag) -> Result<i64, Error> {
+        let _timer = QueryTimer::new("tag", "add_tag");
+        let query = "INSERT INTO Tags (Name) VALUES (?)";
+
+        match sqlx::query(query)
+            .bind(&tag.name)
+            .execute(&self.db_pool)
+            .await
+        {
+            Ok(result) => Ok(result.last_insert_id() as i64),
+            Err(err) => {
+                log::error!("Error adding tag to the database: {}", err);
+                Err(err)
+            }
+        }
+    }
+
+    #[instrument(name = "TagRepository::get_all_tags", skip_all)]
+    pub async fn get_all_tags(&self) -> Result<Vec<Tag>, Error> {
+        let _timer = QueryTimer::new("tag", "get_all_tags");
+        let query = "SELECT idTag as id_tag, name FROM Tags ORDER BY Name";
+
+        match sqlx::query_as::<MySql, Tag>(query)
+            .fetch_all(&self.db_pool)
+            .await
+        {
+            Ok(tags) => Ok(tags),
+            Err(err) => {
+                log::error!("Error retrieving tags from the database: {}", err);
+                Err(err)
+            }
+        }
+    }
+
+    /// Renames a tag, along with the category of the recipes it is the
+    /// category of, so that the category stays one of their tags.
+    #[instrument(name = "TagRepository::update_tag", skip_all)]
+    pub async fn update_tag(&self, tag: &Tag) -> Result<(), Error> {
+        let _timer = QueryTimer::new("tag", "update_tag");
+        let mut transaction = self.db_pool.begin().await?;
+
+        let queries = [
+            "UPDATE Recipes r JOIN Tags t ON t.Name = LOWER(TRIM(r.Category)) SET r.Category = ? WHERE t.idTag = ?",
+            "UPDATE Tags SET Name = ? WHERE idTag = ?"
+        ];
+
+        for query in queries {
+            if let Err(err) = sqlx::query(query).bind(&tag.name).bind(tag.id_tag).execute(&mut *transaction).await {
+                transaction.rollback().await?;
+                log::error!("Error updating tag in the database: {}", err);
+                return Err(err);
+            }
+        }
+
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    /// Deletes a tag, taking it off the recipes that carry it.
+    #[instrument(name = "TagRepository::delete_tag", skip_all)]
+    pub async fn delete_tag(&self, id: i64) -> Result<(), Error> {
+        let _timer = QueryTimer::new("tag", "delete_tag");
+        let mut transaction = self.db_pool.begin().await?;
+
+        for query in ["DELETE FROM RecipeTags WHERE Tags_idTag = ?", "DELETE FROM Tags WHERE idTag = ?"] {
+            if let Err(err) = sqlx::query(query).bind(id).execute(&mut *transaction).await {
+                transaction.rollback().await?;
+                log::error!("Error deleting tag in the database: {}", err);
+                return Err(err);
+            }
+        }
+
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    /// The tags of each of `id_recipes`.
+    #[instrument(name = "TagRepository::get_recipe_tags", skip_all)]
+    pub async fn get_recipe_tags(&self, id_recipes: &[i64]) -> Result<Vec<RecipeTag>, Error> {
+        let _timer = QueryTimer::new("tag", "get_recipe_tags");
+
+        if id_recipes.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let query = format!(r#"
+            SELECT rt.Recipes_idRecipe as recipes_id, t.Name as name
+            FROM RecipeTags rt
+            JOIN Tags t ON t.idTag = rt.Tags_idTag
+            WHERE rt.Recipes_idRecipe IN ({})
+            ORDER BY t.Name
+        "#, placeholders(id_recipes.len()));
+        let mut select = sqlx::query_as::<MySql, RecipeTag>(&query);
+
+        for id_recipe in id_recipes {
+            select = select.bind(id_recipe);
+        }
+
+        match select.fetch_all(&self.db_pool).await
+        {
+            Ok(tags) => Ok(tags),
+            Err(err) => {
+                log::error!("Error retrieving recipe tags from the database: {}", err);
+                Err(err)
+            }
+        }
+    }
+
+    /// Replaces the tags of a recipe, creating the ones that do not exist yet.
+    #[instrument(name = "TagRepository::set_recipe_tags", skip_all)]
+    pub async fn set_recipe_tags(&self, transaction: &mut Transaction<'_, MySql>, id_recipe: i64, names: &[String]) -> Result<(), Error> {
+        let _timer = QueryTimer::new("tag", "set_recipe_tags");
+        if let Err(err) = sqlx::query("DELETE FROM RecipeTags WHERE Recipes_idRecipe = ?")
+            .bind(id_recipe)
+            .execute(&mut **transaction)
+            .await
+        {
+            log::error!("Error deleting recipe tags in the database: {}", err);
+            return Err(err);
+        }
+
+        for name in names {
+            let result = async {
+                sqlx::query("INSERT IGNORE INTO Tags (Name) VALUES (?)")
+                    .bind(name)
+                    .execute(&mut **transaction)
+                    .await?;
+
+                sqlx::query("INSERT INTO RecipeTags (Recipes_idRecipe, Tags_idTag) SELECT ?, idTag FROM Tags WHERE Name = ?")
+                    .bind(id_recipe)
+                    .bind(name)
+                    .execute(&mut **transaction)
+                    .await
+            }
+            .await;
+
+            if let Err(err) = result {
+                log::error!("Error adding recipe tag to the database: {}", err);
+                return Err(err);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[instrument(name = "TagRepository::delete_recipe_tags", skip_all)]
+    pub async fn delete_recipe_tags(&self, transaction: &mut Transaction<'_, MySql>, id_recipe: i64) -> Result<(), Error> {
+        let _timer = QueryTimer::new("tag", "delete_recipe_tags");
+        let query = "DELETE FROM RecipeTags WHERE Recipes_idRecipe = ?";
+
+        match sqlx::query(query)
+            .bind(id_recipe)
+            .execute(&mut **transaction)
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                log::error!("Error deleting recipe tags in the database: {}", err);
+                Err(err)
+            }
+        }
+    }
+
+    /// Whether some recipe has the tag as its category.
+    #[instrument(name = "TagRepository::is_tag_a_category", skip_all)]
+    pub async fn is_tag_a_category(&self, id: i64) -> Result<bool, Error> {
+        let _timer = QueryTimer::new("tag", "is_tag_a_category");
+        let query = "SELECT COUNT(*) FROM Recipes r JOIN Tags t ON t.Name = LOWER(TRIM(r.Category)) WHERE t.idTag = ?";
+        let count: i64 = sqlx::query_scalar(query)
+            .bind(id)
+            .fetch_one(&self.db_pool)
+            .await?;
+
+        Ok(count > 0)
+    }
+
+    #[instrument(name = "TagRepository::tag_exist", skip_all)]
+    pub async fn tag_exist(&self, id: i64) -> Result<bool, Error> {
+        let _timer = QueryTimer::new("tag", "tag_exist");
+        let query = "SELECT COUNT(*) FROM Tags WHERE idTag = ?";
+        let count: i64 = sqlx::query_scalar(query)
+            .bind(id)
+            .fetch_one(&self.db_pool)
+            .await?;
+
+        Ok(count > 0)
+    }
+
+    #[instrument(name = "TagRepository::does_name_exist", skip_all)]
+    pub async fn does_name_exist(&self, name: &str) -> Result<bool, Error> {
+        let _timer = QueryTimer::new("tag", "does_name_exist");
+        let query = "SELECT COUNT(*) FROM Tags WHERE Name = ?";
+        let count: i64 = sqlx::query_scalar(query)
+            .bind(name)
+            .fetch_one(&self.db_pool)
+            .await?;
+
+        Ok(count > 0)
+    }
+
+    #[instrument(name = "TagRepository::does_name_exist_and_id", skip_all)]
+    pub async fn does_name_exist_and_id(&self, id_tag: i64, name: &str) -> Result<bool, Error> {
+        let _timer = QueryTimer::new("tag", "does_name_exist_and_id");
+        let query = "SELECT COUNT(*) FROM Tags WHERE Name = ? AND idTag != ?";
+        let count: i64 = sqlx::query_scalar(query)
+            .bind(name)
+            .bind(id_tag)
+            .fetch_one(&self.db_pool)
+            .await?;
+
+        Ok(count > 0)
+    }
+}
@@ -0,0 +1,131 @@
+use sqlx::{MySql, MySqlPool, Error};
+use tracing::instrument;
+
+use crate::data_access::entities::users::User;
+use crate::metrics::QueryTimer;
+
+pub struct UserRepository {
+    pub db_pool: MySqlPool,
+}
+
+impl UserRepository {
+    pub fn new(db_pool: MySqlPool) -> Self {
+        Self { db_pool }
+    }
+
+    #[instrument(name = "UserRepository::add_user", skip_all)]
+    pub async fn add_user(&self, user: &User) -> Result<i64, Error> {
+        let _timer = QueryTimer::new("user", "add_user");
+        let query = r#"
+            INSERT INTO Users (Username, PasswordHash, Role)
+            VALUES (?, ?, ?)
+        "#;
+
+        match sqlx::query(query)
+            .bind(&user.username)
+            .bind(&user.password_hash)
+            .bind(&user.role)
+            .execute(&self.db_pool)
+            .await
+        {
+            Ok(result) => Ok(result.last_insert_id() as i64),
+            Err(err) => {
+                log::error!("Error adding user to the database: {}", err);
+                Err(err)
+            }
+        }
+    }
+
+    #[instrument(name = "UserRepository::get_by_username", skip_all)]
+    pub async fn get_by_username(&self, username: &str) -> Result<Option<User>, Error> {
+        let _timer = QueryTimer::new("user", "get_by_username");
+        let query = "SELECT idUser as id_user, username, PasswordHash as password_hash, role FROM Users WHERE Username = ?";
+
+        match sqlx::query_as::<MySql, User>(query)
+            .bind(username)
+            .fetch_optional(&self.db_pool)
+            .await
+        {
+            Ok(user) => Ok(user),
+            Err(err) => {
+                log::error!("Error retrieving user from the database: {}", err);
+                Err(err)
+            }
+        }
+    }
+
+    #[instrument(name = "UserRepository::get_by_id", skip_all)]
+    pub async fn get_by_id(&self, id: i64) -> Result<Option<User>, Error> {
+        let _timer = QueryTimer::new("user", "get_by_id");
+        let query = "SELECT idUser as id_user, username, PasswordHash as password_hash, role FROM Users WHERE idUser = ?";
+
+        match sqlx::query_as::<MySql, User>(query)
+            .bind(id)
+            .fetch_optional(&self.db_pool)
+            .await
+        {
+            Ok(user) => Ok(user),
+            Err(err) => {
+                log::error!("Error retrieving user from the database: {}", err);
+                Err(err)
+            }
+        }
+    }
+
+    #[instrument(name = "UserRepository::get_all_users", skip_all)]
+    pub async fn get_all_users(&self) -> Result<Vec<User>, Error> {
+        let _timer = QueryTimer::new("user", "get_all_users");
+        match sqlx::query_as::<MySql, User>("SELECT idUser as id_user, username, PasswordHash as password_hash, role FROM Users ORDER BY idUser")
+            .fetch_all(&self.db_pool)
+            .await
+        {
+            Ok(users) => Ok(users),
+            Err(err) => {
+                log::error!("Error retrieving users from the database: {}", err);
+                Err(err)
+            }
+        }
+    }
+
+    #[instrument(name = "UserRepository::update_role", skip_all)]
+    pub async fn update_role(&self, id: i64, role: &str) -> Result<(), Error> {
+        let _timer = QueryTimer::new("user", "update_role");
+        let query = "UPDATE Users SET Role = ? WHERE idUser = ?";
+
+        match sqlx::query(query)
+            .bind(role)
+            .bind(id)
+            .execute(&self.db_pool)
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                log::error!("Error updating user role in the database: {}", err);
+                Err(err)
+            }
+        }
+    }
+
+    #[instrument(name = "UserRepository::count_users_with_role", skip_all)]
+    pub async fn count_users_with_role(&self, role: Option<&str>) -> Result<i64, Error> {
+        let _timer = QueryTimer::new("user", "count_users_with_role");
+        let query = "SELECT COUNT(*) FROM Users WHERE ? IS NULL OR Role = ?";
+        sqlx::query_scalar(query)
+            .bind(role)
+            .bind(role)
+            .fetch_one(&self.db_pool)
+            .await
+    }
+
+    #[instrument(name = "UserRepository::does_username_exist", skip_all)]
+    pub async fn does_username_exist(&self, username: &str) -> Result<bool, Error> {
+        let _timer = QueryTimer::new("user", "does_username_exist");
+        let query = "SELECT COUNT(*) FROM Users WHERE Username = ?";
+        let count: i64 = sqlx::query_scalar(query)
+            .bind(username)
+            .fetch_one(&self.db_pool)
+            .await?;
+
+        Ok(count > 0)
+    }
+}
@@ -1,3 +1,7 @@
 pub mod api;
 pub mod business;
-pub mod data_access;
\ No newline at end of file
+pub mod config;
+pub mod data_access;
+pub mod logging;
+pub mod metrics;
+pub mod telemetry;
\ No newline at end of file
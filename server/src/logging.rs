@@ -0,0 +1,98 @@
+use std::io::Write;
+
+use env_logger::{Builder, Env};
+use log::kv::{Key, Value, VisitSource};
+use serde::Deserialize;
+use serde_json::{json, Map};
+
+tokio::task_local! {
+    /// Id of the request being handled, set around every route handler.
+    pub static REQUEST_ID: String;
+}
+
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(|request_id| request_id.clone()).ok()
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json
+}
+
+/// Collects the structured fields of a record, e.g. those of the access log.
+struct Fields(Vec<(String, serde_json::Value)>);
+
+impl<'kvs> VisitSource<'kvs> for Fields {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), log::kv::Error> {
+        let value = if let Some(number) = value.to_u64() {
+            json!(number)
+        } else if let Some(number) = value.to_i64() {
+            json!(number)
+        } else if let Some(number) = value.to_f64() {
+            json!(number)
+        } else if let Some(flag) = value.to_bool() {
+            json!(flag)
+        } else {
+            json!(value.to_string())
+        };
+
+        self.0.push((key.to_string(), value));
+        Ok(())
+    }
+}
+
+/// Installs the logger. `RUST_LOG` filters as usual; every line carries the id of
+/// the request it was emitted for, if any.
+pub fn init(format: LogFormat) {
+    Builder::from_env(Env::default().default_filter_or("info"))
+        .format(move |buf, record| {
+            let mut fields = Fields(Vec::new());
+            let _ = record.key_values().visit(&mut fields);
+
+            let request_id = current_request_id().or_else(|| {
+                fields.0.iter()
+                    .position(|(key, _)| key == "request_id")
+                    .map(|index| fields.0.remove(index).1)
+                    .and_then(|value| value.as_str().map(str::to_string))
+            });
+
+            match format {
+                LogFormat::Json => {
+                    let mut line = Map::new();
+                    line.insert("timestamp".to_string(), json!(buf.timestamp_millis().to_string()));
+                    line.insert("level".to_string(), json!(record.level().as_str()));
+                    line.insert("target".to_string(), json!(record.target()));
+                    line.insert("request_id".to_string(), json!(request_id));
+                    line.insert("message".to_string(), json!(record.args().to_string()));
+                    for (key, value) in fields.0 {
+                        line.insert(key, value);
+                    }
+
+                    writeln!(buf, "{}", serde_json::Value::Object(line))
+                }
+                LogFormat::Text => {
+                    let fields: String = fields.0.iter()
+                        .map(|(key, value)| match value.as_str() {
+                            Some(text) => format!(" {}={}", key, text),
+                            None => format!(" {}={}", key, value)
+                        })
+                        .collect();
+
+                    writeln!(
+                        buf,
+                        "[{} {:<5} {}] [{}] {}{}",
+                        buf.timestamp(),
+                        record.level(),
+                        record.target(),
+                        request_id.as_deref().unwrap_or("-"),
+                        record.args(),
+                        fields
+                    )
+                }
+            }
+        })
+        .init();
+}
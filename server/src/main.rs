@@ -1,37 +1,159 @@
-use std::env;
-use env_logger::Env;
+use std::path::{Path, PathBuf};
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use clap::{Parser, Subcommand, ValueEnum};
+use rocket::figment::Figment;
+use sqlx::migrate::Migrate;
 use sqlx::mysql::MySqlPoolOptions;
+use sqlx::MySqlPool;
 
 use server::api;
+use server::data_access::MIGRATOR;
+use server::api::fairings::cors::Cors;
+use server::api::fairings::metrics::RequestMetrics;
+use server::api::fairings::rate_limit::RateLimiter;
+use server::api::fairings::request_id::{self, RequestId};
+use server::api::fairings::shutdown::GracefulShutdown;
+use server::config::{AppConfig, DatabaseConfig};
+use server::logging;
+use server::telemetry;
+use server::business::dtos::user_dto::UserCredentialsDTO;
+use server::business::food_datasets::{self, DatasetFormat};
+use server::business::services::food_import_service::{FoodImportAction, FoodImportService, FoodImportServiceTrait};
 use server::business::services::ingredient_service::{ IngredientServiceTrait, IngredientService};
 use server::business::services::mealplan_service::{ MealPlanService, MealPlanServiceTrait};
 use server::business::services::recipe_service::{ RecipeService, RecipeServiceTrait};
+use server::business::services::tag_service::{ TagService, TagServiceTrait};
+use server::business::services::user_service::{ UserError, UserService, UserServiceTrait};
+use server::business::services::api_key_service::{ ApiKeyService, ApiKeyServiceTrait};
+use server::business::services::health_service::{ HealthService, HealthServiceTrait};
+use server::business::services::metrics_service::{ MetricsService, MetricsServiceTrait};
+
+#[derive(Parser)]
+#[command(about = "REST API for meal plans")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Start the REST API (default)
+    Serve,
+    /// Import ingredients from a FoodData Central or CIQUAL dump
+    ImportFoods {
+        #[arg(long, value_enum)]
+        format: DatasetFormatArg,
+        /// FoodData Central JSON file or CSV directory, or CIQUAL CSV file
+        path: PathBuf,
+        /// Print what would change without writing to the database
+        #[arg(long)]
+        dry_run: bool
+    },
+    /// Create an admin account, reading its password from standard input
+    CreateAdmin {
+        username: String
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum DatasetFormatArg {
+    Fdc,
+    Ciqual
+}
+
+impl From<DatasetFormatArg> for DatasetFormat {
+    fn from(format: DatasetFormatArg) -> Self {
+        match format {
+            DatasetFormatArg::Fdc => DatasetFormat::FoodDataCentral,
+            DatasetFormatArg::Ciqual => DatasetFormat::Ciqual
+        }
+    }
+}
 
 #[tokio::main]
 async fn main() {    
-    env_logger::Builder::from_env(Env::default().default_filter_or("info")).init(); 
-    
-    dotenv::from_filename("database.env").ok();
-    let db_url: String = match env::var("DATABASE_URL") {
-        Ok(url) => url,
-        Err(_) => {
-            eprintln!("Error: DATABASE_URL environment variable not set");
+    let cli = Cli::parse();
+
+    let figment = AppConfig::figment();
+    let config = match AppConfig::from_figment(&figment) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            std::process::exit(1);
+        }
+    };
+
+    logging::init(config.logging.format);
+
+    let tracer_provider = match telemetry::init(config.logging.traces) {
+        Ok(provider) => provider,
+        Err(err) => {
+            eprintln!("Error: could not start tracing: {}", err);
             std::process::exit(1);
         }
     };
 
-    if let Err(err) = run(db_url.to_string()).await {
+    let result = match cli.command.unwrap_or(Command::Serve) {
+        Command::Serve => run(figment, config).await,
+        Command::ImportFoods { format, path, dry_run } => import_foods(&config.database, format.into(), &path, dry_run).await,
+        Command::CreateAdmin { username } => create_admin(&figment, &config, username).await
+    };
+
+    if let Some(provider) = tracer_provider {
+        if let Err(err) = provider.shutdown() {
+            log::error!("Error flushing traces: {}", err);
+        }
+    }
+
+    if let Err(err) = result {
         eprintln!("Error: {}", err);
         std::process::exit(1);
     }
 }
 
-async fn run(db_url: String) -> Result<(), sqlx::Error> {
+async fn connect(database: &DatabaseConfig) -> Result<MySqlPool, sqlx::Error> {
     let pool: sqlx::Pool<sqlx::MySql> = MySqlPoolOptions::new()
-        .max_connections(5)
-        .connect(&db_url)
+        .max_connections(database.max_connections)
+        .min_connections(database.min_connections)
+        .acquire_timeout(database.acquire_timeout())
+        .idle_timeout(database.idle_timeout())
+        .max_lifetime(database.max_lifetime())
+        .connect(&database.url)
         .await?;
 
+    Ok(pool)
+}
+
+/// Connects and brings the schema up to date.
+async fn connect_and_migrate(database: &DatabaseConfig) -> Result<MySqlPool, String> {
+    let pool = connect(database).await.map_err(|err| err.to_string())?;
+
+    MIGRATOR.run(&pool).await.map_err(|err| err.to_string())?;
+
+    Ok(pool)
+}
+
+/// Fails when the schema is behind, for commands that must not change it.
+async fn check_migrations(pool: &MySqlPool) -> Result<(), String> {
+    let mut connection = pool.acquire().await.map_err(|err| err.to_string())?;
+    let applied = connection.list_applied_migrations().await.map_err(|err| format!("could not read the applied migrations: {}", err))?;
+
+    let pending = MIGRATOR
+        .iter()
+        .filter(|migration| !migration.migration_type.is_down_migration())
+        .filter(|migration| !applied.iter().any(|applied| applied.version == migration.version))
+        .count();
+
+    if pending > 0 {
+        return Err(format!("{} database migrations are pending, run without --dry-run or start the server first", pending));
+    }
+
+    Ok(())
+}
+
+async fn run(figment: Figment, config: AppConfig) -> Result<(), String> {
+    let pool = connect_and_migrate(&config.database).await?;
+
     let ingredient_service: Box<dyn IngredientServiceTrait + Send + Sync> =
         Box::new(IngredientService::new(pool.clone()));
 
@@ -39,15 +161,151 @@ async fn run(db_url: String) -> Result<(), sqlx::Error> {
         Box::new(RecipeService::new(pool.clone()));
 
     let mealplan_service: Box<dyn MealPlanServiceTrait + Send + Sync> =
-        Box::new(MealPlanService::new(pool.clone()));
+        Box::new(MealPlanService::new(pool.clone(), config.meal_plans.custom_meal_types.clone()));
+
+    let tag_service: Box<dyn TagServiceTrait + Send + Sync> =
+        Box::new(TagService::new(pool.clone()));
+
+    let api_key_service: Box<dyn ApiKeyServiceTrait + Send + Sync> =
+        Box::new(ApiKeyService::new(pool.clone()));
+
+    let health_service: Box<dyn HealthServiceTrait + Send + Sync> =
+        Box::new(HealthService::new(pool.clone()));
+
+    let metrics_service: Box<dyn MetricsServiceTrait + Send + Sync> =
+        Box::new(MetricsService::new(pool.clone()));
 
-    let rocket = rocket::build()
-        .mount("/api", api::controllers::routes::routes())
+    // Signals are handled by `GracefulShutdown`, which drains before notifying Rocket.
+    let figment = figment
+        .merge(("shutdown.ctrlc", false))
+        .merge(("shutdown.signals", Vec::<String>::new()));
+
+    let rocket = rocket::custom(figment);
+
+    let user_service: Box<dyn UserServiceTrait + Send + Sync> =
+        Box::new(UserService::new(pool.clone(), &token_secret(rocket.figment()), config.features.registration));
+
+    let rocket = rocket
+        .mount("/api", request_id::scoped(api::controllers::routes::routes()))
+        .mount("/health", request_id::scoped(api::controllers::routes::health_routes()))
+        .attach(GracefulShutdown::new(pool.clone(), config.shutdown.drain()))
+        .attach(RequestId);
+
+    let rocket = if config.features.metrics {
+        rocket
+            .mount("/", request_id::scoped(api::controllers::routes::metrics_routes()))
+            .attach(RequestMetrics)
+            .manage(metrics_service)
+    } else {
+        rocket
+    };
+
+    let rocket = rocket
+        .attach(RateLimiter::new(config.rate_limit))
         .manage(ingredient_service)
         .manage(recipe_service)
-        .manage(mealplan_service);
+        .manage(tag_service)
+        .manage(mealplan_service)
+        .manage(user_service)
+        .manage(api_key_service)
+        .manage(health_service);
+
+    // Attached last so the preflight routes cover every mounted route.
+    let rocket = if config.cors.allowed_origins.is_empty() {
+        rocket
+    } else {
+        rocket.attach(Cors::new(config.cors.clone()))
+    };
+
+    let rocket = rocket.manage(config.cors);
+
+    rocket.launch().await.map_err(|err| err.to_string())?;
+
+    Ok(())
+}
+
+/// Key material for signing tokens: Rocket's `secret_key`, which release builds
+/// require. Debug builds without one get a random key, so tokens do not survive
+/// a restart.
+fn token_secret(figment: &Figment) -> Vec<u8> {
+    match figment.extract_inner::<String>("secret_key") {
+        Ok(secret_key) => secret_key.into_bytes(),
+        Err(_) => {
+            log::warn!("secret_key is not configured, issued tokens will be invalid after a restart");
+            let mut secret = vec![0u8; 64];
+            OsRng.fill_bytes(&mut secret);
+            secret
+        }
+    }
+}
+
+async fn import_foods(database: &DatabaseConfig, format: DatasetFormat, path: &Path, dry_run: bool) -> Result<(), String> {
+    let dataset = food_datasets::load(format, path).map_err(|err| format!("{}: {}", path.display(), err))?;
+
+    for skipped in &dataset.skipped {
+        log::warn!("Skipping {}", skipped);
+    }
+
+    let pool = if dry_run {
+        let pool = connect(database).await.map_err(|err| err.to_string())?;
+        check_migrations(&pool).await?;
+        pool
+    } else {
+        connect_and_migrate(database).await?
+    };
+    let food_import_service = FoodImportService::new(pool.clone());
+
+    let summary = food_import_service.import_foods(dataset.foods, dry_run).await
+        .map_err(|err| format!("{:?}", err))?;
+
+    for change in &summary.changes {
+        match &change.action {
+            FoodImportAction::Create if dry_run => println!("create  {} {}", change.source_id, change.name),
+            FoodImportAction::Update(differences) if dry_run => {
+                println!("update  {} {} ({})", change.source_id, change.name, differences.join(", "))
+            }
+            FoodImportAction::Skip(reason) => println!("skip    {} {}: {}", change.source_id, change.name, reason),
+            _ => {}
+        }
+    }
+
+    println!(
+        "{} created, {} updated, {} unchanged, {} skipped{}",
+        summary.count(|action| matches!(action, FoodImportAction::Create)),
+        summary.count(|action| matches!(action, FoodImportAction::Update(_))),
+        summary.count(|action| matches!(action, FoodImportAction::Unchanged)),
+        summary.count(|action| matches!(action, FoodImportAction::Skip(_))) + dataset.skipped.len(),
+        if dry_run { " (dry run, nothing was written)" } else { "" }
+    );
+
+    pool.close().await;
 
-    rocket.launch().await.unwrap();
-    
     Ok(())
-}
\ No newline at end of file
+}
+
+async fn create_admin(figment: &Figment, config: &AppConfig, username: String) -> Result<(), String> {
+    let mut password = String::new();
+    std::io::stdin().read_line(&mut password).map_err(|err| format!("could not read the password: {}", err))?;
+
+    let credentials = UserCredentialsDTO {
+        username,
+        password: password.trim_end_matches(['\r', '\n']).to_string()
+    };
+
+    let pool = connect_and_migrate(&config.database).await?;
+    let user_service = UserService::new(pool.clone(), &token_secret(figment), false);
+
+    let result = match user_service.create_admin(credentials).await {
+        Ok(user) => {
+            println!("admin {} created with id {}", user.username, user.id_user);
+            Ok(())
+        }
+        Err(UserError::ValidationError) => Err("the username must have 3 to 45 characters and the password 8 to 128".to_string()),
+        Err(UserError::DuplicateNameError) => Err("the username is already taken".to_string()),
+        Err(err) => Err(format!("{:?}", err))
+    };
+
+    pool.close().await;
+
+    result
+}
@@ -0,0 +1,115 @@
+use std::sync::OnceLock;
+use std::time::Instant;
+
+use prometheus::{Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry, TextEncoder};
+
+/// Process-wide Prometheus collectors, shared by the HTTP fairing, the
+/// repositories and the `/metrics` endpoint.
+pub struct Metrics {
+    registry: Registry,
+    pub http_requests: IntCounterVec,
+    pub http_request_duration: HistogramVec,
+    pub db_query_duration: HistogramVec,
+    pub db_queries_in_flight: IntGauge,
+    pub db_pool_connections: IntGaugeVec,
+    pub domain_items: IntGaugeVec
+}
+
+static METRICS: OnceLock<Metrics> = OnceLock::new();
+
+pub fn metrics() -> &'static Metrics {
+    METRICS.get_or_init(Metrics::new)
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let registry = Registry::new();
+
+        let http_requests = IntCounterVec::new(
+            Opts::new("http_requests_total", "HTTP requests by route and status"),
+            &["method", "route", "status"]
+        ).expect("valid metric");
+
+        let http_request_duration = HistogramVec::new(
+            HistogramOpts::new("http_request_duration_seconds", "HTTP request latency by route"),
+            &["method", "route"]
+        ).expect("valid metric");
+
+        let db_query_duration = HistogramVec::new(
+            HistogramOpts::new("db_query_duration_seconds", "Database time of each repository method")
+                .buckets(vec![0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5]),
+            &["repository", "method"]
+        ).expect("valid metric");
+
+        let db_queries_in_flight = IntGauge::new("db_queries_in_flight", "Repository methods currently running")
+            .expect("valid metric");
+
+        let db_pool_connections = IntGaugeVec::new(
+            Opts::new("db_pool_connections", "sqlx pool connections by state (size, idle, in_use, max, waiters)"),
+            &["state"]
+        ).expect("valid metric");
+
+        let domain_items = IntGaugeVec::new(
+            Opts::new("domain_items", "Stored items by kind"),
+            &["kind"]
+        ).expect("valid metric");
+
+        for collector in [
+            Box::new(http_requests.clone()) as Box<dyn prometheus::core::Collector>,
+            Box::new(http_request_duration.clone()),
+            Box::new(db_query_duration.clone()),
+            Box::new(db_queries_in_flight.clone()),
+            Box::new(db_pool_connections.clone()),
+            Box::new(domain_items.clone())
+        ] {
+            registry.register(collector).expect("metric registered once");
+        }
+
+        Metrics {
+            registry,
+            http_requests,
+            http_request_duration,
+            db_query_duration,
+            db_queries_in_flight,
+            db_pool_connections,
+            domain_items
+        }
+    }
+
+    /// All metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let mut buffer = Vec::new();
+
+        if let Err(err) = TextEncoder::new().encode(&self.registry.gather(), &mut buffer) {
+            log::error!("Error encoding metrics: {}", err);
+        }
+
+        String::from_utf8(buffer).unwrap_or_default()
+    }
+}
+
+/// Records how long a repository method takes, from creation until it is dropped.
+pub struct QueryTimer {
+    repository: &'static str,
+    method: &'static str,
+    started: Instant
+}
+
+impl QueryTimer {
+    pub fn new(repository: &'static str, method: &'static str) -> Self {
+        metrics().db_queries_in_flight.inc();
+
+        QueryTimer { repository, method, started: Instant::now() }
+    }
+}
+
+impl Drop for QueryTimer {
+    fn drop(&mut self) {
+        let metrics = metrics();
+
+        metrics.db_queries_in_flight.dec();
+        metrics.db_query_duration
+            .with_label_values(&[self.repository, self.method])
+            .observe(self.started.elapsed().as_secs_f64());
+    }
+}
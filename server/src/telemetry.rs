@@ -0,0 +1,53 @@
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry::{global, KeyValue};
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::TracerProvider;
+use opentelemetry_sdk::{runtime, Resource};
+use serde::Deserialize;
+use tracing_subscriber::layer::SubscriberExt;
+
+const SERVICE_NAME: &str = "rust-api-rest-rocket";
+
+/// Where the spans of requests, services and repository queries are sent.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TraceExporter {
+    #[default]
+    None,
+    #[serde(alias = "console")]
+    Stdout,
+    Otlp
+}
+
+/// Installs the global tracing subscriber exporting to `exporter`. The OTLP
+/// exporter sends over HTTP to `OTEL_EXPORTER_OTLP_ENDPOINT`, by default a local
+/// collector on `http://localhost:4318`. The returned provider must be shut down
+/// before exiting to flush pending spans.
+pub fn init(exporter: TraceExporter) -> Result<Option<TracerProvider>, String> {
+    let builder = TracerProvider::builder()
+        .with_resource(Resource::new(vec![KeyValue::new("service.name", SERVICE_NAME)]));
+
+    let provider = match exporter {
+        TraceExporter::None => return Ok(None),
+        TraceExporter::Stdout => builder
+            .with_simple_exporter(opentelemetry_stdout::SpanExporter::default())
+            .build(),
+        TraceExporter::Otlp => {
+            let exporter = opentelemetry_otlp::SpanExporter::builder()
+                .with_http()
+                .build()
+                .map_err(|err| err.to_string())?;
+
+            builder.with_batch_exporter(exporter, runtime::Tokio).build()
+        }
+    };
+
+    global::set_text_map_propagator(TraceContextPropagator::new());
+
+    let subscriber = tracing_subscriber::registry()
+        .with(tracing_opentelemetry::layer().with_tracer(provider.tracer(SERVICE_NAME)));
+
+    tracing::subscriber::set_global_default(subscriber).map_err(|err| err.to_string())?;
+
+    Ok(Some(provider))
+}